@@ -0,0 +1,80 @@
+//! Compares how fast the dense `Board` and the packed `BitBoard` step the same random
+//! soup, on a small and a large board. Run it with
+//! `cargo run --release --example board_representations`.
+
+use game_of_life::life::{
+    self, BitBoard, Board, BoundaryCondition, LifeBoard, Neighborhood, Ruleset,
+};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::time::Instant;
+
+const SEED: u64 = 0x5eed;
+const DENSITY: f64 = 0.3;
+const SIZES: [(i32, i32, u64); 2] = [(100, 100, 2000), (1000, 1000, 50)];
+
+fn main() {
+    let ruleset = Ruleset::default();
+
+    for (cols, rows, generations) in SIZES {
+        let mut rng = StdRng::seed_from_u64(SEED);
+        let cells: Vec<(i32, i32)> = (0..rows)
+            .flat_map(|y| (0..cols).map(move |x| (x, y)))
+            .filter(|_| rng.gen_bool(DENSITY))
+            .collect();
+
+        println!("{cols}x{rows}, {generations} generations:");
+
+        let dense = time_steps(
+            "Board",
+            &mut Board::default(),
+            cols,
+            rows,
+            generations,
+            &cells,
+            &ruleset,
+        );
+        let packed = time_steps(
+            "BitBoard",
+            &mut BitBoard::default(),
+            cols,
+            rows,
+            generations,
+            &cells,
+            &ruleset,
+        );
+
+        if dense != packed {
+            eprintln!("the two boards disagree after {generations} generations");
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Steps `board` from `cells` and prints its throughput, returning the hash of the
+/// final generation.
+fn time_steps(
+    name: &str,
+    board: &mut dyn LifeBoard,
+    cols: i32,
+    rows: i32,
+    generations: u64,
+    cells: &[(i32, i32)],
+    ruleset: &Ruleset,
+) -> u64 {
+    board.load_cells(cols, rows, &mut cells.iter().copied());
+
+    let start = Instant::now();
+    for _ in 0..generations {
+        board.step_life(BoundaryCondition::Wrap, Neighborhood::Moore, ruleset);
+    }
+    let elapsed = start.elapsed().as_secs_f64();
+
+    println!(
+        "  {name:<8} {:>10.1} generations/s, final population {}",
+        generations as f64 / elapsed,
+        board.population()
+    );
+
+    life::cells_hash(board.alive_cells())
+}
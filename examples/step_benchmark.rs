@@ -0,0 +1,123 @@
+//! Times one generation step through the dense `Board` and the sparse `step_into`, and
+//! a neighbor scan over squares kept in nested rows against one flat row-major `Vec`,
+//! like `SquareMap`. Run it with `cargo run --release --example step_benchmark`.
+
+use game_of_life::life::{self, Board, BoundaryCondition, Neighborhood, Ruleset, StepBuffers};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::HashSet;
+use std::hint::black_box;
+use std::time::Instant;
+
+const SEED: u64 = 0x5eed;
+/// A dense soup, and a sparse one like a few patterns on a large board.
+const RUNS: [(i32, i32, f64, u32); 3] = [
+    (100, 100, 0.3, 1000),
+    (1000, 1000, 0.3, 10),
+    (1000, 1000, 0.001, 10),
+];
+const SCANS: u32 = 200;
+
+fn main() {
+    let ruleset = Ruleset::default();
+
+    for (cols, rows, density, generations) in RUNS {
+        let mut rng = StdRng::seed_from_u64(SEED);
+        let cells: HashSet<(i32, i32)> = (0..rows)
+            .flat_map(|y| (0..cols).map(move |x| (x, y)))
+            .filter(|_| rng.gen_bool(density))
+            .collect();
+
+        println!(
+            "{cols}x{rows} at density {density}, {generations} generations, starting population {}:",
+            cells.len()
+        );
+
+        let mut board = Board::default();
+        board.load(cols, rows, cells.iter().copied());
+        let dense = time("dense", "generations", generations, || {
+            board.step(BoundaryCondition::Wrap, Neighborhood::Moore, &ruleset)
+        });
+
+        let mut buffers = StepBuffers::default();
+        let mut current = cells.clone();
+        let mut next = HashSet::new();
+        let sparse = time("sparse", "generations", generations, || {
+            life::step_into(
+                &current,
+                cols,
+                rows,
+                BoundaryCondition::Wrap,
+                Neighborhood::Moore,
+                &ruleset,
+                &mut buffers,
+                &mut next,
+            );
+            std::mem::swap(&mut current, &mut next);
+        });
+
+        println!("  sparse runs at {:.2}x the speed of dense", dense / sparse);
+
+        if board.live_cells().collect::<HashSet<_>>() != current {
+            eprintln!("the two steps disagree after {generations} generations");
+            std::process::exit(1);
+        }
+    }
+
+    scan_square_maps(100, 100);
+}
+
+/// Runs `step` `runs` times, prints the throughput and returns the seconds each run
+/// took.
+fn time(name: &str, unit: &str, runs: u32, mut step: impl FnMut()) -> f64 {
+    let start = Instant::now();
+    for _ in 0..runs {
+        step();
+    }
+    let per_run = start.elapsed().as_secs_f64() / runs as f64;
+
+    println!("  {name:<7} {:>10.1} {unit}/s", 1. / per_run);
+
+    per_run
+}
+
+/// Counts the live neighbors of every square the way the sprite renderer looks squares
+/// up, once through a `Vec` per row and once through a single flat `Vec`.
+fn scan_square_maps(cols: i32, rows: i32) {
+    let mut rng = StdRng::seed_from_u64(SEED);
+    let flat: Vec<bool> = (0..cols * rows).map(|_| rng.gen_bool(0.3)).collect();
+    let nested: Vec<Vec<bool>> = flat.chunks(cols as usize).map(|row| row.to_vec()).collect();
+
+    println!("{cols}x{rows} neighbor scan, {SCANS} passes:");
+
+    let nested = time("nested", "passes", SCANS, || {
+        black_box(count_live_neighbors(cols, rows, |x, y| {
+            nested[y as usize][x as usize]
+        }));
+    });
+    let flat = time("flat", "passes", SCANS, || {
+        black_box(count_live_neighbors(cols, rows, |x, y| {
+            flat[(y * cols + x) as usize]
+        }));
+    });
+
+    println!("  flat runs at {:.2}x the speed of nested", nested / flat);
+}
+
+fn count_live_neighbors(cols: i32, rows: i32, is_alive: impl Fn(i32, i32) -> bool) -> u32 {
+    let mut total = 0;
+
+    for y in 0..rows {
+        for x in 0..cols {
+            for &(dx, dy) in Neighborhood::Moore.offsets() {
+                let (nx, ny) = (x + dx, y + dy);
+
+                if (0..cols).contains(&nx) && (0..rows).contains(&ny) && is_alive(nx, ny) {
+                    total += 1;
+                }
+            }
+        }
+    }
+
+    total
+}
@@ -0,0 +1,75 @@
+use crate::rle::{MAX_PATTERN_SIZE, Pattern};
+
+/// Decodes a plaintext `.cells` pattern, where `O` is alive and `.` is dead.
+///
+/// ```
+/// use game_of_life::cells;
+///
+/// let block = cells::decode("!Name: Block\nOO\nOO\n").unwrap();
+/// assert_eq!((block.width, block.height), (2, 2));
+/// assert_eq!(block.cells.len(), 4);
+/// ```
+pub fn decode(input: &str) -> Result<Pattern, String> {
+    let mut cells = Vec::new();
+    let mut width = 0;
+    let mut height = 0;
+
+    for (y, line) in input
+        .lines()
+        .filter(|line| !line.starts_with('!'))
+        .enumerate()
+    {
+        let line = line.trim_end();
+
+        for (x, c) in line.chars().enumerate() {
+            match c {
+                '.' => {}
+                'O' => cells.push((x as i32, y as i32)),
+                _ => return Err(format!("unexpected character `{c}` on row {y}")),
+            }
+        }
+
+        if !line.is_empty() {
+            width = width.max(line.chars().count() as i32);
+            height = y as i32 + 1;
+        }
+
+        if width > MAX_PATTERN_SIZE || height > MAX_PATTERN_SIZE {
+            return Err(format!(
+                "pattern is larger than {MAX_PATTERN_SIZE}x{MAX_PATTERN_SIZE}"
+            ));
+        }
+    }
+
+    Ok(Pattern {
+        width,
+        height,
+        cells,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_live_cells_and_skips_comments() {
+        let glider = decode("!Name: Glider\n!\n.O.\n..O\nOOO\n").unwrap();
+
+        assert_eq!((glider.width, glider.height), (3, 3));
+        assert_eq!(glider.cells, [(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)]);
+    }
+
+    #[test]
+    fn trailing_blank_rows_do_not_add_height() {
+        let blinker = decode("OOO\n...\n\n").unwrap();
+
+        assert_eq!((blinker.width, blinker.height), (3, 2));
+        assert_eq!(decode("OOO  \n\n").unwrap().height, 1);
+    }
+
+    #[test]
+    fn rejects_unexpected_characters() {
+        assert!(decode("O*O\n").is_err());
+    }
+}
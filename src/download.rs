@@ -0,0 +1,154 @@
+//! Fetches patterns for `--load-url` with a plain HTTP/1.0 GET. There is no TLS, so
+//! only `http://` URLs work; save `https://` patterns to a file and use `--load`.
+
+use std::io::{Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::time::Duration;
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+/// Far larger than any pattern collection file, so a bad URL cannot fill the memory.
+const MAX_RESPONSE_BYTES: u64 = 16 * 1024 * 1024;
+
+/// Downloads the body of `url`, which must answer `200 OK` with UTF-8 text.
+pub(super) fn get(url: &str) -> Result<String, String> {
+    let (host, port, path) = parse_url(url)?;
+    let address = (host, port)
+        .to_socket_addrs()
+        .map_err(|err| format!("failed to resolve `{host}`: {err}"))?
+        .next()
+        .ok_or_else(|| format!("`{host}` has no address"))?;
+
+    let mut stream =
+        TcpStream::connect_timeout(&address, TIMEOUT).map_err(|err| err.to_string())?;
+    stream
+        .set_read_timeout(Some(TIMEOUT))
+        .and_then(|()| stream.set_write_timeout(Some(TIMEOUT)))
+        .map_err(|err| err.to_string())?;
+
+    // HTTP/1.0 keeps servers from answering with a chunked body.
+    write!(
+        stream,
+        "GET {path} HTTP/1.0\r\nHost: {host}\r\nUser-Agent: game-of-life\r\nConnection: close\r\n\r\n"
+    )
+    .map_err(|err| err.to_string())?;
+
+    let mut response = Vec::new();
+    stream
+        .take(MAX_RESPONSE_BYTES + 1)
+        .read_to_end(&mut response)
+        .map_err(|err| err.to_string())?;
+
+    if response.len() as u64 > MAX_RESPONSE_BYTES {
+        return Err(format!(
+            "response is larger than {MAX_RESPONSE_BYTES} bytes"
+        ));
+    }
+
+    body(&response)
+}
+
+/// Splits an `http://host[:port][/path]` URL into its parts.
+fn parse_url(url: &str) -> Result<(&str, u16, &str), String> {
+    let Some(rest) = url.strip_prefix("http://") else {
+        return Err(format!(
+            "unsupported URL `{url}`, only `http://` URLs can be loaded"
+        ));
+    };
+    let (authority, path) = match rest.find('/') {
+        Some(index) => rest.split_at(index),
+        None => (rest, "/"),
+    };
+    let (host, port) = match authority.rsplit_once(':') {
+        Some((host, port)) => (
+            host,
+            port.parse()
+                .map_err(|_| format!("invalid port `{port}` in URL `{url}`"))?,
+        ),
+        None => (authority, 80),
+    };
+
+    if host.is_empty() {
+        return Err(format!("missing host in URL `{url}`"));
+    }
+
+    Ok((host, port, path))
+}
+
+/// The body of a raw HTTP response, or an error naming the status if it is not 200.
+fn body(response: &[u8]) -> Result<String, String> {
+    let response =
+        std::str::from_utf8(response).map_err(|_| "response is not UTF-8 text".to_string())?;
+    let (head, body) = response
+        .split_once("\r\n\r\n")
+        .ok_or("response has no body")?;
+    let status = head.lines().next().unwrap_or_default();
+
+    match status.split_whitespace().nth(1) {
+        Some("200") => Ok(body.to_string()),
+        _ => Err(format!("server answered `{status}`")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+
+    #[test]
+    fn parses_host_port_and_path() {
+        assert_eq!(
+            parse_url("http://example.com/patterns/glider.rle"),
+            Ok(("example.com", 80, "/patterns/glider.rle"))
+        );
+        assert_eq!(
+            parse_url("http://127.0.0.1:8080"),
+            Ok(("127.0.0.1", 8080, "/"))
+        );
+    }
+
+    #[test]
+    fn rejects_urls_it_cannot_fetch() {
+        assert!(parse_url("https://example.com/glider.rle").is_err());
+        assert!(parse_url("http://example.com:http/").is_err());
+        assert!(parse_url("http:///glider.rle").is_err());
+        assert!(parse_url("glider.rle").is_err());
+    }
+
+    #[test]
+    fn only_ok_responses_have_a_body() {
+        assert_eq!(
+            body(b"HTTP/1.1 200 OK\r\nContent-Length: 3\r\n\r\n3o!"),
+            Ok("3o!".to_string())
+        );
+        assert_eq!(
+            body(b"HTTP/1.1 404 Not Found\r\n\r\nmissing"),
+            Err("server answered `HTTP/1.1 404 Not Found`".to_string())
+        );
+    }
+
+    #[test]
+    fn downloads_from_a_local_server() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = [0; 1024];
+            let read = stream.read(&mut request).unwrap();
+            stream
+                .write_all(b"HTTP/1.0 200 OK\r\n\r\nx = 3, y = 1\n3o!\n")
+                .unwrap();
+
+            String::from_utf8_lossy(&request[..read]).into_owned()
+        });
+
+        let body = get(&format!("http://127.0.0.1:{port}/blinker.rle")).unwrap();
+
+        assert_eq!(body, "x = 3, y = 1\n3o!\n");
+        assert!(
+            server
+                .join()
+                .unwrap()
+                .starts_with("GET /blinker.rle HTTP/1.0\r\n")
+        );
+    }
+}
@@ -0,0 +1,187 @@
+/// Maps the squares of a `cols` by `rows` board to world-space positions and back.
+///
+/// Square (0, 0) is the top-left one and y grows downwards. The board is laid out
+/// so that its centre sits half a square up and to the left of the world origin.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct GridGeometry {
+    pub cols: i32,
+    pub rows: i32,
+    pub cell_width: f32,
+    pub cell_height: f32,
+}
+
+impl GridGeometry {
+    pub fn new(cols: i32, rows: i32, cell_width: f32, cell_height: f32) -> Self {
+        GridGeometry {
+            cols,
+            rows,
+            cell_width,
+            cell_height,
+        }
+    }
+
+    /// World position of the centre of square (`x`, `y`). Squares outside the board
+    /// continue the same layout.
+    ///
+    /// ```
+    /// use game_of_life::geometry::GridGeometry;
+    ///
+    /// let geometry = GridGeometry::new(4, 2, 10., 10.);
+    /// assert_eq!(geometry.cell_to_world(0, 0), (-20., 10.));
+    /// assert_eq!(geometry.cell_to_world(3, 1), (10., 0.));
+    /// ```
+    pub fn cell_to_world(&self, x: i32, y: i32) -> (f32, f32) {
+        (
+            -self.cols as f32 * self.cell_width / 2. + x as f32 * self.cell_width,
+            self.rows as f32 * self.cell_height / 2. - y as f32 * self.cell_height,
+        )
+    }
+
+    /// The square containing a world position, or `None` if it is off the board.
+    ///
+    /// A position exactly on the line between two squares belongs to the one to its
+    /// right or below it, so the board covers its left and top edges but not its right
+    /// and bottom ones.
+    ///
+    /// ```
+    /// use game_of_life::geometry::GridGeometry;
+    ///
+    /// let geometry = GridGeometry::new(4, 2, 10., 10.);
+    /// assert_eq!(geometry.world_to_cell((-20., 10.)), Some((0, 0)));
+    /// assert_eq!(geometry.world_to_cell((100., 0.)), None);
+    /// ```
+    pub fn world_to_cell(&self, position: (f32, f32)) -> Option<(i32, i32)> {
+        let (x, y) = self.unclamped_cell(position)?;
+
+        (x >= 0 && y >= 0 && x < self.cols && y < self.rows).then_some((x, y))
+    }
+
+    /// Like [`world_to_cell`](Self::world_to_cell), but positions off the board map to
+    /// the nearest square on its edge.
+    ///
+    /// ```
+    /// use game_of_life::geometry::GridGeometry;
+    ///
+    /// let geometry = GridGeometry::new(4, 2, 10., 10.);
+    /// assert_eq!(geometry.nearest_cell((100., -100.)), (3, 1));
+    /// ```
+    pub fn nearest_cell(&self, position: (f32, f32)) -> (i32, i32) {
+        let (x, y) = self.unclamped_cell(position).unwrap_or((0, 0));
+
+        (x.clamp(0, self.cols - 1), y.clamp(0, self.rows - 1))
+    }
+
+    /// World position of the top-left corner of the board.
+    pub fn top_left(&self) -> (f32, f32) {
+        let (x, y) = self.cell_to_world(0, 0);
+
+        (x - self.cell_width / 2., y + self.cell_height / 2.)
+    }
+
+    /// World position of the centre of the board.
+    pub fn center(&self) -> (f32, f32) {
+        (-self.cell_width / 2., self.cell_height / 2.)
+    }
+
+    /// Width and height of the whole board in world units.
+    pub fn size(&self) -> (f32, f32) {
+        (
+            self.cols as f32 * self.cell_width,
+            self.rows as f32 * self.cell_height,
+        )
+    }
+
+    /// Centre and size of the block of squares between two corners, inclusive.
+    ///
+    /// ```
+    /// use game_of_life::geometry::GridGeometry;
+    ///
+    /// let geometry = GridGeometry::new(4, 2, 10., 10.);
+    /// assert_eq!(geometry.cells_rect((0, 0), (1, 1)), ((-15., 5.), (20., 20.)));
+    /// ```
+    pub fn cells_rect(
+        &self,
+        (min_x, min_y): (i32, i32),
+        (max_x, max_y): (i32, i32),
+    ) -> ((f32, f32), (f32, f32)) {
+        let (left, top) = self.top_left();
+        let width = (max_x - min_x + 1) as f32 * self.cell_width;
+        let height = (max_y - min_y + 1) as f32 * self.cell_height;
+
+        (
+            (
+                left + min_x as f32 * self.cell_width + width / 2.,
+                top - min_y as f32 * self.cell_height - height / 2.,
+            ),
+            (width, height),
+        )
+    }
+
+    fn unclamped_cell(&self, (x, y): (f32, f32)) -> Option<(i32, i32)> {
+        let (left, top) = self.top_left();
+        let cell_x = ((x - left) / self.cell_width).floor();
+        let cell_y = ((top - y) / self.cell_height).floor();
+
+        (cell_x.is_finite() && cell_y.is_finite()).then_some((cell_x as i32, cell_y as i32))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Squares are 10 units wide, so the board spans x from -25 to 15 and y from 15
+    /// down to -5.
+    fn geometry() -> GridGeometry {
+        GridGeometry::new(4, 2, 10., 10.)
+    }
+
+    #[test]
+    fn square_centres_map_back_to_their_square() {
+        let geometry = geometry();
+
+        for (x, y) in [(0, 0), (3, 0), (0, 1), (3, 1)] {
+            assert_eq!(
+                geometry.world_to_cell(geometry.cell_to_world(x, y)),
+                Some((x, y))
+            );
+        }
+    }
+
+    #[test]
+    fn positions_just_inside_an_edge_select_that_square() {
+        let geometry = geometry();
+
+        assert_eq!(geometry.world_to_cell((-15.01, 5.01)), Some((0, 0)));
+        assert_eq!(geometry.world_to_cell((-14.99, 4.99)), Some((1, 1)));
+    }
+
+    #[test]
+    fn lines_between_squares_go_right_and_down() {
+        let geometry = geometry();
+
+        assert_eq!(geometry.world_to_cell((-15., 5.)), Some((1, 1)));
+        assert_eq!(geometry.world_to_cell((-25., 15.)), Some((0, 0)));
+    }
+
+    #[test]
+    fn positions_off_the_board_have_no_square() {
+        let geometry = geometry();
+
+        assert_eq!(geometry.world_to_cell((-25.01, 0.)), None);
+        assert_eq!(geometry.world_to_cell((0., 15.01)), None);
+        assert_eq!(geometry.world_to_cell((15., 0.)), None);
+        assert_eq!(geometry.world_to_cell((0., -5.)), None);
+        assert_eq!(geometry.world_to_cell((f32::NAN, 0.)), None);
+    }
+
+    #[test]
+    fn positions_off_the_board_clamp_to_the_nearest_edge_square() {
+        let geometry = geometry();
+
+        assert_eq!(geometry.nearest_cell((-100., 100.)), (0, 0));
+        assert_eq!(geometry.nearest_cell((100., -100.)), (3, 1));
+        assert_eq!(geometry.nearest_cell((0., 8.)), (2, 0));
+        assert_eq!(geometry.nearest_cell((-12., 100.)), (1, 0));
+    }
+}
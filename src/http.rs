@@ -0,0 +1,129 @@
+//! A small status endpoint for dashboards and scripts, built with the `http` feature.
+//! `--http-port PORT` serves the current generation, population and state as JSON on
+//! `127.0.0.1:PORT` from a background thread, which reads whatever the game last
+//! published into a shared [`StatusEndpoint`].
+
+use super::{GameState, Generation, Paused, Population};
+use bevy::prelude::*;
+use serde::Serialize;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// How many generations a headless run steps between status updates.
+pub(super) const HEADLESS_STATUS_INTERVAL: u64 = 64;
+/// Requests are answered one at a time, so a client that stops sending or reading
+/// is dropped after this long instead of blocking everyone behind it.
+const CLIENT_TIMEOUT: Duration = Duration::from_secs(2);
+
+#[derive(Clone, Default, Serialize)]
+struct Status {
+    generation: u64,
+    population: usize,
+    state: &'static str,
+}
+
+#[derive(Resource, Clone)]
+pub(super) struct StatusEndpoint(Arc<Mutex<Status>>);
+
+impl StatusEndpoint {
+    /// Starts answering requests on `127.0.0.1:port` until the process exits.
+    pub(super) fn serve(port: u16) -> io::Result<Self> {
+        let listener = TcpListener::bind(("127.0.0.1", port))?;
+        let endpoint = StatusEndpoint(Arc::default());
+        let status = endpoint.0.clone();
+
+        std::thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                if let Err(err) = respond(stream, &status) {
+                    eprintln!("failed to answer a status request: {err}");
+                }
+            }
+        });
+
+        Ok(endpoint)
+    }
+
+    pub(super) fn publish(&self, generation: u64, population: usize, state: &'static str) {
+        if let Ok(mut status) = self.0.lock() {
+            *status = Status {
+                generation,
+                population,
+                state,
+            };
+        }
+    }
+}
+
+/// Serves the endpoint for the interactive app when `port` is set. Runs before the
+/// plugins are added, so failures go straight to standard error.
+pub(super) fn add_status_endpoint(app: &mut App, port: Option<u16>) {
+    let Some(port) = port else {
+        return;
+    };
+
+    match StatusEndpoint::serve(port) {
+        Ok(endpoint) => {
+            eprintln!("serving status on http://127.0.0.1:{port}/");
+            app.insert_resource(endpoint)
+                .add_systems(Update, publish_status);
+        }
+        Err(err) => eprintln!("failed to serve status on port {port}: {err}"),
+    }
+}
+
+fn publish_status(
+    endpoint: Res<StatusEndpoint>,
+    generation: Res<Generation>,
+    population: Res<Population>,
+    game_state: Res<State<GameState>>,
+    paused: Res<Paused>,
+) {
+    let state = match (game_state.get(), paused.0) {
+        (GameState::Placing, _) => "placing",
+        (GameState::Ongoing, true) => "paused",
+        (GameState::Ongoing, false) => "running",
+    };
+
+    endpoint.publish(generation.0, population.0, state);
+}
+
+fn respond(stream: TcpStream, status: &Mutex<Status>) -> io::Result<()> {
+    stream.set_read_timeout(Some(CLIENT_TIMEOUT))?;
+    stream.set_write_timeout(Some(CLIENT_TIMEOUT))?;
+
+    let mut reader = BufReader::new(stream);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+
+    // The headers are not needed, but they have to be read before answering.
+    let mut header = String::new();
+    while reader.read_line(&mut header)? > 2 {
+        header.clear();
+    }
+
+    let mut parts = request_line.split_whitespace();
+    let (code, body) = match (parts.next(), parts.next()) {
+        (Some("GET"), Some("/" | "/status")) => {
+            let status = status
+                .lock()
+                .map(|status| status.clone())
+                .unwrap_or_default();
+            let json = serde_json::to_string(&status).map_err(io::Error::other)?;
+
+            ("200 OK", json)
+        }
+        (Some("GET"), _) => ("404 Not Found", r#"{"error":"not found"}"#.to_string()),
+        _ => (
+            "405 Method Not Allowed",
+            r#"{"error":"only GET is supported"}"#.to_string(),
+        ),
+    };
+
+    write!(
+        reader.get_mut(),
+        "HTTP/1.1 {code}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nAccess-Control-Allow-Origin: *\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    )
+}
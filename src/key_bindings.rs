@@ -0,0 +1,328 @@
+use bevy::prelude::{ButtonInput, KeyCode, Resource};
+use std::collections::HashMap;
+use toml_edit::{DocumentMut, Item};
+
+/// Something a key press does. Digits, Ctrl, Shift and Alt keep their meaning whatever
+/// the bindings say, so they are not listed here.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Action {
+    Start,
+    Pause,
+    Step,
+    Rewind,
+    Clear,
+    Random,
+    Quit,
+    Faster,
+    Slower,
+    MoreSteps,
+    FewerSteps,
+    PanLeft,
+    PanRight,
+    PanUp,
+    PanDown,
+    ResetCamera,
+    Fit,
+    ZoomBoard,
+    Zoom1x,
+    Zoom2x,
+    Zoom4x,
+    ClusterTour,
+    Follow,
+    CursorLeft,
+    CursorRight,
+    CursorUp,
+    CursorDown,
+    CursorToggle,
+    ImmortalBrush,
+    PaintColor,
+    RotateClockwise,
+    RotateCounterclockwise,
+    MirrorHorizontally,
+    MirrorVertically,
+    BrushSmaller,
+    BrushLarger,
+    Undo,
+    Redo,
+    Copy,
+    Paste,
+    Save,
+    Load,
+    Fullscreen,
+    ControlPanel,
+    Minimap,
+    PopulationGraph,
+    Diagnostics,
+    GridLines,
+    Ruler,
+    Screenshot,
+    Theme,
+    CellShape,
+    Trails,
+    NeighborOverlay,
+    SmoothCells,
+}
+
+/// Every action with its name in the bindings file and the keys it starts out on.
+const ACTIONS: &[(Action, &str, &[KeyCode])] = &[
+    (Action::Start, "start", &[KeyCode::Space]),
+    (Action::Pause, "pause", &[KeyCode::KeyP]),
+    (Action::Step, "step", &[KeyCode::Period]),
+    (Action::Rewind, "rewind", &[KeyCode::Backspace]),
+    (Action::Clear, "clear", &[KeyCode::KeyC]),
+    (Action::Random, "random", &[KeyCode::KeyR]),
+    (Action::Quit, "quit", &[KeyCode::Escape]),
+    (
+        Action::Faster,
+        "faster",
+        &[KeyCode::Equal, KeyCode::NumpadAdd],
+    ),
+    (
+        Action::Slower,
+        "slower",
+        &[KeyCode::Minus, KeyCode::NumpadSubtract],
+    ),
+    (Action::MoreSteps, "more_steps", &[KeyCode::PageUp]),
+    (Action::FewerSteps, "fewer_steps", &[KeyCode::PageDown]),
+    (Action::PanLeft, "pan_left", &[KeyCode::KeyA]),
+    (Action::PanRight, "pan_right", &[KeyCode::KeyD]),
+    (Action::PanUp, "pan_up", &[KeyCode::KeyW]),
+    (Action::PanDown, "pan_down", &[KeyCode::KeyS]),
+    (Action::ResetCamera, "reset_camera", &[KeyCode::Home]),
+    (Action::Fit, "fit", &[KeyCode::KeyF]),
+    (Action::ZoomBoard, "zoom_board", &[KeyCode::F5]),
+    (Action::Zoom1x, "zoom_1x", &[KeyCode::F6]),
+    (Action::Zoom2x, "zoom_2x", &[KeyCode::F7]),
+    (Action::Zoom4x, "zoom_4x", &[KeyCode::F8]),
+    (Action::ClusterTour, "cluster_tour", &[KeyCode::Tab]),
+    (Action::Follow, "follow", &[KeyCode::KeyJ]),
+    (Action::CursorLeft, "cursor_left", &[KeyCode::ArrowLeft]),
+    (Action::CursorRight, "cursor_right", &[KeyCode::ArrowRight]),
+    (Action::CursorUp, "cursor_up", &[KeyCode::ArrowUp]),
+    (Action::CursorDown, "cursor_down", &[KeyCode::ArrowDown]),
+    (
+        Action::CursorToggle,
+        "cursor_toggle",
+        &[KeyCode::Enter, KeyCode::NumpadEnter],
+    ),
+    (Action::ImmortalBrush, "immortal_brush", &[KeyCode::KeyX]),
+    (Action::PaintColor, "paint_color", &[KeyCode::KeyU]),
+    (
+        Action::RotateClockwise,
+        "rotate_clockwise",
+        &[KeyCode::BracketRight],
+    ),
+    (
+        Action::RotateCounterclockwise,
+        "rotate_counterclockwise",
+        &[KeyCode::BracketLeft],
+    ),
+    (
+        Action::MirrorHorizontally,
+        "mirror_horizontally",
+        &[KeyCode::KeyH],
+    ),
+    (
+        Action::MirrorVertically,
+        "mirror_vertically",
+        &[KeyCode::KeyV],
+    ),
+    (
+        Action::BrushSmaller,
+        "brush_smaller",
+        &[KeyCode::BracketLeft],
+    ),
+    (
+        Action::BrushLarger,
+        "brush_larger",
+        &[KeyCode::BracketRight],
+    ),
+    (Action::Undo, "undo", &[KeyCode::KeyZ]),
+    (Action::Redo, "redo", &[KeyCode::KeyY]),
+    (Action::Copy, "copy", &[KeyCode::KeyC]),
+    (Action::Paste, "paste", &[KeyCode::KeyV]),
+    (Action::Save, "save", &[KeyCode::KeyS]),
+    (Action::Load, "load", &[KeyCode::KeyO]),
+    (Action::Fullscreen, "fullscreen", &[KeyCode::F11]),
+    (Action::ControlPanel, "control_panel", &[KeyCode::KeyK]),
+    (Action::Minimap, "minimap", &[KeyCode::KeyM]),
+    (
+        Action::PopulationGraph,
+        "population_graph",
+        &[KeyCode::KeyI],
+    ),
+    (Action::Diagnostics, "diagnostics", &[KeyCode::F3]),
+    (Action::GridLines, "grid_lines", &[KeyCode::KeyG]),
+    (Action::Ruler, "ruler", &[KeyCode::KeyE]),
+    (Action::Screenshot, "screenshot", &[KeyCode::F12]),
+    (Action::Theme, "theme", &[KeyCode::KeyT]),
+    (Action::CellShape, "cell_shape", &[KeyCode::KeyO]),
+    (Action::Trails, "trails", &[KeyCode::KeyL]),
+    (
+        Action::NeighborOverlay,
+        "neighbor_overlay",
+        &[KeyCode::KeyN],
+    ),
+    (Action::SmoothCells, "smooth_cells", &[KeyCode::KeyQ]),
+];
+
+/// Names a key is written as in the bindings file and shown as in the status bar.
+const KEY_NAMES: &[(&str, KeyCode)] = &[
+    ("A", KeyCode::KeyA),
+    ("B", KeyCode::KeyB),
+    ("C", KeyCode::KeyC),
+    ("D", KeyCode::KeyD),
+    ("E", KeyCode::KeyE),
+    ("F", KeyCode::KeyF),
+    ("G", KeyCode::KeyG),
+    ("H", KeyCode::KeyH),
+    ("I", KeyCode::KeyI),
+    ("J", KeyCode::KeyJ),
+    ("K", KeyCode::KeyK),
+    ("L", KeyCode::KeyL),
+    ("M", KeyCode::KeyM),
+    ("N", KeyCode::KeyN),
+    ("O", KeyCode::KeyO),
+    ("P", KeyCode::KeyP),
+    ("Q", KeyCode::KeyQ),
+    ("R", KeyCode::KeyR),
+    ("S", KeyCode::KeyS),
+    ("T", KeyCode::KeyT),
+    ("U", KeyCode::KeyU),
+    ("V", KeyCode::KeyV),
+    ("W", KeyCode::KeyW),
+    ("X", KeyCode::KeyX),
+    ("Y", KeyCode::KeyY),
+    ("Z", KeyCode::KeyZ),
+    ("F1", KeyCode::F1),
+    ("F2", KeyCode::F2),
+    ("F3", KeyCode::F3),
+    ("F4", KeyCode::F4),
+    ("F5", KeyCode::F5),
+    ("F6", KeyCode::F6),
+    ("F7", KeyCode::F7),
+    ("F8", KeyCode::F8),
+    ("F9", KeyCode::F9),
+    ("F10", KeyCode::F10),
+    ("F11", KeyCode::F11),
+    ("F12", KeyCode::F12),
+    ("Space", KeyCode::Space),
+    ("Enter", KeyCode::Enter),
+    ("Esc", KeyCode::Escape),
+    ("Tab", KeyCode::Tab),
+    ("Backspace", KeyCode::Backspace),
+    ("Delete", KeyCode::Delete),
+    ("Insert", KeyCode::Insert),
+    ("Home", KeyCode::Home),
+    ("End", KeyCode::End),
+    ("PgUp", KeyCode::PageUp),
+    ("PgDn", KeyCode::PageDown),
+    ("Left", KeyCode::ArrowLeft),
+    ("Right", KeyCode::ArrowRight),
+    ("Up", KeyCode::ArrowUp),
+    ("Down", KeyCode::ArrowDown),
+    ("[", KeyCode::BracketLeft),
+    ("]", KeyCode::BracketRight),
+    ("-", KeyCode::Minus),
+    ("=", KeyCode::Equal),
+    (".", KeyCode::Period),
+    (",", KeyCode::Comma),
+    ("/", KeyCode::Slash),
+    (";", KeyCode::Semicolon),
+    ("'", KeyCode::Quote),
+    ("`", KeyCode::Backquote),
+    ("\\", KeyCode::Backslash),
+    ("NumpadEnter", KeyCode::NumpadEnter),
+    ("NumpadAdd", KeyCode::NumpadAdd),
+    ("NumpadSubtract", KeyCode::NumpadSubtract),
+    ("NumpadMultiply", KeyCode::NumpadMultiply),
+    ("NumpadDivide", KeyCode::NumpadDivide),
+];
+
+/// The keys each [`Action`] is on. Several actions can share a key when they never apply
+/// at the same time, like C clearing the board and Ctrl+C copying the selection, or [
+/// rotating a stamp and Shift+[ shrinking the brush.
+#[derive(Resource, Clone)]
+pub struct KeyBindings(HashMap<Action, Vec<KeyCode>>);
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        KeyBindings(
+            ACTIONS
+                .iter()
+                .map(|&(action, _, keys)| (action, keys.to_vec()))
+                .collect(),
+        )
+    }
+}
+
+impl KeyBindings {
+    pub fn keys(&self, action: Action) -> &[KeyCode] {
+        self.0.get(&action).map_or(&[], Vec::as_slice)
+    }
+
+    pub fn pressed(&self, input: &ButtonInput<KeyCode>, action: Action) -> bool {
+        input.any_pressed(self.keys(action).iter().copied())
+    }
+
+    pub fn just_pressed(&self, input: &ButtonInput<KeyCode>, action: Action) -> bool {
+        input.any_just_pressed(self.keys(action).iter().copied())
+    }
+
+    /// The first key `action` is on, for the status bar.
+    pub fn label(&self, action: Action) -> &'static str {
+        self.keys(action)
+            .first()
+            .map_or("unbound", |&key| key_name(key))
+    }
+}
+
+fn key_name(key: KeyCode) -> &'static str {
+    KEY_NAMES
+        .iter()
+        .find(|&&(_, named)| named == key)
+        .map_or("?", |&(name, _)| name)
+}
+
+/// Reads bindings, keeping the default keys for any action the file leaves out. Each
+/// action takes a key name or a list of them, and an empty list unbinds it.
+pub fn decode(input: &str) -> Result<KeyBindings, String> {
+    let document: DocumentMut = input.parse().map_err(|err| format!("{err}"))?;
+    let mut bindings = KeyBindings::default();
+
+    for (name, item) in document.iter() {
+        let &(action, _, _) = ACTIONS
+            .iter()
+            .find(|&&(_, action_name, _)| action_name == name)
+            .ok_or_else(|| format!("unknown action `{name}`"))?;
+
+        bindings.0.insert(action, keys(name, item)?);
+    }
+
+    Ok(bindings)
+}
+
+fn keys(action: &str, item: &Item) -> Result<Vec<KeyCode>, String> {
+    let names: Vec<&str> = if let Some(name) = item.as_str() {
+        vec![name]
+    } else if let Some(array) = item.as_array() {
+        array
+            .iter()
+            .map(|name| name.as_str())
+            .collect::<Option<_>>()
+            .ok_or_else(|| format!("`{action}` must list key names"))?
+    } else {
+        return Err(format!("`{action}` must be a key name or a list of them"));
+    };
+
+    names
+        .into_iter()
+        .map(|name| {
+            KEY_NAMES
+                .iter()
+                .find(|&&(key_name, _)| key_name.eq_ignore_ascii_case(name))
+                .map(|&(_, key)| key)
+                .ok_or_else(|| format!("unknown key `{name}` for `{action}`"))
+        })
+        .collect()
+}
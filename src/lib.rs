@@ -0,0 +1,9 @@
+//! Core of the Game of Life simulation, independent of the Bevy front-end.
+
+pub mod cells;
+pub mod geometry;
+pub mod life;
+pub mod pattern_file;
+pub mod patterns;
+pub mod rle;
+pub mod terminal;
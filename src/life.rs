@@ -0,0 +1,1660 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+pub struct Ruleset {
+    pub birth: HashSet<u32>,
+    pub survival: HashSet<u32>,
+    /// How many states a cell can be in, two for Life-like rules. Generations rules have
+    /// more: a live cell that does not survive goes through `states - 2` dying states,
+    /// where it neither counts as a neighbor nor can be born, before it is dead.
+    pub states: u8,
+}
+
+impl Default for Ruleset {
+    fn default() -> Self {
+        Ruleset {
+            birth: HashSet::from([3]),
+            survival: HashSet::from([2, 3]),
+            states: 2,
+        }
+    }
+}
+
+impl fmt::Display for Ruleset {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut birth: Vec<_> = self.birth.iter().collect();
+        let mut survival: Vec<_> = self.survival.iter().collect();
+        birth.sort();
+        survival.sort();
+
+        write!(f, "B")?;
+        for count in birth {
+            write!(f, "{count}")?;
+        }
+
+        write!(f, "/S")?;
+        for count in survival {
+            write!(f, "{count}")?;
+        }
+
+        if self.states > 2 {
+            write!(f, "/C{}", self.states)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum Neighborhood {
+    #[default]
+    Moore,
+    VonNeumann,
+}
+
+const MOORE_OFFSETS: [(i32, i32); 8] = [
+    (-1, -1),
+    (-1, 0),
+    (-1, 1),
+    (0, -1),
+    (0, 1),
+    (1, -1),
+    (1, 0),
+    (1, 1),
+];
+const VON_NEUMANN_OFFSETS: [(i32, i32); 4] = [(-1, 0), (0, -1), (0, 1), (1, 0)];
+
+impl Neighborhood {
+    pub fn offsets(self) -> &'static [(i32, i32)] {
+        match self {
+            Neighborhood::Moore => &MOORE_OFFSETS,
+            Neighborhood::VonNeumann => &VON_NEUMANN_OFFSETS,
+        }
+    }
+
+    pub fn max_neighbors(self) -> u32 {
+        self.offsets().len() as u32
+    }
+}
+
+/// The transition rules the board follows. Life-like automata use a [`Ruleset`], while
+/// Brian's Brain has fixed rules and a third, dying state.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum Automaton {
+    #[default]
+    Life,
+    BriansBrain,
+}
+
+/// Parses an automaton name, either `life` or `brians-brain`.
+///
+/// ```
+/// use game_of_life::life::{self, Automaton};
+///
+/// assert_eq!(life::parse_automaton("brians-brain"), Ok(Automaton::BriansBrain));
+/// ```
+pub fn parse_automaton(name: &str) -> Result<Automaton, String> {
+    match name.to_ascii_lowercase().as_str() {
+        "life" => Ok(Automaton::Life),
+        "brians-brain" | "briansbrain" => Ok(Automaton::BriansBrain),
+        _ => Err(format!(
+            "invalid automaton `{name}`, expected `life` or `brians-brain`"
+        )),
+    }
+}
+
+/// What lies beyond the edges of the board when counting neighbors.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum BoundaryCondition {
+    /// Squares off the board are dead.
+    #[default]
+    Dead,
+    /// Squares off the board are alive, so edge squares always have live neighbors.
+    Alive,
+    /// The board wraps around into a torus.
+    Wrap,
+}
+
+/// Parses a boundary condition name, either `dead`, `alive` or `wrap`.
+///
+/// ```
+/// use game_of_life::life::{self, BoundaryCondition};
+///
+/// assert_eq!(life::parse_boundary("wrap"), Ok(BoundaryCondition::Wrap));
+/// assert!(life::parse_boundary("mirror").is_err());
+/// ```
+pub fn parse_boundary(name: &str) -> Result<BoundaryCondition, String> {
+    match name.to_ascii_lowercase().as_str() {
+        "dead" => Ok(BoundaryCondition::Dead),
+        "alive" => Ok(BoundaryCondition::Alive),
+        "wrap" => Ok(BoundaryCondition::Wrap),
+        _ => Err(format!(
+            "invalid boundary `{name}`, expected `dead`, `alive` or `wrap`"
+        )),
+    }
+}
+
+/// Parses a neighborhood name, either `moore` or `von-neumann`.
+///
+/// ```
+/// use game_of_life::life::{self, Neighborhood};
+///
+/// assert_eq!(life::parse_neighborhood("von-neumann"), Ok(Neighborhood::VonNeumann));
+/// ```
+pub fn parse_neighborhood(name: &str) -> Result<Neighborhood, String> {
+    match name.to_ascii_lowercase().as_str() {
+        "moore" => Ok(Neighborhood::Moore),
+        "von-neumann" | "vonneumann" => Ok(Neighborhood::VonNeumann),
+        _ => Err(format!(
+            "invalid neighborhood `{name}`, expected `moore` or `von-neumann`"
+        )),
+    }
+}
+
+/// Checks that a rule only uses neighbor counts the neighborhood can reach.
+///
+/// ```
+/// use game_of_life::life::{self, Neighborhood};
+///
+/// let rule = life::parse_rule("B5/S23").unwrap();
+/// assert!(life::check_rule(&rule, Neighborhood::VonNeumann).is_err());
+/// ```
+pub fn check_rule(ruleset: &Ruleset, neighborhood: Neighborhood) -> Result<(), String> {
+    let max_neighbors = neighborhood.max_neighbors();
+
+    match ruleset
+        .birth
+        .iter()
+        .chain(ruleset.survival.iter())
+        .find(|&&count| count > max_neighbors)
+    {
+        Some(count) => Err(format!(
+            "rule `{ruleset}` uses {count} neighbors, but the {neighborhood:?} neighborhood only has {max_neighbors}"
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Parses a rule in B/S notation, such as `B36/S23` for HighLife, optionally followed by
+/// a number of states for a Generations rule, such as `B2/S345/C4` for Star Wars.
+///
+/// ```
+/// use game_of_life::life;
+///
+/// let highlife = life::parse_rule("B36/S23").unwrap();
+/// assert_eq!(highlife.to_string(), "B36/S23");
+///
+/// let star_wars = life::parse_rule("B2/S345/C4").unwrap();
+/// assert_eq!(star_wars.states, 4);
+/// assert_eq!(star_wars.to_string(), "B2/S345/C4");
+/// assert!(life::parse_rule("B2/S345/C1").is_err());
+/// ```
+pub fn parse_rule(notation: &str) -> Result<Ruleset, String> {
+    let mut birth = None;
+    let mut survival = None;
+    let mut states = None;
+
+    for part in notation.split('/') {
+        let mut chars = part.chars();
+        let target = match chars.next().map(|c| c.to_ascii_uppercase()) {
+            Some('B') => &mut birth,
+            Some('S') => &mut survival,
+            Some('C') => {
+                let count = match chars.as_str().parse::<u8>() {
+                    Ok(count) if count >= 2 => count,
+                    _ => {
+                        return Err(format!(
+                            "invalid number of states `{}` in rule `{notation}`, expected 2 to 255",
+                            chars.as_str()
+                        ));
+                    }
+                };
+
+                if states.replace(count).is_some() {
+                    return Err(format!("duplicate section `{part}` in rule `{notation}`"));
+                }
+
+                continue;
+            }
+            _ => {
+                return Err(format!(
+                    "invalid rule `{notation}`, expected B<digits>/S<digits>"
+                ));
+            }
+        };
+
+        let counts = chars
+            .map(|c| match c.to_digit(10) {
+                Some(count) if count <= 8 => Ok(count),
+                _ => Err(format!("invalid neighbor count `{c}` in rule `{notation}`")),
+            })
+            .collect::<Result<HashSet<u32>, String>>()?;
+
+        if target.replace(counts).is_some() {
+            return Err(format!("duplicate section `{part}` in rule `{notation}`"));
+        }
+    }
+
+    match (birth, survival) {
+        (Some(birth), Some(survival)) => Ok(Ruleset {
+            birth,
+            survival,
+            states: states.unwrap_or(2),
+        }),
+        _ => Err(format!(
+            "invalid rule `{notation}`, expected B<digits>/S<digits>"
+        )),
+    }
+}
+
+/// Counts the live neighbors of every square next to at least one live cell, plus
+/// every edge square when the boundary is alive.
+///
+/// ```
+/// use std::collections::HashSet;
+/// use game_of_life::life::{self, BoundaryCondition, Neighborhood};
+///
+/// let pair = HashSet::from([(0, 0), (1, 0)]);
+/// let counts = life::neighbor_counts(&pair, 3, 3, BoundaryCondition::Dead, Neighborhood::Moore);
+/// assert_eq!(counts[&(1, 1)], 2);
+/// assert_eq!(counts[&(0, 0)], 1);
+///
+/// // A live boundary also counts the squares off the board, here five of them for a
+/// // corner.
+/// let counts = life::neighbor_counts(&pair, 3, 3, BoundaryCondition::Alive, Neighborhood::Moore);
+/// assert_eq!(counts[&(0, 0)], 6);
+/// assert_eq!(counts[&(2, 2)], 5);
+/// assert_eq!(counts.get(&(1, 1)), Some(&2));
+/// ```
+pub fn neighbor_counts(
+    cells: &HashSet<(i32, i32)>,
+    cols: i32,
+    rows: i32,
+    boundary: BoundaryCondition,
+    neighborhood: Neighborhood,
+) -> HashMap<(i32, i32), u32> {
+    let mut neighbor_counts = HashMap::new();
+    count_neighbors_into(
+        cells,
+        cols,
+        rows,
+        boundary,
+        neighborhood,
+        &mut neighbor_counts,
+    );
+
+    neighbor_counts
+}
+
+fn count_neighbors_into(
+    cells: &HashSet<(i32, i32)>,
+    cols: i32,
+    rows: i32,
+    boundary: BoundaryCondition,
+    neighborhood: Neighborhood,
+    neighbor_counts: &mut HashMap<(i32, i32), u32>,
+) {
+    neighbor_counts.clear();
+
+    for &(x, y) in cells {
+        for neighbor in neighbors(x, y, cols, rows, boundary, neighborhood) {
+            *neighbor_counts.entry(neighbor).or_insert(0) += 1;
+        }
+    }
+
+    // A live boundary gives every edge square extra neighbors, whether or not any cell
+    // is alive near it.
+    if boundary == BoundaryCondition::Alive {
+        for y in 0..rows {
+            let mut xs: Vec<i32> = if y == 0 || y == rows - 1 {
+                (0..cols).collect()
+            } else {
+                vec![0, cols - 1]
+            };
+            xs.dedup();
+
+            for x in xs {
+                let off_board = off_board_neighbors(x, y, cols, rows, boundary, neighborhood);
+
+                if off_board > 0 {
+                    *neighbor_counts.entry((x, y)).or_insert(0) += off_board;
+                }
+            }
+        }
+    }
+}
+
+/// The distinct squares on a `cols` by `rows` grid next to (`x`, `y`).
+///
+/// ```
+/// use game_of_life::life::{self, BoundaryCondition, Neighborhood};
+///
+/// let neighbors = |cols, rows, boundary| life::neighbors(0, 0, cols, rows, boundary, Neighborhood::Moore);
+/// assert_eq!(neighbors(3, 3, BoundaryCondition::Dead).len(), 3);
+/// assert_eq!(neighbors(3, 3, BoundaryCondition::Alive).len(), 3);
+/// assert_eq!(neighbors(3, 3, BoundaryCondition::Wrap).len(), 8);
+/// assert_eq!(neighbors(2, 2, BoundaryCondition::Wrap).len(), 3);
+/// ```
+pub fn neighbors(
+    x: i32,
+    y: i32,
+    cols: i32,
+    rows: i32,
+    boundary: BoundaryCondition,
+    neighborhood: Neighborhood,
+) -> Vec<(i32, i32)> {
+    let mut neighbors = Vec::with_capacity(8);
+
+    for &(dx, dy) in neighborhood.offsets() {
+        let mut neighbor_x = x + dx;
+        let mut neighbor_y = y + dy;
+
+        if boundary == BoundaryCondition::Wrap {
+            neighbor_x = (neighbor_x + cols) % cols;
+            neighbor_y = (neighbor_y + rows) % rows;
+        } else if neighbor_x < 0 || neighbor_y < 0 || neighbor_x >= cols || neighbor_y >= rows {
+            continue;
+        }
+
+        let neighbor = (neighbor_x, neighbor_y);
+
+        // On a wrapped grid narrower than three cells, several offsets land on the
+        // same square (or on the cell itself), which must only be counted once.
+        if neighbor != (x, y) && !neighbors.contains(&neighbor) {
+            neighbors.push(neighbor);
+        }
+    }
+
+    neighbors
+}
+
+/// How many live neighbors (`x`, `y`) has off the edge of the board. That is only ever
+/// non-zero under [`BoundaryCondition::Alive`].
+///
+/// ```
+/// use game_of_life::life::{self, BoundaryCondition, Neighborhood};
+///
+/// let alive = BoundaryCondition::Alive;
+/// assert_eq!(life::off_board_neighbors(0, 0, 4, 4, alive, Neighborhood::Moore), 5);
+/// assert_eq!(life::off_board_neighbors(1, 0, 4, 4, alive, Neighborhood::Moore), 3);
+/// assert_eq!(life::off_board_neighbors(1, 1, 4, 4, alive, Neighborhood::Moore), 0);
+/// assert_eq!(life::off_board_neighbors(0, 0, 4, 4, BoundaryCondition::Dead, Neighborhood::Moore), 0);
+/// ```
+pub fn off_board_neighbors(
+    x: i32,
+    y: i32,
+    cols: i32,
+    rows: i32,
+    boundary: BoundaryCondition,
+    neighborhood: Neighborhood,
+) -> u32 {
+    if boundary != BoundaryCondition::Alive {
+        return 0;
+    }
+
+    neighborhood
+        .offsets()
+        .iter()
+        .filter(|&&(dx, dy)| {
+            let (neighbor_x, neighbor_y) = (x + dx, y + dy);
+
+            neighbor_x < 0 || neighbor_y < 0 || neighbor_x >= cols || neighbor_y >= rows
+        })
+        .count() as u32
+}
+
+/// A 64-bit FNV-1a hash of a set of live cells. It does not depend on the order the
+/// cells are given in, and it is the same on every platform and build, so runs can be
+/// compared against a known value.
+///
+/// ```
+/// use game_of_life::life;
+///
+/// let blinker = [(1, 0), (1, 1), (1, 2)];
+/// assert_eq!(life::cells_hash(blinker), life::cells_hash([(1, 2), (1, 0), (1, 1)]));
+/// assert_ne!(life::cells_hash(blinker), life::cells_hash([(0, 1), (1, 1), (2, 1)]));
+/// assert_eq!(life::cells_hash([]), 0xcbf29ce484222325);
+/// ```
+pub fn cells_hash(cells: impl IntoIterator<Item = (i32, i32)>) -> u64 {
+    let mut cells: Vec<_> = cells.into_iter().collect();
+    cells.sort_unstable();
+
+    cells
+        .iter()
+        .flat_map(|&(x, y)| x.to_le_bytes().into_iter().chain(y.to_le_bytes()))
+        .fold(0xcbf29ce484222325, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+        })
+}
+
+/// Splits live cells into clusters of cells that touch, diagonally included. Clusters are
+/// ordered by their topmost, then leftmost cell, and the cells in each are sorted the
+/// same way.
+///
+/// ```
+/// use game_of_life::life;
+///
+/// let glider_and_block = [(1, 0), (2, 1), (0, 2), (1, 2), (2, 2), (6, 5), (7, 5), (6, 6), (7, 6)];
+/// let clusters = life::clusters(glider_and_block);
+///
+/// assert_eq!(clusters.len(), 2);
+/// assert_eq!(clusters[0].len(), 5);
+/// assert_eq!(clusters[1], vec![(6, 5), (7, 5), (6, 6), (7, 6)]);
+/// ```
+pub fn clusters(cells: impl IntoIterator<Item = (i32, i32)>) -> Vec<Vec<(i32, i32)>> {
+    let mut remaining: HashSet<(i32, i32)> = cells.into_iter().collect();
+    let mut starts: Vec<(i32, i32)> = remaining.iter().copied().collect();
+    starts.sort_unstable_by_key(|&(x, y)| (y, x));
+
+    let mut clusters = Vec::new();
+    let mut stack = Vec::new();
+
+    for start in starts {
+        if !remaining.remove(&start) {
+            continue;
+        }
+
+        let mut cluster = vec![start];
+        stack.push(start);
+
+        while let Some((x, y)) = stack.pop() {
+            for &(dx, dy) in Neighborhood::Moore.offsets() {
+                let neighbor = (x + dx, y + dy);
+
+                if remaining.remove(&neighbor) {
+                    cluster.push(neighbor);
+                    stack.push(neighbor);
+                }
+            }
+        }
+
+        cluster.sort_unstable_by_key(|&(x, y)| (y, x));
+        clusters.push(cluster);
+    }
+
+    clusters
+}
+
+/// Scratch space kept between calls to [`step_into`] so that stepping a large board
+/// does not allocate a new neighbor map every generation.
+#[derive(Default)]
+pub struct StepBuffers {
+    neighbor_counts: HashMap<(i32, i32), u32>,
+}
+
+impl StepBuffers {
+    /// How many squares the last [`step_into`] call had to look at, which is every
+    /// square next to a live cell.
+    pub fn candidates(&self) -> usize {
+        self.neighbor_counts.len()
+    }
+}
+
+/// Advances the live cells of a `cols` by `rows` grid by one generation. Only the live
+/// cells are kept, so this ignores the dying states of Generations rules.
+///
+/// ```
+/// use std::collections::HashSet;
+/// use game_of_life::life::{self, BoundaryCondition, Neighborhood, Ruleset};
+///
+/// let blinker = HashSet::from([(1, 0), (1, 1), (1, 2)]);
+/// let next = life::step(&blinker, 3, 3, BoundaryCondition::Dead, Neighborhood::Moore, &Ruleset::default());
+/// assert_eq!(next, HashSet::from([(0, 1), (1, 1), (2, 1)]));
+/// ```
+pub fn step(
+    cells: &HashSet<(i32, i32)>,
+    cols: i32,
+    rows: i32,
+    boundary: BoundaryCondition,
+    neighborhood: Neighborhood,
+    ruleset: &Ruleset,
+) -> HashSet<(i32, i32)> {
+    let mut next = HashSet::new();
+    step_into(
+        cells,
+        cols,
+        rows,
+        boundary,
+        neighborhood,
+        ruleset,
+        &mut StepBuffers::default(),
+        &mut next,
+    );
+
+    next
+}
+
+/// Like [`step`], but writes the next generation into `next`, reusing its allocation
+/// and the one in `buffers`.
+///
+/// ```
+/// use std::collections::HashSet;
+/// use game_of_life::life::{self, BoundaryCondition, Neighborhood, Ruleset, StepBuffers};
+///
+/// let mut buffers = StepBuffers::default();
+/// let mut blinker = HashSet::from([(1, 0), (1, 1), (1, 2)]);
+/// let mut next = HashSet::new();
+///
+/// for _ in 0..2 {
+///     life::step_into(&blinker, 3, 3, BoundaryCondition::Dead, Neighborhood::Moore, &Ruleset::default(), &mut buffers, &mut next);
+///     std::mem::swap(&mut blinker, &mut next);
+/// }
+/// assert_eq!(blinker, HashSet::from([(1, 0), (1, 1), (1, 2)]));
+/// ```
+#[allow(clippy::too_many_arguments)]
+pub fn step_into(
+    cells: &HashSet<(i32, i32)>,
+    cols: i32,
+    rows: i32,
+    boundary: BoundaryCondition,
+    neighborhood: Neighborhood,
+    ruleset: &Ruleset,
+    buffers: &mut StepBuffers,
+    next: &mut HashSet<(i32, i32)>,
+) {
+    let neighbor_counts = &mut buffers.neighbor_counts;
+    count_neighbors_into(cells, cols, rows, boundary, neighborhood, neighbor_counts);
+
+    let survivors = cells.iter().copied().filter(|cell| {
+        ruleset
+            .survival
+            .contains(neighbor_counts.get(cell).unwrap_or(&0))
+    });
+    let births = neighbor_counts
+        .iter()
+        .filter(|(cell, count)| !cells.contains(cell) && ruleset.birth.contains(count))
+        .map(|(cell, _)| *cell);
+
+    next.clear();
+    next.extend(survivors.chain(births));
+}
+
+/// A dense board that computes each generation from a frozen front buffer into a back
+/// buffer, then swaps the two, so every cell updates simultaneously.
+///
+/// ```
+/// use game_of_life::life::{Board, BoundaryCondition, Neighborhood, Ruleset};
+///
+/// let mut board = Board::default();
+/// board.load(3, 3, [(1, 0), (1, 1), (1, 2)]);
+/// board.step(BoundaryCondition::Dead, Neighborhood::Moore, &Ruleset::default());
+/// assert_eq!(board.live_cells().collect::<Vec<_>>(), [(0, 1), (1, 1), (2, 1)]);
+/// ```
+#[derive(Default)]
+pub struct Board {
+    cols: i32,
+    rows: i32,
+    front: Vec<CellState>,
+    back: Vec<CellState>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum CellState {
+    #[default]
+    Dead,
+    Alive,
+    /// Used by Brian's Brain and Generations rules for cells that stopped being alive,
+    /// with how many generations ago that was, starting from 1.
+    Dying(u8),
+}
+
+impl Board {
+    /// Replaces the board with a `cols` by `rows` grid where only `cells` are alive,
+    /// reusing the existing buffers when they are large enough.
+    pub fn load(&mut self, cols: i32, rows: i32, cells: impl IntoIterator<Item = (i32, i32)>) {
+        let len = (cols.max(0) as usize)
+            .checked_mul(rows.max(0) as usize)
+            .expect("the board has more squares than fit in memory");
+
+        self.cols = cols;
+        self.rows = rows;
+        self.front.clear();
+        self.front.resize(len, CellState::Dead);
+        self.back.resize(len, CellState::Dead);
+
+        for (x, y) in cells {
+            self.set(x, y, CellState::Alive);
+        }
+    }
+
+    pub fn state(&self, x: i32, y: i32) -> CellState {
+        self.index(x, y)
+            .map_or(CellState::Dead, |index| self.front[index])
+    }
+
+    /// Sets a single cell, ignoring positions outside the board.
+    pub fn set(&mut self, x: i32, y: i32, state: CellState) {
+        if let Some(index) = self.index(x, y) {
+            self.front[index] = state;
+        }
+    }
+
+    pub fn is_alive(&self, x: i32, y: i32) -> bool {
+        self.state(x, y) == CellState::Alive
+    }
+
+    /// Every cell of the current generation, row by row from the top-left.
+    pub fn states(&self) -> &[CellState] {
+        &self.front
+    }
+
+    pub fn cells(&self, state: CellState) -> impl Iterator<Item = (i32, i32)> + '_ {
+        let cols = self.cols;
+
+        self.front
+            .iter()
+            .enumerate()
+            .filter(move |(_, cell)| **cell == state)
+            .map(move |(index, _)| (index as i32 % cols, index as i32 / cols))
+    }
+
+    pub fn live_cells(&self) -> impl Iterator<Item = (i32, i32)> + '_ {
+        self.cells(CellState::Alive)
+    }
+
+    /// Every dying cell, with how many generations ago it stopped being alive.
+    pub fn dying_cells(&self) -> impl Iterator<Item = ((i32, i32), u8)> + '_ {
+        let cols = self.cols;
+
+        self.front
+            .iter()
+            .enumerate()
+            .filter_map(move |(index, cell)| match *cell {
+                CellState::Dying(stage) => {
+                    Some(((index as i32 % cols, index as i32 / cols), stage))
+                }
+                _ => None,
+            })
+    }
+
+    /// Advances the board one generation under `ruleset`, sending cells that do not
+    /// survive through its dying states when it is a Generations rule.
+    ///
+    /// ```
+    /// use game_of_life::life::{self, Board, BoundaryCondition, CellState, Neighborhood};
+    ///
+    /// let star_wars = life::parse_rule("B2/S345/C4").unwrap();
+    /// let mut board = Board::default();
+    /// board.load(4, 4, [(1, 1)]);
+    ///
+    /// board.step(BoundaryCondition::Dead, Neighborhood::Moore, &star_wars);
+    /// assert_eq!(board.state(1, 1), CellState::Dying(1));
+    /// ```
+    pub fn step(
+        &mut self,
+        boundary: BoundaryCondition,
+        neighborhood: Neighborhood,
+        ruleset: &Ruleset,
+    ) {
+        for y in 0..self.rows {
+            for x in 0..self.cols {
+                let live_neighbors = self.live_neighbors(x, y, boundary, neighborhood);
+                let index = (y * self.cols + x) as usize;
+
+                self.back[index] = match self.front[index] {
+                    CellState::Alive if ruleset.survival.contains(&live_neighbors) => {
+                        CellState::Alive
+                    }
+                    CellState::Alive if ruleset.states > 2 => CellState::Dying(1),
+                    CellState::Dying(stage) if stage + 2 < ruleset.states => {
+                        CellState::Dying(stage + 1)
+                    }
+                    CellState::Dead if ruleset.birth.contains(&live_neighbors) => CellState::Alive,
+                    _ => CellState::Dead,
+                };
+            }
+        }
+
+        std::mem::swap(&mut self.front, &mut self.back);
+    }
+
+    /// Advances the board one generation under Brian's Brain: dead cells with exactly
+    /// two live neighbors fire, live cells start dying, and dying cells die.
+    ///
+    /// ```
+    /// use game_of_life::life::{Board, BoundaryCondition, CellState, Neighborhood};
+    ///
+    /// let mut board = Board::default();
+    /// board.load(4, 4, [(1, 1), (2, 1)]);
+    /// board.step_brians_brain(BoundaryCondition::Dead, Neighborhood::Moore);
+    ///
+    /// assert_eq!(board.state(1, 1), CellState::Dying(1));
+    /// assert_eq!(board.state(1, 0), CellState::Alive);
+    /// ```
+    pub fn step_brians_brain(&mut self, boundary: BoundaryCondition, neighborhood: Neighborhood) {
+        for y in 0..self.rows {
+            for x in 0..self.cols {
+                let index = (y * self.cols + x) as usize;
+
+                self.back[index] = match self.front[index] {
+                    CellState::Alive => CellState::Dying(1),
+                    CellState::Dying(_) => CellState::Dead,
+                    CellState::Dead if self.live_neighbors(x, y, boundary, neighborhood) == 2 => {
+                        CellState::Alive
+                    }
+                    CellState::Dead => CellState::Dead,
+                };
+            }
+        }
+
+        std::mem::swap(&mut self.front, &mut self.back);
+    }
+
+    fn live_neighbors(
+        &self,
+        x: i32,
+        y: i32,
+        boundary: BoundaryCondition,
+        neighborhood: Neighborhood,
+    ) -> u32 {
+        let mut neighbors = [(0, 0); 8];
+        let mut neighbor_count = 0;
+        let mut off_board = 0;
+
+        for &(dx, dy) in neighborhood.offsets() {
+            let mut neighbor_x = x + dx;
+            let mut neighbor_y = y + dy;
+
+            if boundary == BoundaryCondition::Wrap {
+                neighbor_x = (neighbor_x + self.cols) % self.cols;
+                neighbor_y = (neighbor_y + self.rows) % self.rows;
+            } else if neighbor_x < 0
+                || neighbor_y < 0
+                || neighbor_x >= self.cols
+                || neighbor_y >= self.rows
+            {
+                if boundary == BoundaryCondition::Alive {
+                    off_board += 1;
+                }
+
+                continue;
+            }
+
+            let neighbor = (neighbor_x, neighbor_y);
+
+            // Same as in `neighbor_counts`: offsets that wrap onto the same square,
+            // or onto the cell itself, only count once.
+            if neighbor != (x, y) && !neighbors[..neighbor_count].contains(&neighbor) {
+                neighbors[neighbor_count] = neighbor;
+                neighbor_count += 1;
+            }
+        }
+
+        off_board
+            + neighbors[..neighbor_count]
+                .iter()
+                .filter(|&&(neighbor_x, neighbor_y)| {
+                    self.front[(neighbor_y * self.cols + neighbor_x) as usize] == CellState::Alive
+                })
+                .count() as u32
+    }
+
+    fn index(&self, x: i32, y: i32) -> Option<usize> {
+        (x >= 0 && y >= 0 && x < self.cols && y < self.rows).then(|| (y * self.cols + x) as usize)
+    }
+}
+
+/// The operations a board needs for running Life-like rules, so callers can pick the
+/// representation that suits them.
+pub trait LifeBoard {
+    /// Replaces the board with a `cols` by `rows` grid where only `cells` are alive.
+    fn load_cells(&mut self, cols: i32, rows: i32, cells: &mut dyn Iterator<Item = (i32, i32)>);
+
+    fn step_life(
+        &mut self,
+        boundary: BoundaryCondition,
+        neighborhood: Neighborhood,
+        ruleset: &Ruleset,
+    );
+
+    fn alive(&self, x: i32, y: i32) -> bool;
+
+    fn population(&self) -> usize;
+
+    /// Every live cell, row by row from the top-left.
+    fn alive_cells(&self) -> Box<dyn Iterator<Item = (i32, i32)> + '_>;
+}
+
+impl LifeBoard for Board {
+    fn load_cells(&mut self, cols: i32, rows: i32, cells: &mut dyn Iterator<Item = (i32, i32)>) {
+        self.load(cols, rows, cells);
+    }
+
+    fn step_life(
+        &mut self,
+        boundary: BoundaryCondition,
+        neighborhood: Neighborhood,
+        ruleset: &Ruleset,
+    ) {
+        self.step(boundary, neighborhood, ruleset);
+    }
+
+    fn alive(&self, x: i32, y: i32) -> bool {
+        self.is_alive(x, y)
+    }
+
+    fn population(&self) -> usize {
+        self.live_cells().count()
+    }
+
+    fn alive_cells(&self) -> Box<dyn Iterator<Item = (i32, i32)> + '_> {
+        Box::new(self.live_cells())
+    }
+}
+
+/// A board packed one bit per cell, with each row starting on a fresh `u64`. Stepping
+/// counts the neighbors of 64 cells at once by adding whole words of neighbor bits into
+/// four bit planes, which is much faster than [`Board`] on large boards. It only knows
+/// alive and dead, so like [`step`] it ignores the dying states of Generations rules.
+///
+/// ```
+/// use game_of_life::life::{BitBoard, BoundaryCondition, LifeBoard, Neighborhood, Ruleset};
+///
+/// let mut board = BitBoard::default();
+/// board.load(3, 3, [(1, 0), (1, 1), (1, 2)]);
+/// board.step(BoundaryCondition::Dead, Neighborhood::Moore, &Ruleset::default());
+/// assert_eq!(board.alive_cells().collect::<Vec<_>>(), [(0, 1), (1, 1), (2, 1)]);
+/// ```
+#[derive(Default)]
+pub struct BitBoard {
+    cols: i32,
+    rows: i32,
+    words_per_row: usize,
+    front: Vec<u64>,
+    back: Vec<u64>,
+}
+
+impl BitBoard {
+    /// Replaces the board with a `cols` by `rows` grid where only `cells` are alive,
+    /// reusing the existing buffers when they are large enough.
+    pub fn load(&mut self, cols: i32, rows: i32, cells: impl IntoIterator<Item = (i32, i32)>) {
+        self.cols = cols;
+        self.rows = rows;
+        self.words_per_row = (cols.max(0) as usize).div_ceil(64);
+
+        let len = self.words_per_row * rows.max(0) as usize;
+        self.front.clear();
+        self.front.resize(len, 0);
+        self.back.resize(len, 0);
+
+        for (x, y) in cells {
+            self.set(x, y, true);
+        }
+    }
+
+    /// Sets a single cell, ignoring positions outside the board.
+    pub fn set(&mut self, x: i32, y: i32, alive: bool) {
+        if let Some((index, bit)) = self.bit(x, y) {
+            if alive {
+                self.front[index] |= bit;
+            } else {
+                self.front[index] &= !bit;
+            }
+        }
+    }
+
+    pub fn is_alive(&self, x: i32, y: i32) -> bool {
+        self.bit(x, y)
+            .is_some_and(|(index, bit)| self.front[index] & bit != 0)
+    }
+
+    pub fn step(
+        &mut self,
+        boundary: BoundaryCondition,
+        neighborhood: Neighborhood,
+        ruleset: &Ruleset,
+    ) {
+        // An offset that wraps all the way around onto the same square only counts once,
+        // which whole-word shifts cannot tell apart, so boards that narrow take the sparse
+        // path instead.
+        if boundary == BoundaryCondition::Wrap && (self.cols < 3 || self.rows < 3) {
+            let cells: HashSet<_> = self.live_cells().collect();
+            let next = step(
+                &cells,
+                self.cols,
+                self.rows,
+                boundary,
+                neighborhood,
+                ruleset,
+            );
+            self.load(self.cols, self.rows, next);
+            return;
+        }
+
+        let max_neighbors = neighborhood.max_neighbors();
+        let birth: Vec<u32> = (0..=max_neighbors)
+            .filter(|count| ruleset.birth.contains(count))
+            .collect();
+        let survival: Vec<u32> = (0..=max_neighbors)
+            .filter(|count| ruleset.survival.contains(count))
+            .collect();
+        let words = self.words_per_row;
+        let on_board: Vec<u64> = (0..words)
+            .map(|index| {
+                let bits = (self.cols as usize - index * 64).min(64);
+
+                if bits == 64 { !0 } else { (1 << bits) - 1 }
+            })
+            .collect();
+        let off_board = match boundary {
+            BoundaryCondition::Alive => on_board.clone(),
+            _ => vec![0; words],
+        };
+        let mut back = std::mem::take(&mut self.back);
+
+        for y in 0..self.rows {
+            let neighbor_row = |y: i32| match boundary {
+                _ if (0..self.rows).contains(&y) => self.row(y),
+                BoundaryCondition::Wrap => self.row((y + self.rows) % self.rows),
+                _ => off_board.as_slice(),
+            };
+            let (above, row, below) = (neighbor_row(y - 1), self.row(y), neighbor_row(y + 1));
+
+            for index in 0..words {
+                let mut planes = [0u64; 4];
+                let mut add = |bits: u64| {
+                    let mut carry = bits;
+
+                    for plane in planes.iter_mut() {
+                        let sum = *plane ^ carry;
+                        carry &= *plane;
+                        *plane = sum;
+                    }
+                };
+
+                match neighborhood {
+                    Neighborhood::Moore => {
+                        for neighbors in [above, below] {
+                            add(self.west(neighbors, index, boundary));
+                            add(neighbors[index]);
+                            add(self.east(neighbors, index, boundary));
+                        }
+                    }
+                    Neighborhood::VonNeumann => {
+                        add(above[index]);
+                        add(below[index]);
+                    }
+                }
+                add(self.west(row, index, boundary));
+                add(self.east(row, index, boundary));
+
+                // The bits of every cell whose neighbor count is `count`, read off the
+                // planes like a binary number.
+                let with_count = |count: u32| {
+                    planes
+                        .iter()
+                        .enumerate()
+                        .fold(!0, |bits, (plane_index, &plane)| {
+                            bits & if count & (1 << plane_index) != 0 {
+                                plane
+                            } else {
+                                !plane
+                            }
+                        })
+                };
+                let births = birth
+                    .iter()
+                    .fold(0, |bits, &count| bits | with_count(count));
+                let survivors = survival
+                    .iter()
+                    .fold(0, |bits, &count| bits | with_count(count));
+                let alive = row[index];
+
+                back[y as usize * words + index] =
+                    ((alive & survivors) | (!alive & births)) & on_board[index];
+            }
+        }
+
+        self.back = std::mem::replace(&mut self.front, back);
+    }
+
+    pub fn live_cells(&self) -> impl Iterator<Item = (i32, i32)> + '_ {
+        let words = self.words_per_row.max(1);
+
+        self.front
+            .iter()
+            .enumerate()
+            .flat_map(move |(index, &word)| {
+                let x = (index % words) as i32 * 64;
+                let y = (index / words) as i32;
+
+                (0..64)
+                    .filter(move |bit| word & (1 << bit) != 0)
+                    .map(move |bit| (x + bit, y))
+            })
+    }
+
+    fn bit(&self, x: i32, y: i32) -> Option<(usize, u64)> {
+        (x >= 0 && y >= 0 && x < self.cols && y < self.rows).then(|| {
+            (
+                y as usize * self.words_per_row + x as usize / 64,
+                1 << (x % 64),
+            )
+        })
+    }
+
+    fn row(&self, y: i32) -> &[u64] {
+        let start = y as usize * self.words_per_row;
+
+        &self.front[start..start + self.words_per_row]
+    }
+
+    /// Whether the squares just past the left and right ends of `row` are alive.
+    fn edges(&self, row: &[u64], boundary: BoundaryCondition) -> (u64, u64) {
+        let last = (self.cols - 1) as usize;
+
+        match boundary {
+            BoundaryCondition::Dead => (0, 0),
+            BoundaryCondition::Alive => (1, 1),
+            BoundaryCondition::Wrap => ((row[last / 64] >> (last % 64)) & 1, row[0] & 1),
+        }
+    }
+
+    /// Word `index` of `row` moved one square right, so each bit holds its west neighbor.
+    fn west(&self, row: &[u64], index: usize, boundary: BoundaryCondition) -> u64 {
+        let carry = match index {
+            0 => self.edges(row, boundary).0,
+            _ => row[index - 1] >> 63,
+        };
+
+        (row[index] << 1) | carry
+    }
+
+    /// Word `index` of `row` moved one square left, so each bit holds its east neighbor.
+    fn east(&self, row: &[u64], index: usize, boundary: BoundaryCondition) -> u64 {
+        let shifted = (row[index] >> 1) | row.get(index + 1).map_or(0, |next| next << 63);
+
+        // The padding past the last square is always dead, so the last square's east
+        // neighbor comes from the boundary instead.
+        if index + 1 == self.words_per_row {
+            let last = (self.cols - 1) as u32 % 64;
+            shifted | (self.edges(row, boundary).1 << last)
+        } else {
+            shifted
+        }
+    }
+}
+
+impl LifeBoard for BitBoard {
+    fn load_cells(&mut self, cols: i32, rows: i32, cells: &mut dyn Iterator<Item = (i32, i32)>) {
+        self.load(cols, rows, cells);
+    }
+
+    fn step_life(
+        &mut self,
+        boundary: BoundaryCondition,
+        neighborhood: Neighborhood,
+        ruleset: &Ruleset,
+    ) {
+        self.step(boundary, neighborhood, ruleset);
+    }
+
+    fn alive(&self, x: i32, y: i32) -> bool {
+        self.is_alive(x, y)
+    }
+
+    fn population(&self) -> usize {
+        self.front
+            .iter()
+            .map(|word| word.count_ones() as usize)
+            .sum()
+    }
+
+    fn alive_cells(&self) -> Box<dyn Iterator<Item = (i32, i32)> + '_> {
+        Box::new(self.live_cells())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GLIDER: [(i32, i32); 5] = [(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)];
+
+    /// Steps `cells` on a dead-bordered `cols` by `rows` board through both the dense
+    /// [`Board`] and the sparse [`step`], checking they agree after every generation.
+    fn run(cells: &[(i32, i32)], cols: i32, rows: i32, generations: u32) -> HashSet<(i32, i32)> {
+        let ruleset = Ruleset::default();
+        let mut board = Board::default();
+        board.load(cols, rows, cells.iter().copied());
+        let mut sparse: HashSet<_> = cells.iter().copied().collect();
+
+        for _ in 0..generations {
+            board.step(BoundaryCondition::Dead, Neighborhood::Moore, &ruleset);
+            sparse = step(
+                &sparse,
+                cols,
+                rows,
+                BoundaryCondition::Dead,
+                Neighborhood::Moore,
+                &ruleset,
+            );
+            assert_eq!(board.live_cells().collect::<HashSet<_>>(), sparse);
+        }
+
+        sparse
+    }
+
+    fn cells(cells: &[(i32, i32)]) -> HashSet<(i32, i32)> {
+        cells.iter().copied().collect()
+    }
+
+    #[test]
+    fn blinker_has_period_two() {
+        let vertical = [(2, 1), (2, 2), (2, 3)];
+        let horizontal = [(1, 2), (2, 2), (3, 2)];
+
+        assert_eq!(run(&vertical, 5, 5, 1), cells(&horizontal));
+        assert_eq!(run(&vertical, 5, 5, 2), cells(&vertical));
+    }
+
+    #[test]
+    fn block_is_a_still_life() {
+        let block = [(1, 1), (2, 1), (1, 2), (2, 2)];
+
+        for generations in 1..=4 {
+            assert_eq!(run(&block, 4, 4, generations), cells(&block));
+        }
+    }
+
+    #[test]
+    fn glider_moves_one_square_diagonally_every_four_generations() {
+        let moved: Vec<_> = GLIDER.iter().map(|&(x, y)| (x + 1, y + 1)).collect();
+        let moved_twice: Vec<_> = GLIDER.iter().map(|&(x, y)| (x + 2, y + 2)).collect();
+
+        assert_eq!(run(&GLIDER, 10, 10, 4), cells(&moved));
+        assert_eq!(run(&GLIDER, 10, 10, 8), cells(&moved_twice));
+        assert_ne!(run(&GLIDER, 10, 10, 2), cells(&GLIDER));
+    }
+
+    #[test]
+    fn board_matches_step_under_every_boundary() {
+        let ruleset = Ruleset::default();
+        let r_pentomino = [(1, 0), (2, 0), (0, 1), (1, 1), (1, 2)];
+
+        for boundary in [
+            BoundaryCondition::Dead,
+            BoundaryCondition::Alive,
+            BoundaryCondition::Wrap,
+        ] {
+            let mut cells: HashSet<_> =
+                r_pentomino.iter().map(|&(x, y)| (x + 10, y + 10)).collect();
+            let mut board = Board::default();
+            board.load(24, 20, cells.iter().copied());
+
+            for _ in 0..50 {
+                cells = step(&cells, 24, 20, boundary, Neighborhood::Moore, &ruleset);
+                board.step(boundary, Neighborhood::Moore, &ruleset);
+                assert_eq!(
+                    board.live_cells().collect::<HashSet<_>>(),
+                    cells,
+                    "{boundary:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn bit_board_matches_board() {
+        // The second blinker straddles the boundary between the first and second words
+        // of each row.
+        let cells = [
+            (1, 0),
+            (2, 0),
+            (0, 1),
+            (1, 1),
+            (1, 2),
+            (63, 5),
+            (64, 5),
+            (65, 5),
+        ];
+
+        for rule in ["B3/S23", "B2/S", "B36/S23"] {
+            for boundary in [
+                BoundaryCondition::Dead,
+                BoundaryCondition::Alive,
+                BoundaryCondition::Wrap,
+            ] {
+                for neighborhood in [Neighborhood::Moore, Neighborhood::VonNeumann] {
+                    let ruleset = parse_rule(rule).unwrap();
+                    let mut board = Board::default();
+                    let mut bits = BitBoard::default();
+                    board.load(75, 20, cells);
+                    bits.load(75, 20, cells);
+
+                    for _ in 0..30 {
+                        board.step(boundary, neighborhood, &ruleset);
+                        bits.step(boundary, neighborhood, &ruleset);
+                        assert_eq!(
+                            bits.alive_cells().collect::<HashSet<_>>(),
+                            board.live_cells().collect::<HashSet<_>>(),
+                            "{rule} {boundary:?} {neighborhood:?}"
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn neighbor_counts_start_fresh_every_step() {
+        let ruleset = Ruleset::default();
+        let mut buffers = StepBuffers::default();
+        let mut cells = cells(&[(3, 3), (4, 4), (5, 5)]);
+        let mut next = HashSet::new();
+        let mut generations = 0;
+        let step = |cells: &HashSet<_>, next: &mut HashSet<_>, buffers: &mut StepBuffers| {
+            step_into(
+                cells,
+                10,
+                10,
+                BoundaryCondition::Dead,
+                Neighborhood::Moore,
+                &ruleset,
+                buffers,
+                next,
+            );
+        };
+
+        while !cells.is_empty() {
+            step(&cells, &mut next, &mut buffers);
+            assert!(buffers.candidates() > 0);
+            std::mem::swap(&mut cells, &mut next);
+            generations += 1;
+        }
+
+        assert_eq!(generations, 2);
+
+        // Nothing is left near a live cell, so no count from the diagonal may survive.
+        step(&cells, &mut next, &mut buffers);
+        assert_eq!(buffers.candidates(), 0);
+        assert!(next.is_empty());
+    }
+
+    fn sorted_neighbors(
+        (x, y): (i32, i32),
+        cols: i32,
+        rows: i32,
+        boundary: BoundaryCondition,
+    ) -> Vec<(i32, i32)> {
+        let mut neighbors = neighbors(x, y, cols, rows, boundary, Neighborhood::Moore);
+        neighbors.sort_unstable();
+        neighbors
+    }
+
+    #[test]
+    fn bounded_boards_only_count_neighbors_on_the_board() {
+        for boundary in [BoundaryCondition::Dead, BoundaryCondition::Alive] {
+            assert_eq!(
+                sorted_neighbors((0, 0), 5, 4, boundary),
+                [(0, 1), (1, 0), (1, 1)]
+            );
+            assert_eq!(
+                sorted_neighbors((4, 3), 5, 4, boundary),
+                [(3, 2), (3, 3), (4, 2)]
+            );
+            assert_eq!(
+                sorted_neighbors((2, 0), 5, 4, boundary),
+                [(1, 0), (1, 1), (2, 1), (3, 0), (3, 1)]
+            );
+            assert_eq!(sorted_neighbors((0, 2), 5, 4, boundary).len(), 5);
+            assert_eq!(sorted_neighbors((2, 2), 5, 4, boundary).len(), 8);
+        }
+    }
+
+    #[test]
+    fn wrapped_boards_count_neighbors_across_the_edges() {
+        let wrap = BoundaryCondition::Wrap;
+
+        assert_eq!(
+            sorted_neighbors((0, 0), 5, 4, wrap),
+            [
+                (0, 1),
+                (0, 3),
+                (1, 0),
+                (1, 1),
+                (1, 3),
+                (4, 0),
+                (4, 1),
+                (4, 3)
+            ]
+        );
+        assert_eq!(
+            sorted_neighbors((4, 3), 5, 4, wrap),
+            [
+                (0, 0),
+                (0, 2),
+                (0, 3),
+                (3, 0),
+                (3, 2),
+                (3, 3),
+                (4, 0),
+                (4, 2)
+            ]
+        );
+        assert_eq!(
+            sorted_neighbors((2, 0), 5, 4, wrap),
+            [
+                (1, 0),
+                (1, 1),
+                (1, 3),
+                (2, 1),
+                (2, 3),
+                (3, 0),
+                (3, 1),
+                (3, 3)
+            ]
+        );
+    }
+
+    #[test]
+    fn tiny_wrapped_boards_count_each_neighbor_once() {
+        let wrap = BoundaryCondition::Wrap;
+
+        assert_eq!(
+            sorted_neighbors((0, 0), 2, 2, wrap),
+            [(0, 1), (1, 0), (1, 1)]
+        );
+        assert_eq!(sorted_neighbors((0, 1), 1, 3, wrap), [(0, 0), (0, 2)]);
+        assert!(sorted_neighbors((0, 0), 1, 1, wrap).is_empty());
+    }
+
+    #[test]
+    fn only_a_live_boundary_has_neighbors_off_the_board() {
+        let alive = BoundaryCondition::Alive;
+
+        for (x, y) in [(0, 0), (4, 0), (0, 3), (4, 3)] {
+            assert_eq!(
+                off_board_neighbors(x, y, 5, 4, alive, Neighborhood::Moore),
+                5
+            );
+            assert_eq!(
+                off_board_neighbors(x, y, 5, 4, alive, Neighborhood::VonNeumann),
+                2
+            );
+        }
+
+        for (x, y) in [(2, 0), (0, 2), (4, 1), (3, 3)] {
+            assert_eq!(
+                off_board_neighbors(x, y, 5, 4, alive, Neighborhood::Moore),
+                3
+            );
+            assert_eq!(
+                off_board_neighbors(x, y, 5, 4, alive, Neighborhood::VonNeumann),
+                1
+            );
+        }
+
+        assert_eq!(
+            off_board_neighbors(2, 2, 5, 4, alive, Neighborhood::Moore),
+            0
+        );
+
+        for boundary in [BoundaryCondition::Dead, BoundaryCondition::Wrap] {
+            for (x, y) in [(0, 0), (2, 0), (2, 2)] {
+                assert_eq!(
+                    off_board_neighbors(x, y, 5, 4, boundary, Neighborhood::Moore),
+                    0
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn births_across_a_wrapped_corner_need_exactly_three_neighbors() {
+        let step_corner = |live: &[(i32, i32)]| {
+            step(
+                &cells(live),
+                5,
+                5,
+                BoundaryCondition::Wrap,
+                Neighborhood::Moore,
+                &Ruleset::default(),
+            )
+            .contains(&(0, 0))
+        };
+
+        // Each of these is next to (0, 0) only through the wrapped edges.
+        assert!(step_corner(&[(4, 4), (4, 0), (0, 4)]));
+        assert!(!step_corner(&[(4, 4), (4, 0), (0, 4), (1, 4)]));
+        assert!(!step_corner(&[(4, 4), (4, 0)]));
+    }
+
+    #[test]
+    fn a_live_boundary_gives_edges_births_on_an_empty_board() {
+        let counts = neighbor_counts(
+            &HashSet::new(),
+            5,
+            4,
+            BoundaryCondition::Alive,
+            Neighborhood::Moore,
+        );
+        assert_eq!(counts[&(0, 0)], 5);
+        assert_eq!(counts[&(2, 0)], 3);
+        assert!(!counts.contains_key(&(2, 2)));
+
+        let next = step(
+            &HashSet::new(),
+            5,
+            4,
+            BoundaryCondition::Alive,
+            Neighborhood::Moore,
+            &Ruleset::default(),
+        );
+        assert!(next.contains(&(2, 0)) && next.contains(&(0, 1)));
+        assert!(!next.contains(&(0, 0)) && !next.contains(&(2, 2)));
+    }
+
+    #[test]
+    fn one_tick_with_hundreds_of_births_and_deaths_stays_consistent() {
+        // A 15x15 field of vertical blinkers, which all turn horizontal in one tick.
+        let (cols, rows) = (60, 60);
+        let centres: Vec<_> = (0..15)
+            .flat_map(|row| (0..15).map(move |col| (col * 4 + 1, row * 4 + 1)))
+            .collect();
+        let vertical: HashSet<_> = centres
+            .iter()
+            .flat_map(|&(x, y)| [(x, y - 1), (x, y), (x, y + 1)])
+            .collect();
+        let horizontal: HashSet<_> = centres
+            .iter()
+            .flat_map(|&(x, y)| [(x - 1, y), (x, y), (x + 1, y)])
+            .collect();
+
+        let mut board = Board::default();
+        board.load(cols, rows, vertical.iter().copied());
+        board.step(
+            BoundaryCondition::Dead,
+            Neighborhood::Moore,
+            &Ruleset::default(),
+        );
+        let dense: HashSet<_> = board.live_cells().collect();
+
+        let mut buffers = StepBuffers::default();
+        let mut sparse = HashSet::new();
+        step_into(
+            &vertical,
+            cols,
+            rows,
+            BoundaryCondition::Dead,
+            Neighborhood::Moore,
+            &Ruleset::default(),
+            &mut buffers,
+            &mut sparse,
+        );
+
+        assert_eq!(dense, horizontal);
+        assert_eq!(sparse, horizontal);
+        assert_eq!(horizontal.difference(&vertical).count(), 450);
+        assert_eq!(vertical.difference(&horizontal).count(), 450);
+        assert_eq!(
+            board
+                .states()
+                .iter()
+                .filter(|&&state| state == CellState::Alive)
+                .count(),
+            675
+        );
+        assert!(board.dying_cells().next().is_none());
+    }
+
+    #[test]
+    fn parses_rules_in_either_case_and_order() {
+        let rule = parse_rule("s23/b3").unwrap();
+
+        assert_eq!(rule.to_string(), "B3/S23");
+        assert_eq!(parse_rule("B/S").unwrap().to_string(), "B/S");
+        assert_eq!(parse_rule("B36/S23").unwrap().birth, HashSet::from([3, 6]));
+    }
+
+    #[test]
+    fn rejects_malformed_rules() {
+        for notation in ["", "B3", "S23", "B9/S23", "B3/S2x", "B3/S23/B3", "X3/S23"] {
+            assert!(parse_rule(notation).is_err(), "{notation}");
+        }
+    }
+
+    #[test]
+    fn rules_must_fit_the_neighborhood() {
+        let life = Ruleset::default();
+        let b5 = parse_rule("B5/S23").unwrap();
+
+        assert!(check_rule(&life, Neighborhood::Moore).is_ok());
+        assert!(check_rule(&life, Neighborhood::VonNeumann).is_ok());
+        assert!(check_rule(&b5, Neighborhood::Moore).is_ok());
+        assert!(check_rule(&b5, Neighborhood::VonNeumann).is_err());
+        assert!(check_rule(&parse_rule("B3/S4").unwrap(), Neighborhood::VonNeumann).is_ok());
+    }
+
+    #[test]
+    fn parses_neighborhood_names() {
+        assert_eq!(parse_neighborhood("Moore"), Ok(Neighborhood::Moore));
+        assert_eq!(
+            parse_neighborhood("vonneumann"),
+            Ok(Neighborhood::VonNeumann)
+        );
+        assert!(parse_neighborhood("hex").is_err());
+    }
+
+    fn step_star_wars(board: &mut Board) {
+        let star_wars = parse_rule("B2/S345/C4").unwrap();
+        board.step(BoundaryCondition::Dead, Neighborhood::Moore, &star_wars);
+    }
+
+    #[test]
+    fn generations_cells_decay_through_every_dying_state() {
+        let mut board = Board::default();
+        board.load(5, 5, [(2, 2)]);
+
+        let mut states = Vec::new();
+        for _ in 0..3 {
+            step_star_wars(&mut board);
+            states.push(board.state(2, 2));
+        }
+
+        assert_eq!(
+            states,
+            [CellState::Dying(1), CellState::Dying(2), CellState::Dead]
+        );
+    }
+
+    #[test]
+    fn generations_dying_cells_are_not_reborn() {
+        let mut board = Board::default();
+        board.load(5, 5, [(1, 2), (3, 2)]);
+        board.set(2, 2, CellState::Dying(1));
+
+        step_star_wars(&mut board);
+
+        // Two live neighbors would give birth to a dead square under B2.
+        assert_eq!(board.state(2, 2), CellState::Dying(2));
+        assert_eq!(board.state(2, 1), CellState::Alive);
+    }
+
+    #[test]
+    fn generations_dying_cells_are_not_neighbors() {
+        let mut board = Board::default();
+        board.load(5, 5, [(1, 1)]);
+        board.set(3, 1, CellState::Dying(1));
+
+        step_star_wars(&mut board);
+
+        // Counting the dying square would give (2, 2) the two neighbors B2 needs.
+        assert_eq!(board.state(2, 2), CellState::Dead);
+        assert_eq!(board.state(3, 1), CellState::Dying(2));
+    }
+
+    #[test]
+    fn generations_dying_cells_do_not_keep_neighbors_alive() {
+        let conway_with_decay = parse_rule("B3/S23/C3").unwrap();
+        let mut board = Board::default();
+        board.load(5, 5, [(1, 2), (2, 2)]);
+        board.set(3, 2, CellState::Dying(1));
+
+        board.step(
+            BoundaryCondition::Dead,
+            Neighborhood::Moore,
+            &conway_with_decay,
+        );
+
+        // Counting the dying square would give (2, 2) the two neighbors S23 needs.
+        assert_eq!(board.state(2, 2), CellState::Dying(1));
+        assert_eq!(board.state(3, 2), CellState::Dead);
+    }
+
+    fn brians_brain(board: &Board) -> [HashSet<(i32, i32)>; 2] {
+        [
+            board.live_cells().collect(),
+            board.dying_cells().map(|(cell, _)| cell).collect(),
+        ]
+    }
+
+    #[test]
+    fn brians_brain_pair_fires_and_then_dies() {
+        let mut board = Board::default();
+        board.load(4, 4, [(1, 1), (2, 1)]);
+
+        board.step_brians_brain(BoundaryCondition::Dead, Neighborhood::Moore);
+        assert_eq!(
+            brians_brain(&board),
+            [
+                cells(&[(1, 0), (2, 0), (1, 2), (2, 2)]),
+                cells(&[(1, 1), (2, 1)]),
+            ]
+        );
+
+        board.step_brians_brain(BoundaryCondition::Dead, Neighborhood::Moore);
+        assert_eq!(
+            brians_brain(&board),
+            [
+                cells(&[(0, 1), (3, 1), (1, 3), (2, 3)]),
+                cells(&[(1, 0), (2, 0), (1, 2), (2, 2)]),
+            ]
+        );
+    }
+
+    #[test]
+    fn brians_brain_dying_cells_are_not_reborn() {
+        let mut board = Board::default();
+        board.load(5, 5, [(1, 2), (3, 2)]);
+        board.set(2, 2, CellState::Dying(1));
+
+        board.step_brians_brain(BoundaryCondition::Dead, Neighborhood::Moore);
+
+        // Two live neighbors fire the dead squares above and below, not the dying one.
+        assert_eq!(board.state(2, 2), CellState::Dead);
+        assert_eq!(board.state(2, 1), CellState::Alive);
+        assert_eq!(board.state(2, 3), CellState::Alive);
+    }
+
+    #[test]
+    fn brians_brain_dying_cells_are_not_neighbors() {
+        let mut board = Board::default();
+        board.load(5, 5, [(1, 1)]);
+        board.set(3, 1, CellState::Dying(1));
+
+        board.step_brians_brain(BoundaryCondition::Dead, Neighborhood::Moore);
+
+        assert_eq!(board.live_cells().count(), 0);
+    }
+}
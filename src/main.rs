@@ -1,358 +1,7651 @@
+mod download;
+#[cfg(feature = "http")]
+mod http;
+mod key_bindings;
+mod preferences;
+mod session;
+mod smooth;
+mod texture;
+
+use bevy::diagnostic::{DiagnosticsStore, FrameTimeDiagnosticsPlugin};
 use bevy::ecs::system::SystemId;
-use bevy::input::mouse::MouseWheel;
+use bevy::input::mouse::{MouseMotion, MouseWheel};
+use bevy::render::camera::Viewport;
+use bevy::render::render_asset::RenderAssetUsages;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+use bevy::render::view::RenderLayers;
+use bevy::render::view::screenshot::{Screenshot, save_to_disk};
+use bevy::ui::RelativeCursorPosition;
+use bevy::utils::tracing::field;
 use bevy::window::PrimaryWindow;
 use bevy::{prelude::*, window::WindowMode};
-use std::collections::HashSet;
+use game_of_life::geometry::GridGeometry;
+use game_of_life::life::{
+    self, Automaton, BoundaryCondition, CellState, LifeBoard, Neighborhood, Ruleset,
+};
+use game_of_life::pattern_file::{self, PatternFile};
+use game_of_life::terminal::TerminalRenderer;
+use game_of_life::{cells, patterns, rle};
+use key_bindings::{Action, KeyBindings};
+use preferences::Preferences;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 const GAME_TICK_SECOND: f32 = 0.1;
+const PREFERENCES_PATH: &str = "preferences.toml";
+const KEY_BINDINGS_PATH: &str = "keys.toml";
+const MIN_GAME_TICK_SECOND: f32 = 0.01;
+const MAX_GAME_TICK_SECOND: f32 = 2.;
+const GAME_TICK_SPEED_FACTOR: f32 = 1.25;
 
 const NUM_OF_COLS: i32 = 100;
 const NUM_OF_ROWS: i32 = 100;
+const MAX_EXPANDED_DIMENSION: i32 = 1000;
+const MAX_SPRITE_SQUARES: i32 = 250_000;
+const MAX_TEXTURE_DIMENSION: i32 = 8192;
+// As many squares as the largest texture, which keeps every square index within an i32.
+const MAX_BOARD_SQUARES: i32 = MAX_TEXTURE_DIMENSION * MAX_TEXTURE_DIMENSION;
 
 const SQUARE_HEIGHT: f32 = 50.;
 const SQUARE_WIDTH: f32 = 50.;
-const CAMERA_MOVE_FACTOR: f32 = 0.3;
+const DEFAULT_PAN_SPEED: f32 = 18.;
+const DEFAULT_PAN_SMOOTHING: f32 = 12.;
+const PAN_SETTLE_DISTANCE: f32 = 0.1;
+const MIN_ZOOM: f32 = 0.1;
+const MAX_ZOOM: f32 = 10.;
+const DEFAULT_FILL_DENSITY: f32 = 0.3;
+const DEFAULT_SOUP_SIZE: i32 = 16;
+const DEFAULT_SOUP_GENERATIONS: u64 = 1000;
+const SOUP_DENSITY: f64 = 0.5;
+const SOUP_RESULTS_SHOWN: usize = 5;
+const OLD_AGE: u32 = 50;
+const TRAIL_LENGTH: u32 = 8;
+const HISTORY_LENGTH: usize = 256;
+const MAX_STEPS_PER_TICK: u32 = 1024;
+const MINIMAP_FRACTION: f32 = 0.25;
+const MINIMAP_MARGIN: f32 = 10.;
+const MINIMAP_LAYER: usize = 1;
+const STATUS_BAR_HEIGHT: f32 = 26.;
+const POPULATION_GRAPH_LENGTH: usize = 300;
+const POPULATION_GRAPH_SIZE: Vec2 = Vec2::new(320., 120.);
+const POPULATION_GRAPH_MARGIN: f32 = 10.;
+const POPULATION_GRAPH_LAYER: usize = 2;
+/// Label every this many squares along the ruler, picking the first that leaves at
+/// least `RULER_MIN_SPACING` pixels between labels.
+const RULER_STEPS: [i32; 13] = [1, 2, 5, 10, 20, 50, 100, 200, 500, 1000, 2000, 5000, 10000];
+const RULER_MIN_SPACING: f32 = 40.;
+const RULER_LABEL_WIDTH: f32 = 40.;
+const RULER_FONT_SIZE: f32 = 12.;
+/// The density slider snaps to multiples of this.
+const FILL_DENSITY_STEP: f32 = 0.05;
+const SLIDER_WIDTH: f32 = 120.;
+const SLIDER_HEIGHT: f32 = 12.;
+const RULE_PRESETS: [&str; 5] = ["B3/S23", "B36/S23", "B2/S", "B3678/S34678", "B3/S12345"];
+const PANEL_BUTTON_COLOR: Color = Color::srgb(0.25, 0.25, 0.25);
+const PANEL_BUTTON_HOVER_COLOR: Color = Color::srgb(0.35, 0.35, 0.35);
+const PANEL_BUTTON_PRESSED_COLOR: Color = Color::srgb(0.5, 0.5, 0.5);
+const CIRCLE_TEXTURE_SIZE: u32 = 64;
+const CELL_PALETTE: [Color; 6] = [
+    Color::srgb(0.9, 0.3, 0.3),
+    Color::srgb(0.95, 0.6, 0.2),
+    Color::srgb(0.95, 0.9, 0.3),
+    Color::srgb(0.3, 0.85, 0.4),
+    Color::srgb(0.3, 0.6, 0.95),
+    Color::srgb(0.75, 0.4, 0.9),
+];
+const COMPARISON_GAP: i32 = 2;
+const FIT_MARGIN: f32 = 1.2;
+/// How quickly follow mode catches up with the live cells, as the same kind of rate as
+/// [`PanSettings::smoothing`].
+const FOLLOW_SMOOTHING: f32 = 2.;
+const PATTERN_KEYS: [KeyCode; 5] = [
+    KeyCode::Digit1,
+    KeyCode::Digit2,
+    KeyCode::Digit3,
+    KeyCode::Digit4,
+    KeyCode::Digit5,
+];
 
-#[derive(Resource)]
-struct GameTickTimer(Timer);
+#[derive(Resource, Clone, Copy)]
+struct CellSize {
+    w: f32,
+    h: f32,
+}
 
 #[derive(Resource)]
-struct OneShotSystems {
-    game_logic: SystemId,
-    render: SystemId,
+struct GridConfig {
+    cols: i32,
+    rows: i32,
 }
 
-impl FromWorld for OneShotSystems {
-    fn from_world(world: &mut World) -> Self {
-        OneShotSystems {
-            game_logic: world.register_system(game_logic),
-            render: world.register_system(handle_rendering),
-        }
+impl GridConfig {
+    fn geometry(&self, cell_size: &CellSize) -> GridGeometry {
+        GridGeometry::new(self.cols, self.rows, cell_size.w, cell_size.h)
     }
 }
 
-#[derive(Component)]
-struct Square {
-    x: i32,
-    y: i32,
-}
+#[derive(Resource)]
+struct ActiveBoundary(BoundaryCondition);
 
-#[derive(Component)]
-#[component(storage = "SparseSet")]
-struct Alive;
+#[derive(Resource)]
+struct ActiveRuleset(Ruleset);
 
-#[derive(States, Debug, Clone, PartialEq, Eq, Hash)]
-enum GameState {
-    Placing,
-    Ongoing,
+#[derive(Resource)]
+struct ActiveNeighborhood(Neighborhood);
+
+#[derive(Resource)]
+struct ActiveAutomaton(Automaton);
+
+#[derive(Resource)]
+struct ExpandGrid(bool);
+
+/// Steps only the live cells and their neighbors instead of the whole dense board,
+/// which is much cheaper for large, mostly empty grids.
+#[derive(Resource)]
+struct SparseBackend(bool);
+
+#[derive(Resource, Default)]
+struct SparseBuffers(life::StepBuffers);
+
+#[derive(Resource, Default)]
+struct PlacementHistory {
+    undo: Vec<Vec<(Entity, bool)>>,
+    redo: Vec<Vec<(Entity, bool)>>,
+    stroke: Vec<(Entity, bool)>,
+    /// Every square set since the [`ComparisonBoard`] last caught up, undos and redos
+    /// included, so it gets the same edits. Only kept when there is one.
+    edits: Option<Vec<(Entity, bool)>>,
+    /// Set when the whole board was replaced, so the comparison board copies it again.
+    replaced: bool,
 }
 
-#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
-struct PlacingSet;
+impl PlacementHistory {
+    fn record(&mut self, entity: Entity, alive: bool) {
+        self.stroke.push((entity, alive));
+        self.note_edit(entity, alive);
+    }
 
-#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
-struct OngoingSet;
+    fn note_edit(&mut self, entity: Entity, alive: bool) {
+        if let Some(edits) = &mut self.edits {
+            edits.push((entity, alive));
+        }
+    }
 
-#[derive(Eq, Hash, PartialEq, Clone)]
-struct SquareIdentifier {
-    entity: Entity,
-    number_of_neighbors: u32,
+    fn finish_stroke(&mut self) {
+        if self.stroke.is_empty() {
+            return;
+        }
+
+        self.undo.push(std::mem::take(&mut self.stroke));
+        self.redo.clear();
+    }
 }
 
 #[derive(Resource)]
-struct SquareMap {
-    map: Vec<Vec<SquareIdentifier>>,
+struct LastPaintedSquare(Option<(i32, i32)>);
+
+#[derive(Resource, Default)]
+struct Selection(Option<((i32, i32), (i32, i32))>);
+
+impl Selection {
+    fn bounds(&self) -> Option<((i32, i32), (i32, i32))> {
+        self.0.map(|((anchor_x, anchor_y), (corner_x, corner_y))| {
+            (
+                (anchor_x.min(corner_x), anchor_y.min(corner_y)),
+                (anchor_x.max(corner_x), anchor_y.max(corner_y)),
+            )
+        })
+    }
 }
 
-#[derive(Resource)]
-enum LogicState {
-    CalculationNeeded,
-    CalculatingCurrently,
-    Calculated,
+#[derive(Resource, Default)]
+struct Clipboard {
+    width: i32,
+    height: i32,
+    cells: HashSet<(i32, i32)>,
 }
 
+#[derive(Resource, Default)]
+struct StampOrientation(patterns::Orientation);
+
+#[derive(Resource, Default)]
+struct PatternPreview(Vec<Entity>);
+
+/// The board the dense backend steps, kept between ticks so its buffers are reused.
 #[derive(Resource)]
-struct RenderInput {
-    entities_that_died: Vec<Entity>,
-    entities_born: Vec<Entity>,
+enum BoardBuffers {
+    Dense(life::Board),
+    Bitset(life::BitBoard),
 }
 
-fn main() {
-    App::new()
-        .add_plugins(DefaultPlugins.set(WindowPlugin {
-            primary_window: Some(Window {
-                resizable: false,
-                mode: WindowMode::BorderlessFullscreen(MonitorSelection::Primary),
-                ..default()
-            }),
-            ..default()
-        }))
-        .insert_state(GameState::Placing)
-        .configure_sets(Update, PlacingSet.run_if(in_state(GameState::Placing)))
-        .configure_sets(Update, OngoingSet.run_if(in_state(GameState::Ongoing)))
-        .insert_resource(SquareMap {
-            map: Vec::with_capacity(SQUARE_HEIGHT as usize),
-        })
-        .insert_resource(RenderInput {
-            entities_that_died: Vec::new(),
-            entities_born: Vec::new(),
-        })
-        .insert_resource(LogicState::CalculationNeeded)
-        .insert_resource(GameTickTimer(Timer::from_seconds(
-            GAME_TICK_SECOND,
-            TimerMode::Repeating,
-        )))
-        .init_resource::<OneShotSystems>()
-        .add_systems(Startup, setup)
-        .add_systems(Update, (handle_scroll, handle_move, handle_state_change))
-        .add_systems(Update, handle_click.in_set(PlacingSet))
-        .add_systems(Update, game_loop.in_set(OngoingSet))
-        .run();
-}
+impl BoardBuffers {
+    fn new(kind: BoardKind) -> Self {
+        match kind {
+            BoardKind::Dense => BoardBuffers::Dense(life::Board::default()),
+            BoardKind::Bitset => BoardBuffers::Bitset(life::BitBoard::default()),
+        }
+    }
 
-fn setup(mut commands: Commands, mut square_map: ResMut<SquareMap>) {
-    commands.spawn(Camera2d);
+    fn board(&self) -> &dyn LifeBoard {
+        match self {
+            BoardBuffers::Dense(board) => board,
+            BoardBuffers::Bitset(board) => board,
+        }
+    }
 
-    for row in 0..NUM_OF_ROWS {
-        square_map
-            .map
-            .push(Vec::with_capacity(SQUARE_WIDTH as usize));
+    fn board_mut(&mut self) -> &mut dyn LifeBoard {
+        match self {
+            BoardBuffers::Dense(board) => board,
+            BoardBuffers::Bitset(board) => board,
+        }
+    }
 
-        for col in 0..NUM_OF_COLS {
-            let square_id = commands
-                .spawn((
-                    Square { x: col, y: row },
-                    Sprite {
-                        color: Color::WHITE,
-                        custom_size: Some(Vec2::new(SQUARE_WIDTH, SQUARE_HEIGHT)),
-                        ..default()
-                    },
-                    Transform {
-                        translation: Vec2::new(
-                            (-NUM_OF_COLS as f32 * SQUARE_WIDTH / 2.) + col as f32 * SQUARE_WIDTH,
-                            (NUM_OF_ROWS as f32 * SQUARE_HEIGHT / 2.) - row as f32 * SQUARE_HEIGHT,
-                        )
-                        .extend(0.),
-                        ..default()
-                    },
-                ))
-                .id();
+    /// Sets a single square. The bitset has no dying states, so it keeps them as dead.
+    fn set(&mut self, x: i32, y: i32, state: CellState) {
+        match self {
+            BoardBuffers::Dense(board) => board.set(x, y, state),
+            BoardBuffers::Bitset(board) => board.set(x, y, state == CellState::Alive),
+        }
+    }
 
-            square_map.map.last_mut().unwrap().push(SquareIdentifier {
-                entity: square_id,
-                number_of_neighbors: 0,
-            })
+    /// Steps one generation. [`parse_args`] only lets the bitset run Life rules with
+    /// two states, since Brian's Brain and Generations rules need the dying states.
+    fn step(
+        &mut self,
+        automaton: Automaton,
+        boundary: BoundaryCondition,
+        neighborhood: Neighborhood,
+        ruleset: &Ruleset,
+    ) {
+        match (self, automaton) {
+            (BoardBuffers::Dense(board), Automaton::BriansBrain) => {
+                board.step_brians_brain(boundary, neighborhood)
+            }
+            (board, _) => board.board_mut().step_life(boundary, neighborhood, ruleset),
         }
     }
+
+    fn dying_cells(&self) -> impl Iterator<Item = ((i32, i32), u8)> + '_ {
+        match self {
+            BoardBuffers::Dense(board) => Some(board.dying_cells()),
+            BoardBuffers::Bitset(_) => None,
+        }
+        .into_iter()
+        .flatten()
+    }
 }
 
-fn handle_scroll(
-    mut evr_scroll: EventReader<MouseWheel>,
-    mut query_camera: Query<&mut OrthographicProjection, With<Camera2d>>,
-) {
-    let mut camera_projection = query_camera.single_mut();
+#[derive(Component)]
+struct ComparisonSquare;
 
-    for ev in evr_scroll.read() {
-        camera_projection.scale /= if ev.y.is_sign_positive() { 1.1 } else { 0.9 };
+/// A second board to the right of the main one that follows its generations under a
+/// different rule. It mirrors the main board while placing and whenever the whole board
+/// is replaced, and otherwise gets the same edits and rewinds through its own history.
+#[derive(Resource)]
+struct ComparisonBoard {
+    ruleset: Ruleset,
+    cols: i32,
+    rows: i32,
+    cells: HashSet<(i32, i32)>,
+    generation: u64,
+    history: VecDeque<HashSet<(i32, i32)>>,
+    squares: Vec<Entity>,
+    label: Option<Entity>,
+    repaint: bool,
+    buffers: life::StepBuffers,
+}
+
+#[derive(Resource, Default)]
+struct NeighborOverlay {
+    enabled: bool,
+    labels: Vec<Entity>,
+}
+
+#[derive(Resource, Default, Clone, Copy, PartialEq)]
+enum ActiveStamp {
+    #[default]
+    Nothing,
+    Clipboard,
+    Pattern(usize),
+}
+
+#[derive(Resource)]
+struct ZoomLimits {
+    min: f32,
+    max: f32,
+}
+
+/// Zoom levels the camera jumps to, each on its own [`Action`].
+#[derive(Resource)]
+struct ZoomPresets(Vec<(Action, ZoomPreset)>);
+
+impl Default for ZoomPresets {
+    fn default() -> Self {
+        ZoomPresets(vec![
+            (Action::ZoomBoard, ZoomPreset::Board),
+            (Action::Zoom1x, ZoomPreset::Magnified(1.)),
+            (Action::Zoom2x, ZoomPreset::Magnified(2.)),
+            (Action::Zoom4x, ZoomPreset::Magnified(4.)),
+        ])
     }
 }
 
-fn handle_state_change(
-    keys: Res<ButtonInput<KeyCode>>,
-    game_state: Res<State<GameState>>,
-    mut next_game_state: ResMut<NextState<GameState>>,
-) {
-    if keys.just_pressed(KeyCode::Space) {
-        match game_state.get() {
-            GameState::Placing => next_game_state.set(GameState::Ongoing),
-            GameState::Ongoing => next_game_state.set(GameState::Placing),
+#[derive(Clone, Copy)]
+enum ZoomPreset {
+    /// The whole board, centered, the way the camera starts out.
+    Board,
+    /// Squares this many times their cell size in physical pixels, so `1.` draws a
+    /// square pixel for pixel. Keeps the camera where it is.
+    Magnified(f32),
+}
+
+impl ZoomPreset {
+    /// The camera scale this zoom needs on a window with `scale_factor` physical pixels
+    /// per logical one, or `None` for [`ZoomPreset::Board`], which depends on the board.
+    fn scale(self, scale_factor: f32) -> Option<f32> {
+        match self {
+            ZoomPreset::Board => None,
+            ZoomPreset::Magnified(times) => Some(1. / (scale_factor * times)),
         }
     }
 }
 
-fn handle_move(
-    keys: Res<ButtonInput<KeyCode>>,
-    mut query_camera: Query<&mut Transform, With<Camera2d>>,
-) {
-    let mut camera_transform = query_camera.single_mut();
-    let mut move_x = 0.;
-    let mut move_y = 0.;
+/// How fast the WASD keys pan the camera, in squares per second, and how quickly the
+/// camera catches up with where they moved it to. A smoothing rate of zero moves the
+/// camera directly.
+#[derive(Resource, Clone, Copy)]
+struct PanSettings {
+    speed: f32,
+    smoothing: f32,
+}
 
-    if keys.pressed(KeyCode::KeyD) {
-        move_x += SQUARE_WIDTH * CAMERA_MOVE_FACTOR;
-    }
+/// Where keyboard panning is easing the camera towards, while it has not arrived yet,
+/// and where the easing last left the camera, so a jump made by anything else (a
+/// camera reset, say) cancels it instead of being undone.
+#[derive(Resource, Default)]
+struct PanTarget {
+    target: Option<Vec2>,
+    last: Vec2,
+}
+
+/// The flag that keeps patterns and sessions from switching to a Generations rule, see
+/// [`two_state_flag`].
+#[derive(Resource)]
+struct TwoStateOnly(Option<&'static str>);
+
+/// Whether the camera keeps panning and zooming to frame every live cell.
+#[derive(Resource, Default)]
+struct FollowCamera(bool);
+
+fn following_live_cells(follow: Res<FollowCamera>) -> bool {
+    follow.0
+}
+
+/// The cluster of live cells that Tab last centred the camera on.
+#[derive(Resource, Default)]
+struct ClusterTour(Option<usize>);
+
+#[derive(Resource)]
+struct GridLines(bool);
+
+#[derive(Resource)]
+struct GridLineColor(Color);
+
+#[derive(Resource)]
+struct Ruler(bool);
+
+#[derive(Clone, Copy, PartialEq)]
+enum RulerAxis {
+    Columns,
+    Rows,
+}
+
+/// One of the column or row indices along the top and left of the window. Labels are
+/// reused as the camera moves and hidden when there are more than needed.
+#[derive(Component)]
+struct RulerLabel(RulerAxis);
+
+#[derive(Resource)]
+struct SavePath(Option<PathBuf>);
+
+/// Who a pattern saved as JSON is credited to. `--pattern-name` and `--pattern-author`
+/// win over the name and author of the pattern that was loaded last.
+#[derive(Resource, Default)]
+struct PatternCredits {
+    name_flag: Option<String>,
+    author_flag: Option<String>,
+    loaded_name: Option<String>,
+    loaded_author: Option<String>,
+}
 
-    if keys.pressed(KeyCode::KeyA) {
-        move_x -= SQUARE_WIDTH * CAMERA_MOVE_FACTOR;
+impl PatternCredits {
+    fn name(&self) -> Option<String> {
+        self.name_flag.clone().or_else(|| self.loaded_name.clone())
     }
 
-    if keys.pressed(KeyCode::KeyW) {
-        move_y += SQUARE_HEIGHT * CAMERA_MOVE_FACTOR;
+    fn author(&self) -> Option<String> {
+        self.author_flag
+            .clone()
+            .or_else(|| self.loaded_author.clone())
     }
 
-    if keys.pressed(KeyCode::KeyS) {
-        move_y -= SQUARE_HEIGHT * CAMERA_MOVE_FACTOR;
+    /// Forgets the loaded pattern's credits once the board no longer holds it.
+    fn forget_loaded(&mut self) {
+        self.loaded_name = None;
+        self.loaded_author = None;
     }
+}
 
-    camera_transform.translation.x += move_x;
-    camera_transform.translation.y += move_y;
+/// Where the pattern to load comes from. Standard input and URLs are read once at
+/// startup and kept, so reloading them gives the same pattern again.
+enum PatternSource {
+    File(PathBuf),
+    Stdin(String),
+    Url { url: String, contents: String },
 }
 
-fn handle_click(
-    mouse_button: Res<ButtonInput<MouseButton>>,
-    q_window: Query<&Window, With<PrimaryWindow>>,
-    q_camera: Query<(&Camera, &GlobalTransform), With<Camera2d>>,
-    square_map: Res<SquareMap>,
-    mut q_square_sprite: Query<&mut Sprite, With<Square>>,
-    mut commands: Commands,
-) {
-    if !mouse_button.pressed(MouseButton::Left) {
-        return;
+impl fmt::Display for PatternSource {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PatternSource::File(path) => write!(f, "{}", path.display()),
+            PatternSource::Stdin(_) => write!(f, "standard input"),
+            PatternSource::Url { url, .. } => write!(f, "{url}"),
+        }
     }
+}
 
-    let (camera, camera_transform) = q_camera.single();
+#[derive(Resource)]
+struct LoadSource(Option<PatternSource>);
 
-    if let Some(position) = q_window
-        .single()
-        .cursor_position()
-        .and_then(|cursor| camera.viewport_to_world(camera_transform, cursor).ok())
-        .map(|ray| ray.origin.truncate())
-    {
-        let square_x = ((position.x
-            - (-NUM_OF_COLS as f32 * SQUARE_WIDTH / 2. - SQUARE_WIDTH / 2.))
-            / SQUARE_WIDTH)
-            .floor();
-        let square_y = (((NUM_OF_ROWS as f32 * SQUARE_HEIGHT / 2. + SQUARE_HEIGHT / 2.)
-            - position.y)
-            / SQUARE_HEIGHT)
-            .floor();
-
-        if square_x < 0. || square_y < 0. {
-            return;
-        }
+#[derive(Resource)]
+struct SeedPattern(Option<(usize, (i32, i32))>);
 
-        if let Some(square_id) = square_map
-            .map
-            .get(square_y as usize)
-            .map(|row| row.get(square_x as usize))
-            .flatten()
-        {
-            let mut square_sprite = q_square_sprite.get_mut(square_id.entity).unwrap();
-            square_sprite.color = Color::BLACK;
-            commands.entity(square_id.entity).insert(Alive);
-        }
+#[derive(Resource)]
+struct SessionPath(PathBuf);
+
+struct CliArgs {
+    cols: i32,
+    rows: i32,
+    cell_size: CellSize,
+    boundary: BoundaryCondition,
+    expand: bool,
+    sparse: bool,
+    ruleset: Ruleset,
+    compare_ruleset: Option<Ruleset>,
+    neighborhood: Neighborhood,
+    automaton: Automaton,
+    save_path: Option<PathBuf>,
+    load_source: Option<PatternSource>,
+    load_stdin: bool,
+    load_url: Option<String>,
+    pattern_name: Option<String>,
+    pattern_author: Option<String>,
+    seed_pattern: Option<usize>,
+    seed_pattern_at: Option<(i32, i32)>,
+    session_path: PathBuf,
+    seed: Option<u64>,
+    bench: Option<u64>,
+    run_for: Option<u64>,
+    quiet: bool,
+    soup_search: Option<u64>,
+    soup_size: Option<i32>,
+    soup_generations: Option<u64>,
+    stop_on_stable: bool,
+    edit_while_running: bool,
+    render_mode: RenderMode,
+    board: BoardKind,
+    fullscreen: FullscreenMode,
+    smooth: bool,
+    http_port: Option<u16>,
+    rule_chance: RuleChance,
+    tui: bool,
+    tui_viewport: Option<(i32, i32)>,
+    tick_seconds: f32,
+    theme: Theme,
+    pan: PanSettings,
+}
+
+impl CliArgs {
+    /// Whether the run only steps the board and prints the result, without a window or
+    /// a terminal to draw it in.
+    fn headless(&self) -> bool {
+        self.bench.is_some() || self.run_for.is_some() || self.soup_search.is_some()
     }
 }
 
-fn game_loop(
-    mut logic_state: ResMut<LogicState>,
-    mut commands: Commands,
-    one_shot_systems: Res<OneShotSystems>,
-    mut game_tick_timer: ResMut<GameTickTimer>,
-    time: Res<Time>,
-) {
-    match *logic_state {
-        LogicState::CalculationNeeded => {
-            *logic_state = LogicState::CalculatingCurrently;
-            commands.run_system(one_shot_systems.game_logic)
-        }
-        LogicState::CalculatingCurrently => {}
-        LogicState::Calculated => {
-            if !game_tick_timer.0.tick(time.delta()).just_finished() {
-                return;
-            }
+/// Which board the dense backend steps, chosen with `--board`. The bitset keeps one bit
+/// per square and steps 64 squares at a time, which is much faster on large boards, but
+/// it only knows alive and dead.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum BoardKind {
+    Dense,
+    Bitset,
+}
+
+/// How the board is drawn. Sprites give every square its own entity, which every
+/// editing tool builds on; the texture draws the whole board as one image so it scales
+/// to boards with millions of squares.
+#[derive(Resource, Clone, Copy, PartialEq, Eq, Debug)]
+enum RenderMode {
+    Sprites,
+    Texture,
+}
+
+/// The fullscreen mode F11 switches to. Exclusive fullscreen takes over the display,
+/// which can be faster on some multi-GPU setups than a borderless window.
+#[derive(Resource, Clone, Copy, PartialEq, Eq, Debug)]
+enum FullscreenMode {
+    Borderless,
+    Exclusive,
+}
 
-            commands.run_system(one_shot_systems.render);
+impl FullscreenMode {
+    fn window_mode(self, monitor: MonitorSelection) -> WindowMode {
+        match self {
+            FullscreenMode::Borderless => WindowMode::BorderlessFullscreen(monitor),
+            FullscreenMode::Exclusive => WindowMode::Fullscreen(monitor),
         }
     }
 }
 
-fn game_logic(
-    mut square_map: ResMut<SquareMap>,
-    q_alive_squares: Query<&Square, With<Alive>>,
-    mut logic_state: ResMut<LogicState>,
-    mut render_input: ResMut<RenderInput>,
-) {
-    render_input.entities_born.clear();
-    render_input.entities_that_died.clear();
-    let mut potentially_changed_squares: HashSet<(usize, usize)> = HashSet::new();
+#[derive(Resource)]
+struct BenchGenerations(u64);
 
-    for square in q_alive_squares.iter() {
-        potentially_changed_squares.insert((square.y as usize, square.x as usize));
+#[derive(Resource)]
+struct RunForCells(HashSet<(i32, i32)>);
 
-        for dx in -1..=1 {
-            for dy in -1..=1 {
-                if dx == 0 && dy == 0 {
-                    continue;
-                }
+#[derive(Resource)]
+struct SoupSearch {
+    soups: u64,
+    first_seed: u64,
+    size: i32,
+    generations: u64,
+}
 
-                let y = square.y + dy;
-                let x = square.x + dx;
+struct SoupResult {
+    seed: u64,
+    /// The generation the soup died out or settled into a still life or period-2
+    /// oscillator, if it did so within the search.
+    settled_at: Option<u64>,
+    peak_population: usize,
+    final_population: usize,
+}
 
-                if x < 0 || y < 0 || x >= NUM_OF_COLS || y >= NUM_OF_ROWS {
-                    continue;
-                }
+#[derive(Resource)]
+struct RunForQuiet(bool);
 
-                potentially_changed_squares.insert((y as usize, x as usize));
+#[derive(Resource)]
+struct Paused(bool);
 
-                if let Some(neighbor) = square_map
-                    .map
-                    .get_mut(y as usize)
-                    .map(|row| row.get_mut(x as usize))
-                    .flatten()
-                {
-                    neighbor.number_of_neighbors += 1;
-                }
-            }
-        }
-    }
+#[derive(Resource)]
+struct Generation(u64);
 
-    for (y, x) in potentially_changed_squares.into_iter() {
-        if let Some(square) = square_map
-            .map
-            .get_mut(y)
-            .map(|row| row.get_mut(x))
-            .flatten()
-        {
-            let was_previously_alive = q_alive_squares.get(square.entity).is_ok();
-            //println!(
-            //    "checking square ({},{}), alive - {}, neighbors - {}",
-            //    y, x, was_previously_alive, square.number_of_neighbors
-            //);
+#[derive(Resource)]
+struct StepsPerTick(u32);
 
-            if was_previously_alive
-                && square.number_of_neighbors != 2
-                && square.number_of_neighbors != 3
-            {
-                render_input.entities_that_died.push(square.entity);
-            } else if square.number_of_neighbors == 3 {
-                render_input.entities_born.push(square.entity);
-            }
+/// Set while a single step is being calculated, so `game_logic` runs one generation
+/// whatever [`StepsPerTick`] is.
+#[derive(Resource, Default)]
+struct SingleStep(bool);
 
-            square.number_of_neighbors = 0;
-        }
-    }
+/// Digits typed while the game is running, waiting for `.` to step that many generations.
+#[derive(Resource, Default)]
+struct StepCount(Option<u64>);
 
-    *logic_state = LogicState::Calculated;
+/// The generation to pause at once a counted step gets there.
+#[derive(Resource, Default)]
+struct PauseAtGeneration(Option<u64>);
+
+#[derive(Resource, Default)]
+struct History(VecDeque<HashSet<(i32, i32)>>);
+
+#[derive(Resource, PartialEq)]
+struct Population(usize);
+
+/// Population of the most recent generations, oldest first, as `(generation, population)`.
+#[derive(Resource, Default)]
+struct PopulationHistory(VecDeque<(u64, usize)>);
+
+#[derive(Resource, Clone, Copy, PartialEq)]
+struct Theme {
+    name: &'static str,
+    alive: Color,
+    aged: Color,
+    dying: Color,
+    dead: Color,
+    background: Color,
+    immortal: Color,
 }
 
-fn handle_rendering(
-    render_input: Res<RenderInput>,
-    mut commands: Commands,
-    mut q_squares: Query<&mut Sprite>,
-    mut logic_state: ResMut<LogicState>,
-) {
-    for dead_square in render_input.entities_that_died.iter() {
-        let mut entity = q_squares.get_mut(*dead_square).unwrap();
-        entity.color = Color::WHITE;
-        commands.get_entity(*dead_square).unwrap().remove::<Alive>();
-    }
+const THEMES: [Theme; 3] = [
+    Theme {
+        name: "classic",
+        alive: Color::srgb(1., 0., 0.),
+        aged: Color::srgb(0., 0., 1.),
+        dying: Color::srgb(1., 0.65, 0.65),
+        dead: Color::WHITE,
+        background: Color::srgb(0.17, 0.17, 0.18),
+        immortal: Color::srgb(0.35, 0.35, 0.4),
+    },
+    Theme {
+        name: "dark",
+        alive: Color::srgb(0.3, 1., 0.4),
+        aged: Color::srgb(0., 0.45, 0.5),
+        dying: Color::srgb(0.15, 0.4, 0.2),
+        dead: Color::srgb(0.08, 0.08, 0.1),
+        background: Color::BLACK,
+        immortal: Color::srgb(0.55, 0.55, 0.6),
+    },
+    Theme {
+        name: "colorblind",
+        alive: Color::srgb(0.9, 0.62, 0.),
+        aged: Color::srgb(0., 0.45, 0.7),
+        dying: Color::srgb(0.8, 0.47, 0.65),
+        dead: Color::srgb(0.95, 0.95, 0.95),
+        background: Color::srgb(0.2, 0.2, 0.2),
+        immortal: Color::BLACK,
+    },
+];
 
-    for square_born in render_input.entities_born.iter() {
-        let mut entity = q_squares.get_mut(*square_born).unwrap();
-        entity.color = Color::BLACK;
-        commands.get_entity(*square_born).unwrap().insert(Alive);
-    }
+#[derive(Component)]
+struct HudText;
 
-    *logic_state = LogicState::CalculationNeeded;
+#[derive(Component)]
+struct CursorHighlight;
+
+#[derive(Component)]
+struct StatusBar;
+
+#[derive(Component)]
+struct ControlPanel;
+
+#[derive(Component)]
+struct CellTooltip;
+
+#[derive(Component, Clone, Copy)]
+enum PanelAction {
+    Clear,
+    Random,
+    Step,
+    RuleMenu,
+    PickRule(usize),
+}
+
+/// A track that sets its value from where along it the mouse is pressed.
+#[derive(Component, Clone, Copy, PartialEq, Debug)]
+enum PanelSlider {
+    Speed,
+    Density,
+}
+
+/// The filled part of a [`PanelSlider`] track, as wide as the slider's value.
+#[derive(Component)]
+struct SliderFill(PanelSlider);
+
+/// The list of [`RULE_PRESETS`] opened by the rule button.
+#[derive(Component)]
+struct RuleMenu;
+
+#[derive(Component, Clone, Copy)]
+enum PanelValue {
+    Speed,
+    Density,
+    Rule,
+}
+
+#[derive(Resource)]
+struct CursorCell {
+    x: i32,
+    y: i32,
+}
+
+#[derive(Resource, Default, Clone, Copy, PartialEq)]
+enum CellShape {
+    #[default]
+    Square,
+    Circle,
+}
+
+#[derive(Resource)]
+struct CircleTexture(Handle<Image>);
+
+impl FromWorld for CircleTexture {
+    fn from_world(world: &mut World) -> Self {
+        let size = CIRCLE_TEXTURE_SIZE as f32;
+        let data = (0..CIRCLE_TEXTURE_SIZE * CIRCLE_TEXTURE_SIZE)
+            .flat_map(|index| {
+                let x = (index % CIRCLE_TEXTURE_SIZE) as f32 + 0.5 - size / 2.;
+                let y = (index / CIRCLE_TEXTURE_SIZE) as f32 + 0.5 - size / 2.;
+                let coverage = (size / 2. - Vec2::new(x, y).length()).clamp(0., 1.);
+
+                [255, 255, 255, (coverage * 255.) as u8]
+            })
+            .collect();
+        let image = Image::new(
+            Extent3d {
+                width: CIRCLE_TEXTURE_SIZE,
+                height: CIRCLE_TEXTURE_SIZE,
+                depth_or_array_layers: 1,
+            },
+            TextureDimension::D2,
+            data,
+            TextureFormat::Rgba8UnormSrgb,
+            RenderAssetUsages::RENDER_WORLD,
+        );
+
+        CircleTexture(world.resource_mut::<Assets<Image>>().add(image))
+    }
+}
+
+#[derive(Component)]
+struct MainCamera;
+
+#[derive(Component)]
+struct MinimapCamera;
+
+#[derive(Default, Reflect, GizmoConfigGroup)]
+struct MinimapGizmos;
+
+#[derive(Resource, Default)]
+struct MinimapHovered(bool);
+
+/// Renders only the population graph, with world units matching logical window pixels.
+#[derive(Component)]
+struct PopulationGraphCamera;
+
+#[derive(Default, Reflect, GizmoConfigGroup)]
+struct PopulationGraphGizmos;
+
+/// Set while the cursor is over a UI node, so clicks on overlays don't reach the board.
+#[derive(Resource, Default)]
+struct PointerOverUi(bool);
+
+#[derive(Component)]
+struct DiagnosticsText;
+
+#[derive(Resource, Default)]
+struct TickRate {
+    last_tick: Option<Duration>,
+    pending_ticks: u32,
+    generations_per_second: f32,
+}
+
+impl TickRate {
+    fn record_tick(&mut self, now: Duration) {
+        self.pending_ticks += 1;
+
+        let Some(last_tick) = self.last_tick else {
+            self.last_tick = Some(now);
+            self.pending_ticks = 0;
+            return;
+        };
+
+        // Ticks that catch up after a slow frame share a timestamp, so they are
+        // averaged over the next frame that actually moved forward.
+        let elapsed = (now - last_tick).as_secs_f32();
+        if elapsed <= 0. {
+            return;
+        }
+
+        let generations_per_second = self.pending_ticks as f32 / elapsed;
+        self.generations_per_second =
+            self.generations_per_second * 0.8 + generations_per_second * 0.2;
+        self.last_tick = Some(now);
+        self.pending_ticks = 0;
+    }
+}
+
+#[derive(Resource)]
+struct AutoStopOnDeath(bool);
+
+/// Sent when a generation leaves a board that had live cells without any.
+#[derive(Event)]
+struct BoardDiedOut;
+
+#[derive(Resource, Default)]
+struct Stable(Option<u32>);
+
+/// The generation the board last started changing from, so stopping on a stable board
+/// can report how long it took to settle. `None` until the next generation is calculated.
+#[derive(Resource, Default)]
+struct SettleStart(Option<u64>);
+
+#[derive(Resource)]
+struct AutoStopOnStable(bool);
+
+/// Lets squares be clicked, painted and erased while the simulation runs, not only while
+/// placing.
+#[derive(Resource)]
+struct EditWhileRunning(bool);
+
+#[derive(Resource, Default)]
+struct QuitPrompt(bool);
+
+#[derive(Resource)]
+struct FillDensity(f32);
+
+#[derive(Resource)]
+struct RandomSeed(u64);
+
+#[derive(Resource)]
+struct SimulationRng(StdRng);
+
+/// How likely the rule is to be followed, for noisy variants of it: a cell the rule
+/// would bring to life is born with probability `birth`, and one it would keep alive
+/// dies anyway with probability `death`. The defaults follow the rule exactly.
+#[derive(Resource, Clone, Copy, PartialEq, Debug)]
+struct RuleChance {
+    birth: f64,
+    death: f64,
+}
+
+impl Default for RuleChance {
+    fn default() -> Self {
+        RuleChance {
+            birth: 1.,
+            death: 0.,
+        }
+    }
+}
+
+impl RuleChance {
+    fn is_exact(&self) -> bool {
+        *self == RuleChance::default()
+    }
+}
+
+impl FromWorld for SimulationRng {
+    fn from_world(world: &mut World) -> Self {
+        SimulationRng(match world.get_resource::<RandomSeed>() {
+            Some(seed) => StdRng::seed_from_u64(seed.0),
+            None => StdRng::from_entropy(),
+        })
+    }
+}
+
+#[derive(Resource)]
+struct OneShotSystems {
+    game_logic: SystemId,
+    render: SystemId,
+    single_step: SystemId,
+    load_pattern: SystemId,
+    clear_board: SystemId,
+    random_fill: SystemId,
+    random_fill_selection: SystemId,
+}
+
+impl FromWorld for OneShotSystems {
+    fn from_world(world: &mut World) -> Self {
+        OneShotSystems {
+            game_logic: world.register_system(game_logic),
+            render: world.register_system(handle_rendering),
+            single_step: world.register_system(single_step),
+            load_pattern: world.register_system(load_pattern),
+            clear_board: world.register_system(clear_board),
+            random_fill: world.register_system(random_fill),
+            random_fill_selection: world.register_system(random_fill_selection),
+        }
+    }
+}
+
+#[derive(Component)]
+struct Square {
+    x: i32,
+    y: i32,
+}
+
+#[derive(Component)]
+#[component(storage = "SparseSet")]
+struct Alive;
+
+#[derive(Component)]
+#[component(storage = "SparseSet")]
+struct Age(u32);
+
+#[derive(Component)]
+#[component(storage = "SparseSet")]
+struct DeadSince(u32);
+
+/// A live square that the rules never kill. It still counts as a neighbor, so immortal
+/// squares work as walls and reflectors.
+#[derive(Component)]
+#[component(storage = "SparseSet")]
+struct Immortal;
+
+/// Whether clicking and painting places [`Immortal`] squares instead of ordinary ones.
+#[derive(Resource, Default)]
+struct ImmortalBrush(bool);
+
+/// Side lengths of the square block a click, paint stroke or eraser covers.
+const BRUSH_SIZES: [i32; 3] = [1, 3, 5];
+
+/// Which of [`BRUSH_SIZES`] the brush uses.
+#[derive(Resource, Default)]
+struct BrushSize(usize);
+
+impl BrushSize {
+    fn side(&self) -> i32 {
+        BRUSH_SIZES[self.0]
+    }
+}
+
+/// A color given to a live square in place of the theme's, which born squares inherit as
+/// a blend of their colored parents.
+#[derive(Component)]
+#[component(storage = "SparseSet")]
+struct CellColor(Color);
+
+/// The [`CELL_PALETTE`] entry that squares placed by hand are painted with, or `None`
+/// for the theme's alive color.
+#[derive(Resource, Default)]
+struct PaintColor(Option<usize>);
+
+impl PaintColor {
+    fn color(&self) -> Option<Color> {
+        self.0.map(|index| CELL_PALETTE[index])
+    }
+}
+
+/// Squares that stopped being alive under Brian's Brain or a Generations rule, with how
+/// many generations ago that was. They are not alive, so they neither count as neighbors
+/// nor show up in the population.
+#[derive(Component)]
+#[component(storage = "SparseSet")]
+struct Dying(u8);
+
+#[derive(Resource)]
+struct Trails(bool);
+
+#[derive(States, Debug, Clone, PartialEq, Eq, Hash)]
+enum GameState {
+    Placing,
+    Ongoing,
+}
+
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+struct PlacingSet;
+
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+struct OngoingSet;
+
+/// Systems that edit squares, which run while placing and, with [`EditWhileRunning`],
+/// while the simulation runs.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+struct EditingSet;
+
+#[derive(Eq, Hash, PartialEq, Clone)]
+struct SquareIdentifier {
+    entity: Entity,
+}
+
+#[derive(Resource)]
+struct SquareMap {
+    cols: i32,
+    rows: i32,
+    map: Vec<SquareIdentifier>,
+}
+
+impl SquareMap {
+    fn index(&self, x: i32, y: i32) -> Option<usize> {
+        if x < 0 || y < 0 || x >= self.cols || y >= self.rows {
+            return None;
+        }
+
+        Some((y * self.cols + x) as usize)
+    }
+
+    fn get(&self, x: i32, y: i32) -> Option<&SquareIdentifier> {
+        self.index(x, y).and_then(|index| self.map.get(index))
+    }
+}
+
+#[derive(Resource)]
+enum LogicState {
+    CalculationNeeded,
+    CalculatingCurrently,
+    Calculated,
+}
+
+/// How many frames in a row [`LogicState::CalculatingCurrently`] may be seen before the
+/// calculation is assumed lost. `game_logic` runs as soon as the commands that queue it
+/// are applied, so the state normally never lasts into the next frame.
+const LOGIC_STALL_FRAMES: u32 = 3;
+
+/// Frames in a row [`LogicState::CalculatingCurrently`] has been seen for.
+#[derive(Resource, Default)]
+struct LogicWatchdog(u32);
+
+#[derive(Resource)]
+struct RenderInput {
+    entities_that_died: Vec<Entity>,
+    entities_born: Vec<Entity>,
+    entities_dying: Vec<(Entity, u8)>,
+    /// Live squares whose [`CellColor`] changed, with `None` for ones that lost it.
+    entities_recolored: Vec<(Entity, Option<Color>)>,
+    generations: u64,
+    died_out: bool,
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let mut cli_args = match parse_args(args.iter().cloned(), &Preferences::default()) {
+        Ok(cli_args) => cli_args,
+        Err(err) => {
+            eprintln!("{err}");
+            std::process::exit(1);
+        }
+    };
+
+    // Headless runs ignore saved preferences so their output only depends on the flags.
+    // Interactive runs parse the flags again on top of the preferences, so flags win.
+    if !cli_args.headless() {
+        match parse_args(
+            args.into_iter(),
+            &load_preferences(Path::new(PREFERENCES_PATH)),
+        ) {
+            Ok(with_preferences) => cli_args = with_preferences,
+            Err(err) => eprintln!("ignoring the saved preferences: {err}"),
+        }
+    }
+
+    if cli_args.load_stdin {
+        match io::read_to_string(io::stdin()) {
+            Ok(contents) => cli_args.load_source = Some(PatternSource::Stdin(contents)),
+            Err(err) => {
+                eprintln!("failed to read a pattern from standard input: {err}");
+                std::process::exit(1);
+            }
+        }
+    }
+
+    if let Some(url) = cli_args.load_url.take() {
+        match download::get(&url) {
+            Ok(contents) => cli_args.load_source = Some(PatternSource::Url { url, contents }),
+            Err(err) => {
+                eprintln!("failed to download a pattern from {url}: {err}");
+                std::process::exit(1);
+            }
+        }
+    }
+
+    if let Some(generations) = cli_args.bench {
+        run_bench(cli_args, generations);
+        return;
+    }
+
+    if let Some(soups) = cli_args.soup_search {
+        run_soup_search(cli_args, soups);
+        return;
+    }
+
+    if let Some(generations) = cli_args.run_for {
+        if let Err(err) = run_for(cli_args, generations) {
+            eprintln!("{err}");
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    if cli_args.tui {
+        if let Err(err) = run_tui(cli_args) {
+            eprintln!("{err}");
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    if cli_args.render_mode == RenderMode::Texture {
+        texture::run(cli_args);
+        return;
+    }
+
+    let mut app = App::new();
+    add_shared_setup(&mut app, &mut cli_args);
+
+    if let Some(ruleset) = cli_args.compare_ruleset {
+        app.insert_resource(ComparisonBoard {
+            ruleset,
+            cols: 0,
+            rows: 0,
+            cells: HashSet::new(),
+            generation: 0,
+            history: VecDeque::new(),
+            squares: Vec::new(),
+            label: None,
+            repaint: false,
+            buffers: life::StepBuffers::default(),
+        })
+        .insert_resource(PlacementHistory {
+            edits: Some(Vec::new()),
+            ..default()
+        });
+    }
+
+    app.add_plugins(FrameTimeDiagnosticsPlugin)
+        .add_plugins(smooth::SmoothCellsPlugin {
+            enabled: cli_args.smooth,
+            copy_sprites: true,
+        })
+        .insert_resource(ExpandGrid(cli_args.expand))
+        .insert_resource(SparseBackend(cli_args.sparse))
+        .init_resource::<SparseBuffers>()
+        .insert_resource(BoardBuffers::new(cli_args.board))
+        .insert_resource(SavePath(cli_args.save_path))
+        .insert_resource(PatternCredits {
+            name_flag: cli_args.pattern_name,
+            author_flag: cli_args.pattern_author,
+            ..default()
+        })
+        .insert_resource(SessionPath(cli_args.session_path))
+        .init_resource::<PlacementHistory>()
+        .insert_resource(CursorCell {
+            x: cli_args.cols / 2,
+            y: cli_args.rows / 2,
+        })
+        .init_resource::<Selection>()
+        .init_resource::<NeighborOverlay>()
+        .init_resource::<Clipboard>()
+        .init_resource::<StampOrientation>()
+        .init_resource::<ActiveStamp>()
+        .init_resource::<PaintColor>()
+        .init_resource::<ImmortalBrush>()
+        .init_resource::<PatternPreview>()
+        .init_resource::<ClusterTour>()
+        .insert_resource(GridLines(false))
+        .insert_resource(GridLineColor(Color::srgb(0.6, 0.6, 0.6)))
+        .init_gizmo_group::<MinimapGizmos>()
+        .init_resource::<CellShape>()
+        .insert_resource(Trails(false))
+        .init_resource::<CircleTexture>()
+        .init_resource::<MinimapHovered>()
+        .insert_resource(SquareMap {
+            cols: cli_args.cols,
+            rows: cli_args.rows,
+            map: Vec::with_capacity(cli_args.cols as usize * cli_args.rows as usize),
+        })
+        .insert_resource(RenderInput {
+            entities_that_died: Vec::new(),
+            entities_born: Vec::new(),
+            entities_dying: Vec::new(),
+            entities_recolored: Vec::new(),
+            generations: 0,
+            died_out: false,
+        })
+        .insert_resource(LogicState::CalculationNeeded)
+        .init_resource::<SingleStep>()
+        .init_resource::<LogicWatchdog>()
+        .insert_resource(AutoStopOnStable(cli_args.stop_on_stable))
+        .init_resource::<OneShotSystems>()
+        .add_systems(
+            Startup,
+            (
+                setup,
+                load_initial_pattern.after(setup),
+                stamp_seed_pattern.after(setup),
+            ),
+        )
+        .add_systems(FixedUpdate, game_loop.in_set(OngoingSet))
+        .add_systems(OnEnter(GameState::Ongoing), reset_placement_history)
+        .add_systems(
+            OnExit(GameState::Placing),
+            (clear_pattern_preview, clear_neighbor_overlay),
+        )
+        .add_systems(
+            Update,
+            (
+                count_population,
+                update_hud_text.after(count_population),
+                handle_diagnostics_toggle,
+                handle_minimap_toggle,
+                update_minimap,
+                draw_minimap_viewport,
+                handle_minimap_click.after(update_minimap),
+                record_population.after(count_population),
+                update_diagnostics_text,
+                update_status_bar,
+                update_cursor_highlight,
+                (
+                    handle_control_panel_toggle,
+                    handle_panel_buttons,
+                    handle_panel_sliders,
+                    update_panel_labels,
+                ),
+            ),
+        )
+        .add_systems(
+            Update,
+            (
+                handle_clear,
+                handle_grid_lines_toggle,
+                handle_save,
+                handle_screenshot,
+                handle_theme_cycle,
+                handle_cell_shape_toggle,
+                handle_trails_toggle,
+                apply_cell_shape.after(handle_cell_shape_toggle),
+                handle_load,
+                handle_session_save,
+                handle_session_load,
+                handle_camera_reset,
+                handle_fit_to_screen,
+                live_square_bounds
+                    .pipe(follow_live_cells)
+                    .run_if(following_live_cells)
+                    .after(handle_follow_toggle),
+                handle_cluster_tour.before(smooth_pan),
+                draw_grid.run_if(|grid_lines: Res<GridLines>| grid_lines.0),
+            ),
+        )
+        .add_systems(
+            Update,
+            (
+                handle_click,
+                handle_erase,
+                finish_placement_stroke,
+                handle_paint_color_cycle,
+                handle_immortal_brush_toggle,
+            )
+                .in_set(EditingSet),
+        )
+        .add_systems(
+            Update,
+            (
+                handle_random_fill,
+                handle_pattern_spawn,
+                handle_undo_redo,
+                handle_selection,
+                handle_copy_paste,
+                draw_selection,
+                handle_stamp_orientation,
+                draw_stamp_preview,
+                preview_pattern.after(handle_stamp_orientation),
+                handle_cursor_move,
+                handle_cursor_toggle.after(handle_cursor_move),
+                handle_nudge,
+                handle_neighbor_overlay_toggle,
+                update_neighbor_overlay.after(handle_neighbor_overlay_toggle),
+            )
+                .in_set(PlacingSet),
+        )
+        .add_systems(Update, update_cell_tooltip.after(update_pointer_over_ui))
+        .add_systems(
+            Update,
+            (
+                handle_step_count
+                    .after(handle_single_step)
+                    .after(handle_camera_reset)
+                    .before(discard_stale_calculation),
+                discard_stale_calculation.after(EditingSet),
+                watch_logic_state.before(calculate_ahead),
+                calculate_ahead.after(discard_stale_calculation),
+                handle_single_step,
+                handle_rewind,
+                handle_extinction,
+            )
+                .in_set(OngoingSet),
+        )
+        // After every edit and step of the frame, so it sees the main board as drawn.
+        .add_systems(
+            PostUpdate,
+            sync_comparison_board
+                .run_if(resource_exists::<ComparisonBoard>)
+                .before(TransformSystem::TransformPropagate),
+        )
+        .run();
+}
+
+/// Adds the window and the resources and systems both renderers use: the simulation
+/// settings, camera controls, state changes and population graph. Each renderer then
+/// adds how it stores, draws and edits the board. The rule and the pattern to load
+/// are taken out of `cli_args`.
+fn add_shared_setup(app: &mut App, cli_args: &mut CliArgs) {
+    if let Some(seed) = cli_args.seed {
+        app.insert_resource(RandomSeed(seed));
+    }
+
+    #[cfg(feature = "http")]
+    http::add_status_endpoint(app, cli_args.http_port);
+
+    app.insert_resource(current_preferences(cli_args))
+        .insert_resource(TwoStateOnly(two_state_flag(cli_args)))
+        .insert_resource(load_key_bindings(Path::new(KEY_BINDINGS_PATH)))
+        .add_plugins(DefaultPlugins.set(WindowPlugin {
+            primary_window: Some(Window {
+                resizable: false,
+                mode: cli_args.fullscreen.window_mode(MonitorSelection::Primary),
+                ..default()
+            }),
+            ..default()
+        }))
+        .insert_resource(cli_args.fullscreen)
+        .insert_state(GameState::Placing)
+        .insert_resource(GridConfig {
+            cols: cli_args.cols,
+            rows: cli_args.rows,
+        })
+        .insert_resource(cli_args.cell_size)
+        .insert_resource(ActiveBoundary(cli_args.boundary))
+        .insert_resource(ActiveRuleset(std::mem::take(&mut cli_args.ruleset)))
+        .insert_resource(ActiveNeighborhood(cli_args.neighborhood))
+        .insert_resource(ActiveAutomaton(cli_args.automaton))
+        .insert_resource(LoadSource(cli_args.load_source.take()))
+        .insert_resource(SeedPattern(
+            cli_args.seed_pattern.zip(cli_args.seed_pattern_at),
+        ))
+        .insert_resource(LastPaintedSquare(None))
+        .init_resource::<BrushSize>()
+        .insert_resource(ZoomLimits {
+            min: MIN_ZOOM,
+            max: MAX_ZOOM,
+        })
+        .init_resource::<ZoomPresets>()
+        .init_resource::<FollowCamera>()
+        .insert_resource(cli_args.pan)
+        .init_resource::<PanTarget>()
+        .insert_resource(Ruler(false))
+        .insert_resource(cli_args.theme)
+        .insert_resource(ClearColor(cli_args.theme.background))
+        .init_gizmo_group::<PopulationGraphGizmos>()
+        .init_resource::<PointerOverUi>()
+        .configure_sets(Update, PlacingSet.run_if(in_state(GameState::Placing)))
+        .configure_sets(Update, OngoingSet.run_if(in_state(GameState::Ongoing)))
+        .configure_sets(FixedUpdate, OngoingSet.run_if(in_state(GameState::Ongoing)))
+        .configure_sets(Update, EditingSet.run_if(editing_allowed))
+        .insert_resource(Paused(false))
+        .insert_resource(Generation(0))
+        .init_resource::<History>()
+        .insert_resource(StepsPerTick(1))
+        .init_resource::<StepCount>()
+        .init_resource::<PauseAtGeneration>()
+        .insert_resource(Population(0))
+        .init_resource::<PopulationHistory>()
+        .insert_resource(AutoStopOnDeath(true))
+        .add_event::<BoardDiedOut>()
+        .init_resource::<Stable>()
+        .init_resource::<SettleStart>()
+        .insert_resource(cli_args.rule_chance)
+        .insert_resource(EditWhileRunning(cli_args.edit_while_running))
+        .init_resource::<QuitPrompt>()
+        .insert_resource(FillDensity(DEFAULT_FILL_DENSITY))
+        .init_resource::<SimulationRng>()
+        .insert_resource(Time::<Fixed>::from_seconds(cli_args.tick_seconds as f64))
+        .init_resource::<TickRate>()
+        .add_systems(Last, save_preferences)
+        .add_systems(OnEnter(GameState::Placing), reset_generation)
+        .add_systems(OnExit(GameState::Placing), reset_last_painted_square)
+        .add_systems(
+            Update,
+            (
+                handle_quit,
+                handle_window_mode_toggle,
+                update_pointer_over_ui.before(PlacingSet).before(EditingSet),
+                handle_scroll,
+                handle_zoom_presets,
+                handle_follow_toggle,
+                (handle_move, smooth_pan.after(handle_move)),
+                handle_drag_pan,
+                handle_state_change,
+                handle_pause,
+                handle_speed_change,
+                handle_steps_per_tick_change,
+                (handle_ruler_toggle, update_ruler.after(handle_ruler_toggle)),
+                (
+                    handle_population_graph_toggle,
+                    draw_population_graph.after(record_population),
+                ),
+            ),
+        )
+        .add_systems(Update, highlight_hover.in_set(PlacingSet))
+        .add_systems(Update, handle_brush_size.in_set(EditingSet));
+}
+
+fn run_bench(cli_args: CliArgs, generations: u64) {
+    let mut app = App::new();
+
+    if let Some(seed) = cli_args.seed {
+        app.insert_resource(RandomSeed(seed));
+    }
+
+    app.add_plugins(MinimalPlugins)
+        .insert_resource(GridConfig {
+            cols: cli_args.cols,
+            rows: cli_args.rows,
+        })
+        .insert_resource(ActiveBoundary(cli_args.boundary))
+        .insert_resource(ActiveRuleset(cli_args.ruleset))
+        .insert_resource(ActiveNeighborhood(cli_args.neighborhood))
+        .insert_resource(ActiveAutomaton(cli_args.automaton))
+        .insert_resource(FillDensity(DEFAULT_FILL_DENSITY))
+        .insert_resource(BenchGenerations(generations))
+        .insert_resource(SparseBackend(cli_args.sparse))
+        .insert_resource(cli_args.render_mode)
+        .insert_resource(cli_args.cell_size)
+        .init_resource::<SimulationRng>()
+        .add_systems(Startup, bench)
+        .run();
+}
+
+/// Runs a fixed number of generations without a window and prints the final
+/// population and [`life::cells_hash`] of the board, so runs can be checked in scripts.
+fn run_for(mut cli_args: CliArgs, generations: u64) -> Result<(), String> {
+    let grid_config = GridConfig {
+        cols: cli_args.cols,
+        rows: cli_args.rows,
+    };
+    let cells = initial_cells(&mut cli_args, &grid_config)?;
+    let mut app = App::new();
+
+    #[cfg(feature = "http")]
+    if let Some(port) = cli_args.http_port {
+        let endpoint = http::StatusEndpoint::serve(port)
+            .map_err(|err| format!("failed to serve status on port {port}: {err}"))?;
+        app.insert_resource(endpoint);
+    }
+
+    app.add_plugins(MinimalPlugins)
+        .insert_resource(grid_config)
+        .insert_resource(ActiveBoundary(cli_args.boundary))
+        .insert_resource(ActiveRuleset(cli_args.ruleset))
+        .insert_resource(ActiveNeighborhood(cli_args.neighborhood))
+        .insert_resource(ActiveAutomaton(cli_args.automaton))
+        .insert_resource(SparseBackend(cli_args.sparse))
+        .insert_resource(BoardBuffers::new(cli_args.board))
+        .insert_resource(BenchGenerations(generations))
+        .insert_resource(RunForCells(cells))
+        .insert_resource(RunForQuiet(cli_args.quiet))
+        .add_systems(Startup, run_generations)
+        .run();
+
+    Ok(())
+}
+
+/// Redraws the board in the terminal every tick until interrupted, without opening a
+/// window. Generations go through the same board as `--run-for`, picked with `--board`.
+fn run_tui(mut cli_args: CliArgs) -> Result<(), String> {
+    let grid_config = GridConfig {
+        cols: cli_args.cols,
+        rows: cli_args.rows,
+    };
+    let cells = initial_cells(&mut cli_args, &grid_config)?;
+    let (width, height) = cli_args.tui_viewport.unwrap_or_else(terminal_viewport);
+    let renderer = TerminalRenderer::centered(grid_config.cols, grid_config.rows, width, height);
+    let tick = Duration::from_secs_f32(cli_args.tick_seconds);
+    let mut board = BoardBuffers::new(cli_args.board);
+    board
+        .board_mut()
+        .load_cells(grid_config.cols, grid_config.rows, &mut cells.into_iter());
+    let mut stdout = io::stdout().lock();
+
+    for generation in 0u64.. {
+        let status = format!(
+            "generation {generation} | population {} | Ctrl+C to quit",
+            board.board().population()
+        );
+        stdout
+            .write_all(renderer.frame(board.board(), &status).as_bytes())
+            .and_then(|()| stdout.flush())
+            .map_err(|err| format!("failed to draw to the terminal: {err}"))?;
+        std::thread::sleep(tick);
+
+        board.step(
+            cli_args.automaton,
+            cli_args.boundary,
+            cli_args.neighborhood,
+            &cli_args.ruleset,
+        );
+    }
+
+    Ok(())
+}
+
+/// The window `--tui` shows without `--tui-viewport`, in squares: the terminal size the
+/// shell exported, or 80x24, less a line for the status.
+fn terminal_viewport() -> (i32, i32) {
+    let size = |name, default| {
+        std::env::var(name)
+            .ok()
+            .and_then(|value| value.parse::<i32>().ok())
+            .filter(|&size| size > 1)
+            .unwrap_or(default)
+    };
+
+    (size("COLUMNS", 80), (size("LINES", 24) - 1) * 2)
+}
+
+/// The first flag in use that only tracks live cells, and so has nowhere to keep the
+/// dying states of a Generations rule.
+fn two_state_flag(cli_args: &CliArgs) -> Option<&'static str> {
+    [
+        ("--sparse", cli_args.sparse),
+        ("--compare-rule", cli_args.compare_ruleset.is_some()),
+        ("--soup-search", cli_args.soup_search.is_some()),
+        ("--board bitset", cli_args.board == BoardKind::Bitset),
+    ]
+    .into_iter()
+    .find_map(|(flag, used)| used.then_some(flag))
+}
+
+/// The board a run without a window starts from: a loaded pattern, a `--seed-pattern`
+/// or a random fill. A JSON pattern also sets the rule and tick it was saved with.
+fn initial_cells(
+    cli_args: &mut CliArgs,
+    grid_config: &GridConfig,
+) -> Result<HashSet<(i32, i32)>, String> {
+    let mut cells = HashSet::new();
+
+    if let Some(source) = &cli_args.load_source {
+        let pattern = read_centered_pattern(source, grid_config)
+            .map_err(|err| format!("failed to load pattern from {source}: {err}"))?;
+
+        apply_pattern_rule(
+            pattern.rule,
+            cli_args.neighborhood,
+            cli_args.automaton,
+            two_state_flag(cli_args),
+            &mut cli_args.ruleset,
+        );
+        cli_args.tick_seconds = pattern.tick_seconds.unwrap_or(cli_args.tick_seconds);
+        cells = pattern.cells;
+    } else if let Some((index, (x, y))) = cli_args.seed_pattern.zip(cli_args.seed_pattern_at) {
+        cells.extend(
+            patterns::PATTERNS[index]
+                .cells
+                .iter()
+                .map(|&(dx, dy)| (x + dx, y + dy)),
+        );
+    } else {
+        let mut rng = match cli_args.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        };
+        cells.extend(random_cells(grid_config, DEFAULT_FILL_DENSITY, &mut rng));
+    }
+
+    Ok(cells)
+}
+
+#[allow(clippy::too_many_arguments)]
+fn run_generations(
+    generations: Res<BenchGenerations>,
+    grid_config: Res<GridConfig>,
+    boundary: Res<ActiveBoundary>,
+    ruleset: Res<ActiveRuleset>,
+    neighborhood: Res<ActiveNeighborhood>,
+    automaton: Res<ActiveAutomaton>,
+    sparse: Res<SparseBackend>,
+    mut board: ResMut<BoardBuffers>,
+    mut cells: ResMut<RunForCells>,
+    quiet: Res<RunForQuiet>,
+    mut app_exit: EventWriter<AppExit>,
+    #[cfg(feature = "http")] endpoint: Option<Res<http::StatusEndpoint>>,
+) {
+    // Publishing every few generations keeps a long run visible without slowing it down.
+    #[cfg(feature = "http")]
+    let publish = |generation: u64, population: &dyn Fn() -> usize| {
+        if let Some(endpoint) = endpoint
+            .as_ref()
+            .filter(|_| generation.is_multiple_of(http::HEADLESS_STATUS_INTERVAL))
+        {
+            endpoint.publish(generation, population(), "running");
+        }
+    };
+    #[cfg(not(feature = "http"))]
+    let publish = |_: u64, _: &dyn Fn() -> usize| {};
+
+    if sparse.0 {
+        let mut next = HashSet::new();
+        let mut buffers = life::StepBuffers::default();
+
+        for generation in 0..generations.0 {
+            publish(generation, &|| cells.0.len());
+            life::step_into(
+                &cells.0,
+                grid_config.cols,
+                grid_config.rows,
+                boundary.0,
+                neighborhood.0,
+                &ruleset.0,
+                &mut buffers,
+                &mut next,
+            );
+            std::mem::swap(&mut cells.0, &mut next);
+        }
+    } else {
+        board.board_mut().load_cells(
+            grid_config.cols,
+            grid_config.rows,
+            &mut cells.0.iter().copied(),
+        );
+
+        for generation in 0..generations.0 {
+            publish(generation, &|| board.board().population());
+
+            board.step(automaton.0, boundary.0, neighborhood.0, &ruleset.0);
+        }
+
+        cells.0 = board.board().alive_cells().collect();
+    }
+
+    #[cfg(feature = "http")]
+    if let Some(endpoint) = &endpoint {
+        endpoint.publish(generations.0, cells.0.len(), "finished");
+    }
+
+    let hash = life::cells_hash(cells.0.iter().copied());
+
+    if quiet.0 {
+        println!("{} {hash:016x}", cells.0.len());
+    } else {
+        println!(
+            "after {} generations on a {}x{} board: population {}, hash {hash:016x}",
+            generations.0,
+            grid_config.cols,
+            grid_config.rows,
+            cells.0.len()
+        );
+    }
+
+    app_exit.send(AppExit::Success);
+}
+
+/// Fills the middle of the board with seeded random soups, runs each of them for a fixed
+/// number of generations and prints the seeds that stayed active longest or grew largest.
+fn run_soup_search(cli_args: CliArgs, soups: u64) {
+    let mut app = App::new();
+
+    app.add_plugins(MinimalPlugins)
+        .insert_resource(GridConfig {
+            cols: cli_args.cols,
+            rows: cli_args.rows,
+        })
+        .insert_resource(ActiveBoundary(cli_args.boundary))
+        .insert_resource(ActiveRuleset(cli_args.ruleset))
+        .insert_resource(ActiveNeighborhood(cli_args.neighborhood))
+        .insert_resource(SoupSearch {
+            soups,
+            first_seed: cli_args.seed.unwrap_or_else(rand::random),
+            size: cli_args.soup_size.unwrap_or(DEFAULT_SOUP_SIZE),
+            generations: cli_args
+                .soup_generations
+                .unwrap_or(DEFAULT_SOUP_GENERATIONS),
+        })
+        .add_systems(Startup, search_soups)
+        .run();
+}
+
+fn search_soups(
+    search: Res<SoupSearch>,
+    grid_config: Res<GridConfig>,
+    boundary: Res<ActiveBoundary>,
+    ruleset: Res<ActiveRuleset>,
+    neighborhood: Res<ActiveNeighborhood>,
+    mut app_exit: EventWriter<AppExit>,
+) {
+    let size = search.size.min(grid_config.cols).min(grid_config.rows);
+    let left = (grid_config.cols - size) / 2;
+    let top = (grid_config.rows - size) / 2;
+    // Soups are small next to the board, so the sparse stepper does far less work.
+    let mut buffers = life::StepBuffers::default();
+    let mut next = HashSet::new();
+    let mut results = Vec::new();
+
+    for index in 0..search.soups {
+        let seed = search.first_seed.wrapping_add(index);
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut cells: HashSet<(i32, i32)> = (0..size)
+            .flat_map(|y| (0..size).map(move |x| (left + x, top + y)))
+            .filter(|_| rng.gen_bool(SOUP_DENSITY))
+            .collect();
+        let mut result = SoupResult {
+            seed,
+            settled_at: None,
+            peak_population: cells.len(),
+            final_population: 0,
+        };
+        // After each step `previous` holds the board one generation back and `next` the
+        // one two generations back, enough to spot still lifes and period-2 oscillators.
+        let mut previous = HashSet::new();
+
+        for generation in 1..=search.generations {
+            life::step_into(
+                &cells,
+                grid_config.cols,
+                grid_config.rows,
+                boundary.0,
+                neighborhood.0,
+                &ruleset.0,
+                &mut buffers,
+                &mut next,
+            );
+            std::mem::swap(&mut previous, &mut cells);
+            std::mem::swap(&mut cells, &mut next);
+            result.peak_population = result.peak_population.max(cells.len());
+
+            if cells == previous || cells == next {
+                result.settled_at = Some(generation);
+                break;
+            }
+        }
+
+        result.final_population = cells.len();
+        results.push(result);
+    }
+
+    println!(
+        "searched {} soups of {size}x{size} starting at seed {}, for up to {} generations each",
+        search.soups, search.first_seed, search.generations
+    );
+
+    results.sort_by_key(|result| {
+        (
+            std::cmp::Reverse(result.settled_at.unwrap_or(u64::MAX)),
+            std::cmp::Reverse(result.peak_population),
+        )
+    });
+    println!("longest-lived:");
+    for result in results.iter().take(SOUP_RESULTS_SHOWN) {
+        print_soup_result(result, search.generations);
+    }
+
+    results.sort_by_key(|result| std::cmp::Reverse(result.peak_population));
+    println!("highest peak population:");
+    for result in results.iter().take(SOUP_RESULTS_SHOWN) {
+        print_soup_result(result, search.generations);
+    }
+
+    println!("rerun a soup with `--soup-search 1 --seed <seed>`");
+    app_exit.send(AppExit::Success);
+}
+
+fn print_soup_result(result: &SoupResult, generations: u64) {
+    let lifespan = match result.settled_at {
+        Some(generation) => format!("settled after {generation} generations"),
+        None => format!("still active after {generations} generations"),
+    };
+
+    println!(
+        "  seed {}: {lifespan}, peak population {}, final population {}",
+        result.seed, result.peak_population, result.final_population
+    );
+}
+
+fn random_cells(grid_config: &GridConfig, density: f32, rng: &mut StdRng) -> Vec<(i32, i32)> {
+    let density = density.clamp(0., 1.) as f64;
+
+    (0..grid_config.rows)
+        .flat_map(|y| (0..grid_config.cols).map(move |x| (x, y)))
+        .filter(|_| rng.gen_bool(density))
+        .collect()
+}
+
+#[allow(clippy::too_many_arguments)]
+fn bench(
+    bench_generations: Res<BenchGenerations>,
+    grid_config: Res<GridConfig>,
+    boundary: Res<ActiveBoundary>,
+    ruleset: Res<ActiveRuleset>,
+    neighborhood: Res<ActiveNeighborhood>,
+    automaton: Res<ActiveAutomaton>,
+    fill_density: Res<FillDensity>,
+    mut rng: ResMut<SimulationRng>,
+    (render_mode, cell_size, sparse): (Res<RenderMode>, Res<CellSize>, Res<SparseBackend>),
+    mut app_exit: EventWriter<AppExit>,
+) {
+    let cells = random_cells(&grid_config, fill_density.0, &mut rng.0);
+
+    if sparse.0 {
+        let mut cells: HashSet<(i32, i32)> = cells.into_iter().collect();
+        let mut next = HashSet::new();
+        let mut buffers = life::StepBuffers::default();
+        let start = Instant::now();
+
+        for _ in 0..bench_generations.0 {
+            life::step_into(
+                &cells,
+                grid_config.cols,
+                grid_config.rows,
+                boundary.0,
+                neighborhood.0,
+                &ruleset.0,
+                &mut buffers,
+                &mut next,
+            );
+            std::mem::swap(&mut cells, &mut next);
+        }
+
+        let elapsed = start.elapsed().as_secs_f64();
+
+        println!(
+            "{} sparse generations on a {}x{} board in {elapsed:.3}s ({:.1} generations/s), final population {}",
+            bench_generations.0,
+            grid_config.cols,
+            grid_config.rows,
+            bench_generations.0 as f64 / elapsed,
+            cells.len()
+        );
+        app_exit.send(AppExit::Success);
+        return;
+    }
+
+    let mut board = life::Board::default();
+    board.load(grid_config.cols, grid_config.rows, cells);
+
+    // The squares are drawn the way the chosen render mode would draw them, so the
+    // timings include keeping the picture up to date, not just the simulation.
+    let theme = THEMES[0];
+    let stages = dying_stages(automaton.0, &ruleset.0);
+    let resident_before = resident_bytes();
+    let setup_start = Instant::now();
+    let mut renderer = match *render_mode {
+        RenderMode::Sprites => {
+            let mut world = Box::new(World::new());
+            let geometry = grid_config.geometry(&cell_size);
+            let squares: Vec<Entity> = world
+                .spawn_batch(board.states().iter().enumerate().map(|(index, &state)| {
+                    let (x, y) = (
+                        index as i32 % grid_config.cols,
+                        index as i32 / grid_config.cols,
+                    );
+
+                    (
+                        Square { x, y },
+                        Sprite {
+                            color: texture::state_color(&theme, state, stages),
+                            custom_size: Some(Vec2::new(cell_size.w, cell_size.h)),
+                            ..default()
+                        },
+                        Transform::from_translation(
+                            Vec2::from(geometry.cell_to_world(x, y)).extend(0.),
+                        ),
+                    )
+                }))
+                .collect();
+
+            BenchRenderer::Sprites {
+                world,
+                squares,
+                previous: board.states().to_vec(),
+            }
+        }
+        RenderMode::Texture => {
+            let mut pixels = vec![0; board.states().len() * 4];
+            texture::paint(&theme, board.states(), stages, &mut pixels);
+
+            BenchRenderer::Texture { pixels }
+        }
+    };
+    let setup_elapsed = setup_start.elapsed().as_secs_f64();
+    let resident_growth = resident_before
+        .zip(resident_bytes())
+        .map(|(before, after)| after.saturating_sub(before));
+    let start = Instant::now();
+    let mut drawing = Duration::ZERO;
+
+    for _ in 0..bench_generations.0 {
+        match automaton.0 {
+            Automaton::Life => board.step(boundary.0, neighborhood.0, &ruleset.0),
+            Automaton::BriansBrain => board.step_brians_brain(boundary.0, neighborhood.0),
+        }
+
+        let draw_start = Instant::now();
+        match &mut renderer {
+            BenchRenderer::Sprites {
+                world,
+                squares,
+                previous,
+            } => {
+                for ((&entity, previous), &state) in
+                    squares.iter().zip(previous.iter_mut()).zip(board.states())
+                {
+                    if *previous != state {
+                        *previous = state;
+
+                        if let Some(mut sprite) = world.get_mut::<Sprite>(entity) {
+                            sprite.color = texture::state_color(&theme, state, stages);
+                        }
+                    }
+                }
+            }
+            BenchRenderer::Texture { pixels } => {
+                texture::paint(&theme, board.states(), stages, pixels)
+            }
+        }
+        drawing += draw_start.elapsed();
+    }
+
+    let elapsed = start.elapsed().as_secs_f64();
+
+    println!(
+        "{} generations on a {}x{} board in {elapsed:.3}s ({:.1} generations/s), final population {}",
+        bench_generations.0,
+        grid_config.cols,
+        grid_config.rows,
+        bench_generations.0 as f64 / elapsed,
+        board.live_cells().count()
+    );
+    println!(
+        "{:?} rendering: set up in {setup_elapsed:.3}s using {} MiB, {:.3}ms per generation drawing",
+        *render_mode,
+        resident_growth.map_or("unknown".to_string(), |bytes| format!(
+            "{:.1}",
+            bytes as f64 / (1024. * 1024.)
+        )),
+        drawing.as_secs_f64() * 1000. / bench_generations.0.max(1) as f64
+    );
+
+    app_exit.send(AppExit::Success);
+}
+
+enum BenchRenderer {
+    Sprites {
+        world: Box<World>,
+        squares: Vec<Entity>,
+        previous: Vec<CellState>,
+    },
+    Texture {
+        pixels: Vec<u8>,
+    },
+}
+
+/// The resident set size of this process, where the platform reports it.
+fn resident_bytes() -> Option<u64> {
+    let statm = fs::read_to_string("/proc/self/statm").ok()?;
+    let pages: u64 = statm.split_whitespace().nth(1)?.parse().ok()?;
+
+    Some(pages * 4096)
+}
+
+/// Parses the command line on top of `preferences`, which supply the defaults for the
+/// settings they cover.
+fn parse_args(
+    mut args: impl Iterator<Item = String>,
+    preferences: &Preferences,
+) -> Result<CliArgs, String> {
+    let mut cli_args = CliArgs {
+        cols: NUM_OF_COLS,
+        rows: NUM_OF_ROWS,
+        cell_size: CellSize {
+            w: SQUARE_WIDTH,
+            h: SQUARE_HEIGHT,
+        },
+        boundary: life::parse_boundary(&preferences.boundary)?,
+        expand: false,
+        sparse: false,
+        ruleset: life::parse_rule(&preferences.rule)?,
+        compare_ruleset: None,
+        neighborhood: Neighborhood::Moore,
+        automaton: Automaton::Life,
+        save_path: None,
+        load_source: None,
+        load_stdin: false,
+        load_url: None,
+        pattern_name: None,
+        pattern_author: None,
+        seed_pattern: None,
+        seed_pattern_at: None,
+        session_path: PathBuf::from("session.json"),
+        seed: None,
+        bench: None,
+        run_for: None,
+        quiet: false,
+        soup_search: None,
+        soup_size: None,
+        soup_generations: None,
+        stop_on_stable: false,
+        edit_while_running: false,
+        render_mode: RenderMode::Sprites,
+        board: BoardKind::Dense,
+        fullscreen: FullscreenMode::Borderless,
+        smooth: false,
+        http_port: None,
+        rule_chance: RuleChance::default(),
+        tui: false,
+        tui_viewport: None,
+        tick_seconds: clamp_tick_seconds(preferences.tick_seconds)?,
+        theme: parse_theme(&preferences.theme)?,
+        pan: PanSettings {
+            speed: DEFAULT_PAN_SPEED,
+            smoothing: DEFAULT_PAN_SMOOTHING,
+        },
+    };
+    let mut render_mode = None;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--cols" => cli_args.cols = parse_dimension(&arg, args.next())?,
+            "--rows" => cli_args.rows = parse_dimension(&arg, args.next())?,
+            "--cell-size" => {
+                let size: f32 = parse_number(&arg, args.next())?;
+
+                if !size.is_finite() || size <= 0. {
+                    return Err(format!("`{arg}` expects a positive size, got `{size}`"));
+                }
+
+                cli_args.cell_size = CellSize { w: size, h: size };
+            }
+            "--wrap" => cli_args.boundary = BoundaryCondition::Wrap,
+            "--boundary" => {
+                let name = args
+                    .next()
+                    .ok_or_else(|| format!("missing value for `{arg}`"))?;
+                cli_args.boundary = life::parse_boundary(&name)?;
+            }
+            "--expand" => cli_args.expand = true,
+            "--sparse" => cli_args.sparse = true,
+            "--stop-on-stable" => cli_args.stop_on_stable = true,
+            "--birth-chance" | "--death-chance" => {
+                let chance: f64 = parse_number(&arg, args.next())?;
+
+                if !(0. ..=1.).contains(&chance) {
+                    return Err(format!(
+                        "`{arg}` expects a probability from 0 to 1, got `{chance}`"
+                    ));
+                }
+
+                if arg == "--birth-chance" {
+                    cli_args.rule_chance.birth = chance;
+                } else {
+                    cli_args.rule_chance.death = chance;
+                }
+            }
+            "--edit-while-running" => cli_args.edit_while_running = true,
+            "--smooth" => cli_args.smooth = true,
+            "--rule" => {
+                let notation = args
+                    .next()
+                    .ok_or_else(|| format!("missing value for `{arg}`"))?;
+                cli_args.ruleset = life::parse_rule(&notation)?;
+            }
+            "--compare-rule" => {
+                let notation = args
+                    .next()
+                    .ok_or_else(|| format!("missing value for `{arg}`"))?;
+                cli_args.compare_ruleset = Some(life::parse_rule(&notation)?);
+            }
+            "--neighborhood" => {
+                let name = args
+                    .next()
+                    .ok_or_else(|| format!("missing value for `{arg}`"))?;
+                cli_args.neighborhood = life::parse_neighborhood(&name)?;
+            }
+            "--automaton" => {
+                let name = args
+                    .next()
+                    .ok_or_else(|| format!("missing value for `{arg}`"))?;
+                cli_args.automaton = life::parse_automaton(&name)?;
+            }
+            "--board" => {
+                let name = args
+                    .next()
+                    .ok_or_else(|| format!("missing value for `{arg}`"))?;
+                cli_args.board = match name.as_str() {
+                    "dense" => BoardKind::Dense,
+                    "bitset" => BoardKind::Bitset,
+                    _ => {
+                        return Err(format!(
+                            "invalid board `{name}`, expected `dense` or `bitset`"
+                        ));
+                    }
+                };
+            }
+            "--save" => {
+                let path = args
+                    .next()
+                    .ok_or_else(|| format!("missing value for `{arg}`"))?;
+                cli_args.save_path = Some(PathBuf::from(path));
+            }
+            "--seed" => cli_args.seed = Some(parse_number(&arg, args.next())?),
+            "--bench" => cli_args.bench = Some(parse_number(&arg, args.next())?),
+            "--run-for" => cli_args.run_for = Some(parse_number(&arg, args.next())?),
+            "--quiet" => cli_args.quiet = true,
+            "--tui" => cli_args.tui = true,
+            "--tui-viewport" => {
+                let value = args
+                    .next()
+                    .ok_or_else(|| format!("missing value for `{arg}`"))?;
+                let size = value.split_once('x').and_then(|(width, height)| {
+                    Some((width.trim().parse().ok()?, height.trim().parse().ok()?))
+                });
+                let Some(size) = size.filter(|&(width, height)| width > 0 && height > 0) else {
+                    return Err(format!(
+                        "`{arg}` expects a size like `80x48`, got `{value}`"
+                    ));
+                };
+                cli_args.tui_viewport = Some(size);
+            }
+            "--http-port" => cli_args.http_port = Some(parse_number(&arg, args.next())?),
+            "--soup-search" => cli_args.soup_search = Some(parse_number(&arg, args.next())?),
+            "--soup-size" => cli_args.soup_size = Some(parse_dimension(&arg, args.next())?),
+            "--soup-generations" => {
+                cli_args.soup_generations = Some(parse_number(&arg, args.next())?)
+            }
+            "--load" => {
+                let path = args
+                    .next()
+                    .ok_or_else(|| format!("missing value for `{arg}`"))?;
+                cli_args.load_source = Some(PatternSource::File(PathBuf::from(path)));
+            }
+            "--load-stdin" => cli_args.load_stdin = true,
+            "--load-url" => {
+                let url = args
+                    .next()
+                    .ok_or_else(|| format!("missing value for `{arg}`"))?;
+                cli_args.load_url = Some(url);
+            }
+            "--pattern-name" => {
+                let name = args
+                    .next()
+                    .ok_or_else(|| format!("missing value for `{arg}`"))?;
+                cli_args.pattern_name = Some(name);
+            }
+            "--pattern-author" => {
+                let author = args
+                    .next()
+                    .ok_or_else(|| format!("missing value for `{arg}`"))?;
+                cli_args.pattern_author = Some(author);
+            }
+            "--seed-pattern" => {
+                let name = args
+                    .next()
+                    .ok_or_else(|| format!("missing value for `{arg}`"))?;
+                let index = patterns::PATTERNS
+                    .iter()
+                    .position(|pattern| pattern.name == name)
+                    .ok_or_else(|| {
+                        let names: Vec<_> = patterns::PATTERNS
+                            .iter()
+                            .map(|pattern| pattern.name)
+                            .collect();
+                        format!(
+                            "unknown pattern `{name}`, expected one of: {}",
+                            names.join(", ")
+                        )
+                    })?;
+                cli_args.seed_pattern = Some(index);
+            }
+            "--at" => {
+                let value = args
+                    .next()
+                    .ok_or_else(|| format!("missing value for `{arg}`"))?;
+                let position = value
+                    .split_once(',')
+                    .and_then(|(x, y)| Some((x.trim().parse().ok()?, y.trim().parse().ok()?)));
+                let Some(position) = position else {
+                    return Err(format!(
+                        "`{arg}` expects a position like `50,50`, got `{value}`"
+                    ));
+                };
+                cli_args.seed_pattern_at = Some(position);
+            }
+            "--pan-speed" | "--pan-smoothing" => {
+                let value: f32 = parse_number(&arg, args.next())?;
+
+                if !value.is_finite() || value < 0. {
+                    return Err(format!(
+                        "`{arg}` expects a non-negative number, got `{value}`"
+                    ));
+                }
+
+                if arg == "--pan-speed" {
+                    cli_args.pan.speed = value;
+                } else {
+                    cli_args.pan.smoothing = value;
+                }
+            }
+            "--tick" => {
+                cli_args.tick_seconds = clamp_tick_seconds(parse_number(&arg, args.next())?)?;
+            }
+            "--theme" => {
+                let name = args
+                    .next()
+                    .ok_or_else(|| format!("missing value for `{arg}`"))?;
+                cli_args.theme = parse_theme(&name)?;
+            }
+            "--fullscreen" => {
+                let name = args
+                    .next()
+                    .ok_or_else(|| format!("missing value for `{arg}`"))?;
+                cli_args.fullscreen = match name.as_str() {
+                    "borderless" => FullscreenMode::Borderless,
+                    "exclusive" => FullscreenMode::Exclusive,
+                    _ => {
+                        return Err(format!(
+                            "invalid fullscreen mode `{name}`, expected `borderless` or `exclusive`"
+                        ));
+                    }
+                };
+            }
+            "--render" => {
+                let name = args
+                    .next()
+                    .ok_or_else(|| format!("missing value for `{arg}`"))?;
+                render_mode = Some(match name.as_str() {
+                    "sprites" => RenderMode::Sprites,
+                    "texture" => RenderMode::Texture,
+                    _ => {
+                        return Err(format!(
+                            "invalid render mode `{name}`, expected `sprites` or `texture`"
+                        ));
+                    }
+                });
+            }
+            "--session" => {
+                let path = args
+                    .next()
+                    .ok_or_else(|| format!("missing value for `{arg}`"))?;
+                cli_args.session_path = PathBuf::from(path);
+            }
+            _ => return Err(format!("unknown argument `{arg}`")),
+        }
+    }
+
+    life::check_rule(&cli_args.ruleset, cli_args.neighborhood)?;
+
+    if cli_args
+        .cols
+        .checked_mul(cli_args.rows)
+        .is_none_or(|squares| squares > MAX_BOARD_SQUARES)
+    {
+        return Err(format!(
+            "a {}x{} board is larger than the {MAX_BOARD_SQUARES} squares a board can have",
+            cli_args.cols, cli_args.rows
+        ));
+    }
+
+    if let Some(compare_ruleset) = &cli_args.compare_ruleset {
+        life::check_rule(compare_ruleset, cli_args.neighborhood)?;
+    }
+
+    if cli_args.run_for.is_some() && cli_args.bench.is_some() {
+        return Err("`--run-for` and `--bench` cannot be combined".to_string());
+    }
+
+    if cli_args.quiet && cli_args.run_for.is_none() {
+        return Err("`--quiet` requires `--run-for`".to_string());
+    }
+
+    if cli_args.soup_search.is_some() && (cli_args.run_for.is_some() || cli_args.bench.is_some()) {
+        return Err("`--soup-search` cannot be combined with `--run-for` or `--bench`".to_string());
+    }
+
+    if cli_args.tui
+        && (cli_args.run_for.is_some()
+            || cli_args.bench.is_some()
+            || cli_args.soup_search.is_some())
+    {
+        return Err(
+            "`--tui` cannot be combined with `--run-for`, `--bench` or `--soup-search`".to_string(),
+        );
+    }
+
+    if cli_args.tui {
+        for (flag, used) in [
+            ("--sparse", cli_args.sparse),
+            ("--expand", cli_args.expand),
+            ("--compare-rule", cli_args.compare_ruleset.is_some()),
+            ("--http-port", cli_args.http_port.is_some()),
+        ] {
+            if used {
+                return Err(format!("`--tui` does not support `{flag}`"));
+            }
+        }
+    }
+
+    // Runs without a window step the board exactly, and the comparison board has nothing
+    // to compare against if only one side is noisy.
+    if !cli_args.rule_chance.is_exact() {
+        for (flag, used) in [
+            ("--run-for", cli_args.run_for.is_some()),
+            ("--bench", cli_args.bench.is_some()),
+            ("--soup-search", cli_args.soup_search.is_some()),
+            ("--tui", cli_args.tui),
+            ("--compare-rule", cli_args.compare_ruleset.is_some()),
+        ] {
+            if used {
+                return Err(format!(
+                    "`--birth-chance` and `--death-chance` do not work with `{flag}`"
+                ));
+            }
+        }
+    }
+
+    if cli_args.tui_viewport.is_some() && !cli_args.tui {
+        return Err("`--tui-viewport` requires `--tui`".to_string());
+    }
+
+    if cli_args.soup_search.is_none()
+        && (cli_args.soup_size.is_some() || cli_args.soup_generations.is_some())
+    {
+        return Err("`--soup-size` and `--soup-generations` require `--soup-search`".to_string());
+    }
+
+    if let Some(size) = cli_args
+        .soup_size
+        .filter(|&size| size > cli_args.cols || size > cli_args.rows)
+    {
+        return Err(format!(
+            "`--soup-size` {size} does not fit on a {}x{} board",
+            cli_args.cols, cli_args.rows
+        ));
+    }
+
+    if cli_args.boundary != BoundaryCondition::Dead && cli_args.expand {
+        return Err("`--expand` only works with the `dead` boundary".to_string());
+    }
+
+    if cli_args.automaton == Automaton::BriansBrain && cli_args.soup_search.is_some() {
+        return Err("`--soup-search` only works with the `life` automaton".to_string());
+    }
+
+    if cli_args.automaton == Automaton::BriansBrain && cli_args.sparse {
+        return Err("`--sparse` only works with the `life` automaton".to_string());
+    }
+
+    if cli_args.automaton == Automaton::BriansBrain && cli_args.compare_ruleset.is_some() {
+        return Err("`--compare-rule` only works with the `life` automaton".to_string());
+    }
+
+    if cli_args.automaton == Automaton::BriansBrain && cli_args.ruleset.states > 2 {
+        return Err("Generations rules only work with the `life` automaton".to_string());
+    }
+
+    if let Some(flag) = two_state_flag(&cli_args).filter(|_| cli_args.ruleset.states > 2) {
+        return Err(format!("`{flag}` does not support Generations rules"));
+    }
+
+    if cli_args
+        .compare_ruleset
+        .as_ref()
+        .is_some_and(|ruleset| ruleset.states > 2)
+    {
+        return Err("`--compare-rule` does not support Generations rules".to_string());
+    }
+
+    if cfg!(not(feature = "http")) && cli_args.http_port.is_some() {
+        return Err("`--http-port` needs a build with `--features http`".to_string());
+    }
+
+    if cli_args.http_port.is_some() && (cli_args.bench.is_some() || cli_args.soup_search.is_some())
+    {
+        return Err("`--http-port` does not work with `--bench` or `--soup-search`".to_string());
+    }
+
+    // The bitset has nowhere to keep dying states, and the benchmark draws the board
+    // from the dense one's states.
+    if cli_args.board == BoardKind::Bitset {
+        for (flag, used) in [
+            (
+                "--automaton brians-brain",
+                cli_args.automaton == Automaton::BriansBrain,
+            ),
+            ("--sparse", cli_args.sparse),
+            ("--bench", cli_args.bench.is_some()),
+        ] {
+            if used {
+                return Err(format!("`--board bitset` does not support `{flag}`"));
+            }
+        }
+    }
+
+    if cli_args.expand && cli_args.compare_ruleset.is_some() {
+        return Err("`--expand` and `--compare-rule` cannot be combined".to_string());
+    }
+
+    // Boards too big for a sprite per square fall back to the texture unless sprites
+    // were asked for explicitly.
+    cli_args.render_mode = render_mode.unwrap_or(
+        if cli_args.cols as i64 * cli_args.rows as i64 > MAX_SPRITE_SQUARES as i64 {
+            RenderMode::Texture
+        } else {
+            RenderMode::Sprites
+        },
+    );
+
+    // Only a window draws the board into a texture. The terminal and headless runs are
+    // only limited by `MAX_BOARD_SQUARES`.
+    if cli_args.render_mode == RenderMode::Texture && !cli_args.tui && !cli_args.headless() {
+        if cli_args.cols > MAX_TEXTURE_DIMENSION || cli_args.rows > MAX_TEXTURE_DIMENSION {
+            return Err(format!(
+                "texture rendering supports at most {MAX_TEXTURE_DIMENSION} columns and rows"
+            ));
+        }
+
+        if cli_args.expand || cli_args.compare_ruleset.is_some() || cli_args.sparse {
+            return Err(
+                "`--expand`, `--compare-rule` and `--sparse` need `--render sprites`".to_string(),
+            );
+        }
+
+        if cli_args.board == BoardKind::Bitset {
+            return Err("`--board bitset` needs `--render sprites`".to_string());
+        }
+    }
+
+    let pattern_sources = [
+        cli_args.load_source.is_some(),
+        cli_args.load_stdin,
+        cli_args.load_url.is_some(),
+    ];
+
+    if pattern_sources.iter().filter(|&&given| given).count() > 1 {
+        return Err(
+            "only one of `--load`, `--load-stdin` and `--load-url` can be given".to_string(),
+        );
+    }
+
+    if let Some(index) = cli_args.seed_pattern {
+        if pattern_sources.contains(&true) {
+            return Err(
+                "`--seed-pattern` cannot be combined with `--load`, `--load-stdin` or `--load-url`"
+                    .to_string(),
+            );
+        }
+
+        let pattern = &patterns::PATTERNS[index];
+        let (width, height) = pattern.size();
+        let (x, y) = cli_args
+            .seed_pattern_at
+            .unwrap_or(((cli_args.cols - width) / 2, (cli_args.rows - height) / 2));
+
+        if x < 0 || y < 0 || x + width > cli_args.cols || y + height > cli_args.rows {
+            return Err(format!(
+                "`{}` ({width}x{height}) at {x},{y} does not fit on the {}x{} grid",
+                pattern.name, cli_args.cols, cli_args.rows
+            ));
+        }
+
+        cli_args.seed_pattern_at = Some((x, y));
+    } else if cli_args.seed_pattern_at.is_some() {
+        return Err("`--at` requires `--seed-pattern`".to_string());
+    }
+
+    Ok(cli_args)
+}
+
+fn parse_theme(name: &str) -> Result<Theme, String> {
+    THEMES
+        .iter()
+        .find(|theme| theme.name.eq_ignore_ascii_case(name))
+        .copied()
+        .ok_or_else(|| {
+            let names: Vec<_> = THEMES
+                .iter()
+                .map(|theme| format!("`{}`", theme.name))
+                .collect();
+            format!("invalid theme `{name}`, expected {}", names.join(", "))
+        })
+}
+
+impl Default for Preferences {
+    fn default() -> Self {
+        Preferences {
+            tick_seconds: GAME_TICK_SECOND,
+            theme: THEMES[0].name.to_string(),
+            boundary: boundary_name(BoundaryCondition::Dead).to_string(),
+            rule: Ruleset::default().to_string(),
+        }
+    }
+}
+
+/// Reads the saved preferences, falling back to the defaults with a warning when the
+/// file is unreadable or malformed. A missing file is the normal first run.
+fn load_preferences(path: &Path) -> Preferences {
+    let contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Preferences::default(),
+        Err(err) => {
+            eprintln!("failed to read preferences from {}: {err}", path.display());
+            return Preferences::default();
+        }
+    };
+
+    preferences::decode(&contents).unwrap_or_else(|err| {
+        eprintln!("ignoring invalid preferences in {}: {err}", path.display());
+        Preferences::default()
+    })
+}
+
+/// Reads the key bindings, falling back to the defaults the same way [`load_preferences`]
+/// does. The file is only ever written by hand.
+fn load_key_bindings(path: &Path) -> KeyBindings {
+    let contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return KeyBindings::default(),
+        Err(err) => {
+            eprintln!("failed to read key bindings from {}: {err}", path.display());
+            return KeyBindings::default();
+        }
+    };
+
+    key_bindings::decode(&contents).unwrap_or_else(|err| {
+        eprintln!("ignoring invalid key bindings in {}: {err}", path.display());
+        KeyBindings::default()
+    })
+}
+
+fn current_preferences(cli_args: &CliArgs) -> Preferences {
+    Preferences {
+        tick_seconds: cli_args.tick_seconds,
+        theme: cli_args.theme.name.to_string(),
+        boundary: boundary_name(cli_args.boundary).to_string(),
+        rule: cli_args.ruleset.to_string(),
+    }
+}
+
+fn boundary_name(boundary: BoundaryCondition) -> &'static str {
+    match boundary {
+        BoundaryCondition::Dead => "dead",
+        BoundaryCondition::Alive => "alive",
+        BoundaryCondition::Wrap => "wrap",
+    }
+}
+
+fn save_preferences(
+    mut app_exit: EventReader<AppExit>,
+    mut preferences: ResMut<Preferences>,
+    fixed_time: Res<Time<Fixed>>,
+    theme: Res<Theme>,
+    boundary: Res<ActiveBoundary>,
+    ruleset: Res<ActiveRuleset>,
+) {
+    if app_exit.read().next().is_none() {
+        return;
+    }
+
+    preferences.tick_seconds = fixed_time.timestep().as_secs_f32();
+    preferences.theme = theme.name.to_string();
+    preferences.boundary = boundary_name(boundary.0).to_string();
+    preferences.rule = ruleset.0.to_string();
+
+    match preferences::encode(&preferences)
+        .and_then(|encoded| fs::write(PREFERENCES_PATH, encoded).map_err(|err| err.to_string()))
+    {
+        Ok(()) => info!("saved preferences to {PREFERENCES_PATH}"),
+        Err(err) => error!("failed to save preferences to {PREFERENCES_PATH}: {err}"),
+    }
+}
+
+/// Clamps a tick length to the supported range. NaN would pass straight through
+/// `clamp` and panic once it reaches a `Duration`, so it is rejected along with the
+/// infinities.
+fn clamp_tick_seconds(seconds: f32) -> Result<f32, String> {
+    if !seconds.is_finite() {
+        return Err(format!(
+            "tick length must be a finite number of seconds, got {seconds}"
+        ));
+    }
+
+    Ok(seconds.clamp(MIN_GAME_TICK_SECOND, MAX_GAME_TICK_SECOND))
+}
+
+fn parse_number<T: FromStr>(flag: &str, value: Option<String>) -> Result<T, String> {
+    let value = value.ok_or_else(|| format!("missing value for `{flag}`"))?;
+
+    value
+        .parse()
+        .map_err(|_| format!("`{flag}` expects a number, got `{value}`"))
+}
+
+fn parse_dimension(flag: &str, value: Option<String>) -> Result<i32, String> {
+    let value = value.ok_or_else(|| format!("missing value for `{flag}`"))?;
+
+    match value.parse::<i32>() {
+        Ok(dimension) if dimension > 0 => Ok(dimension),
+        _ => Err(format!(
+            "`{flag}` expects a positive integer, got `{value}`"
+        )),
+    }
+}
+
+fn setup(
+    mut commands: Commands,
+    mut square_map: ResMut<SquareMap>,
+    mut gizmo_config_store: ResMut<GizmoConfigStore>,
+    grid_config: Res<GridConfig>,
+    theme: Res<Theme>,
+    cell_size: Res<CellSize>,
+) {
+    commands.spawn((Camera2d, MainCamera, IsDefaultUiCamera));
+    commands.spawn((
+        Camera2d,
+        MinimapCamera,
+        Camera {
+            order: 1,
+            ..default()
+        },
+        RenderLayers::from_layers(&[0, MINIMAP_LAYER]),
+        Transform::from_translation(
+            Vec2::from(grid_config.geometry(&cell_size).center()).extend(0.),
+        ),
+    ));
+
+    let (minimap_gizmos, _) = gizmo_config_store.config_mut::<MinimapGizmos>();
+    minimap_gizmos.render_layers = RenderLayers::layer(MINIMAP_LAYER);
+    spawn_population_graph_camera(&mut commands, &mut gizmo_config_store);
+    commands.spawn((
+        HudText,
+        Interaction::default(),
+        Text::default(),
+        TextColor(Color::WHITE),
+        BackgroundColor(Color::BLACK.with_alpha(0.6)),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(10.),
+            left: Val::Px(10.),
+            padding: UiRect::all(Val::Px(5.)),
+            ..default()
+        },
+    ));
+    commands.spawn((
+        StatusBar,
+        Interaction::default(),
+        Text::default(),
+        TextFont {
+            font_size: 14.,
+            ..default()
+        },
+        TextColor(Color::WHITE),
+        BackgroundColor(Color::BLACK.with_alpha(0.6)),
+        Node {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(0.),
+            left: Val::Px(0.),
+            right: Val::Px(0.),
+            min_height: Val::Px(STATUS_BAR_HEIGHT),
+            padding: UiRect::axes(Val::Px(10.), Val::Px(4.)),
+            ..default()
+        },
+    ));
+    spawn_control_panel(&mut commands);
+    commands.spawn((
+        CellTooltip,
+        Text::default(),
+        TextFont {
+            font_size: 14.,
+            ..default()
+        },
+        TextColor(Color::WHITE),
+        BackgroundColor(Color::BLACK.with_alpha(0.8)),
+        Visibility::Hidden,
+        // Drawn above every other node, so it is never hidden behind the panels.
+        GlobalZIndex(1),
+        Node {
+            position_type: PositionType::Absolute,
+            padding: UiRect::all(Val::Px(4.)),
+            ..default()
+        },
+    ));
+    commands.spawn((
+        DiagnosticsText,
+        Interaction::default(),
+        Text::default(),
+        TextColor(Color::WHITE),
+        BackgroundColor(Color::BLACK.with_alpha(0.6)),
+        Visibility::Hidden,
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(10.),
+            right: Val::Px(10.),
+            padding: UiRect::all(Val::Px(5.)),
+            ..default()
+        },
+    ));
+
+    commands.spawn((
+        CursorHighlight,
+        Sprite {
+            color: Color::srgba(1., 0.8, 0., 0.5),
+            custom_size: Some(Vec2::new(cell_size.w, cell_size.h)),
+            ..default()
+        },
+        Transform::from_xyz(0., 0., 1.),
+        Visibility::Hidden,
+    ));
+
+    for row in 0..grid_config.rows {
+        for col in 0..grid_config.cols {
+            let square_id = spawn_square(&mut commands, &grid_config, &cell_size, &theme, col, row);
+
+            square_map.map.push(SquareIdentifier { entity: square_id })
+        }
+    }
+}
+
+fn spawn_square(
+    commands: &mut Commands,
+    grid_config: &GridConfig,
+    cell_size: &CellSize,
+    theme: &Theme,
+    col: i32,
+    row: i32,
+) -> Entity {
+    commands
+        .spawn((
+            Square { x: col, y: row },
+            Sprite {
+                color: theme.dead,
+                custom_size: Some(Vec2::new(cell_size.w, cell_size.h)),
+                ..default()
+            },
+            Transform::from_translation(
+                Vec2::from(grid_config.geometry(cell_size).cell_to_world(col, row)).extend(0.),
+            ),
+        ))
+        .id()
+}
+
+fn expand_grid(
+    commands: &mut Commands,
+    square_map: &mut SquareMap,
+    grid_config: &mut GridConfig,
+    cell_size: &CellSize,
+    theme: &Theme,
+    q_squares: &mut Query<(&mut Square, Has<Alive>, Option<&Dying>)>,
+) {
+    let old_cols = grid_config.cols;
+    let old_rows = grid_config.rows;
+
+    // Growing by one square on every side keeps the grid centered, so the existing
+    // sprites stay where they are and only their coordinates shift by one.
+    grid_config.cols += 2;
+    grid_config.rows += 2;
+
+    let mut map = Vec::with_capacity(grid_config.cols as usize * grid_config.rows as usize);
+
+    for row in 0..grid_config.rows {
+        for col in 0..grid_config.cols {
+            let is_old = (1..=old_cols).contains(&col) && (1..=old_rows).contains(&row);
+            let square_id = match square_map.get(col - 1, row - 1) {
+                Some(square_id) if is_old => square_id.clone(),
+                _ => SquareIdentifier {
+                    entity: spawn_square(commands, grid_config, cell_size, theme, col, row),
+                },
+            };
+
+            map.push(square_id);
+        }
+    }
+
+    square_map.cols = grid_config.cols;
+    square_map.rows = grid_config.rows;
+    square_map.map = map;
+
+    for (mut square, _, _) in q_squares.iter_mut() {
+        square.x += 1;
+        square.y += 1;
+    }
+
+    info!("expanded grid to {}x{}", grid_config.cols, grid_config.rows);
+}
+
+fn handle_scroll(
+    mut evr_scroll: EventReader<MouseWheel>,
+    q_window: Query<&Window, With<PrimaryWindow>>,
+    mut query_camera: Query<(&mut Transform, &mut OrthographicProjection), With<MainCamera>>,
+    zoom_limits: Res<ZoomLimits>,
+) {
+    let Ok((mut camera_transform, mut camera_projection)) = query_camera.get_single_mut() else {
+        evr_scroll.clear();
+        return;
+    };
+
+    // Offset of the cursor from the window center in screen pixels, with y pointing up
+    // like world space. Keeping `translation + offset * scale` fixed while the scale
+    // changes zooms toward whatever is under the cursor.
+    let cursor_offset = q_window
+        .get_single()
+        .ok()
+        .and_then(|window| {
+            window
+                .cursor_position()
+                .map(|cursor| cursor - window.size() / 2.)
+        })
+        .map(|offset| Vec2::new(offset.x, -offset.y))
+        .unwrap_or(Vec2::ZERO);
+
+    for ev in evr_scroll.read() {
+        let old_scale = camera_projection.scale;
+
+        camera_projection.scale /= if ev.y.is_sign_positive() { 1.1 } else { 0.9 };
+        camera_projection.scale = camera_projection
+            .scale
+            .clamp(zoom_limits.min, zoom_limits.max);
+
+        let shift = cursor_offset * (old_scale - camera_projection.scale);
+        camera_transform.translation += shift.extend(0.);
+    }
+}
+
+fn handle_state_change(
+    keys: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    game_state: Res<State<GameState>>,
+    mut next_game_state: ResMut<NextState<GameState>>,
+) {
+    if bindings.just_pressed(&keys, Action::Start) {
+        match game_state.get() {
+            GameState::Placing => next_game_state.set(GameState::Ongoing),
+            GameState::Ongoing => next_game_state.set(GameState::Placing),
+        }
+    }
+}
+
+fn handle_pause(
+    keys: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    mut paused: ResMut<Paused>,
+    mut pause_at: ResMut<PauseAtGeneration>,
+) {
+    if bindings.just_pressed(&keys, Action::Pause) {
+        paused.0 = !paused.0;
+        pause_at.0 = None;
+    }
+}
+
+fn digit_value(key: KeyCode) -> Option<u64> {
+    let digit = match key {
+        KeyCode::Digit0 | KeyCode::Numpad0 => 0,
+        KeyCode::Digit1 | KeyCode::Numpad1 => 1,
+        KeyCode::Digit2 | KeyCode::Numpad2 => 2,
+        KeyCode::Digit3 | KeyCode::Numpad3 => 3,
+        KeyCode::Digit4 | KeyCode::Numpad4 => 4,
+        KeyCode::Digit5 | KeyCode::Numpad5 => 5,
+        KeyCode::Digit6 | KeyCode::Numpad6 => 6,
+        KeyCode::Digit7 | KeyCode::Numpad7 => 7,
+        KeyCode::Digit8 | KeyCode::Numpad8 => 8,
+        KeyCode::Digit9 | KeyCode::Numpad9 => 9,
+        _ => return None,
+    };
+
+    Some(digit)
+}
+
+/// Typing a number and then pressing `.` runs exactly that many generations and pauses.
+/// Delete forgets the number typed so far.
+fn handle_step_count(
+    keys: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    mut step_count: ResMut<StepCount>,
+    mut pause_at: ResMut<PauseAtGeneration>,
+    mut paused: ResMut<Paused>,
+    generation: Res<Generation>,
+) {
+    for &key in keys.get_just_pressed() {
+        // A leading zero would not change the count, so 0 on its own still resets the
+        // camera.
+        let Some(digit) = digit_value(key).filter(|&digit| digit != 0 || step_count.0.is_some())
+        else {
+            continue;
+        };
+
+        step_count.0 = Some(
+            step_count
+                .0
+                .unwrap_or_default()
+                .saturating_mul(10)
+                .saturating_add(digit),
+        );
+    }
+
+    if keys.just_pressed(KeyCode::Delete) {
+        step_count.0 = None;
+    }
+
+    if !bindings.just_pressed(&keys, Action::Step) {
+        return;
+    }
+
+    let Some(count) = step_count.0.take() else {
+        return;
+    };
+
+    pause_at.0 = Some(generation.0.saturating_add(count));
+    paused.0 = false;
+    info!("stepping {count} generations");
+}
+
+/// Pauses once the generation a counted step was heading for is on screen.
+fn pause_at_target(generation: u64, paused: &mut Paused, pause_at: &mut PauseAtGeneration) {
+    if pause_at.0.is_some_and(|target| generation >= target) {
+        paused.0 = true;
+        pause_at.0 = None;
+        info!("paused at generation {generation}");
+    }
+}
+
+fn handle_clear(
+    keys: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    one_shot_systems: Res<OneShotSystems>,
+    mut commands: Commands,
+) {
+    if ctrl_pressed(&keys) || !bindings.just_pressed(&keys, Action::Clear) {
+        return;
+    }
+
+    commands.run_system(one_shot_systems.clear_board);
+}
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn clear_board(
+    mut q_alive_squares: Query<
+        (Entity, &mut Sprite, Has<Alive>),
+        Or<(With<Alive>, With<DeadSince>, With<Dying>)>,
+    >,
+    mut placement_history: ResMut<PlacementHistory>,
+    mut render_input: ResMut<RenderInput>,
+    mut logic_state: ResMut<LogicState>,
+    mut generation: ResMut<Generation>,
+    (mut history, mut settle_start): (ResMut<History>, ResMut<SettleStart>),
+    mut credits: ResMut<PatternCredits>,
+    theme: Res<Theme>,
+    mut commands: Commands,
+) {
+    placement_history.finish_stroke();
+    credits.forget_loaded();
+
+    for (entity, mut sprite, is_alive) in q_alive_squares.iter_mut() {
+        sprite.color = theme.dead;
+        commands
+            .entity(entity)
+            .remove::<(Alive, Age, DeadSince, Dying)>();
+
+        if is_alive {
+            placement_history.record(entity, false);
+        }
+    }
+
+    placement_history.finish_stroke();
+    placement_history.replaced = true;
+
+    render_input.entities_born.clear();
+    render_input.entities_that_died.clear();
+    render_input.entities_dying.clear();
+    *logic_state = LogicState::CalculationNeeded;
+    generation.0 = 0;
+    history.0.clear();
+    settle_start.0 = None;
+}
+
+fn reset_generation(
+    mut generation: ResMut<Generation>,
+    mut tick_rate: ResMut<TickRate>,
+    mut history: ResMut<History>,
+    (mut stable, mut settle_start): (ResMut<Stable>, ResMut<SettleStart>),
+    (mut step_count, mut pause_at): (ResMut<StepCount>, ResMut<PauseAtGeneration>),
+) {
+    generation.0 = 0;
+    *tick_rate = TickRate::default();
+    history.0.clear();
+    stable.0 = None;
+    settle_start.0 = None;
+    step_count.0 = None;
+    pause_at.0 = None;
+}
+
+fn count_population(q_alive_squares: Query<(), With<Alive>>, mut population: ResMut<Population>) {
+    population.set_if_neq(Population(q_alive_squares.iter().count()));
+}
+
+fn record_population(
+    generation: Res<Generation>,
+    population: Res<Population>,
+    mut population_history: ResMut<PopulationHistory>,
+) {
+    let history = &mut population_history.0;
+
+    // Rewinding or resetting the board drops the generations that no longer happened.
+    while history
+        .back()
+        .is_some_and(|&(recorded, _)| recorded >= generation.0)
+    {
+        history.pop_back();
+    }
+
+    history.push_back((generation.0, population.0));
+
+    if history.len() > POPULATION_GRAPH_LENGTH {
+        history.pop_front();
+    }
+}
+
+fn handle_extinction(
+    auto_stop_on_death: Res<AutoStopOnDeath>,
+    mut died_out: EventReader<BoardDiedOut>,
+    generation: Res<Generation>,
+    mut next_game_state: ResMut<NextState<GameState>>,
+) {
+    // Only a generation ends the run, so starting on or clearing to an empty board
+    // keeps running until something is placed.
+    if died_out.read().count() == 0 || !auto_stop_on_death.0 {
+        return;
+    }
+
+    info!("board died out after {} generations", generation.0);
+    next_game_state.set(GameState::Placing);
+}
+
+fn update_hud_text(
+    generation: Res<Generation>,
+    population: Res<Population>,
+    fixed_time: Res<Time<Fixed>>,
+    steps_per_tick: Res<StepsPerTick>,
+    stable: Res<Stable>,
+    quit_prompt: Res<QuitPrompt>,
+    mut q_hud_text: Query<&mut Text, With<HudText>>,
+) {
+    let mut hud = format!(
+        "Generation: {}\nPopulation: {}\nTick: {:.3}s x{}",
+        generation.0,
+        population.0,
+        fixed_time.timestep().as_secs_f32(),
+        steps_per_tick.0
+    );
+
+    match stable.0 {
+        Some(1) => hud.push_str("\nSTABLE"),
+        Some(period) => hud.push_str(&format!("\nSTABLE (period {period})")),
+        None => {}
+    }
+
+    if quit_prompt.0 {
+        hud.push_str("\nPress Esc again to quit, any other key to cancel");
+    }
+
+    for mut text in q_hud_text.iter_mut() {
+        if text.0 != hud {
+            text.0 = hud.clone();
+        }
+    }
+}
+
+fn update_pointer_over_ui(
+    q_interactions: Query<&Interaction, With<Node>>,
+    mut pointer_over_ui: ResMut<PointerOverUi>,
+) {
+    pointer_over_ui.0 = q_interactions
+        .iter()
+        .any(|interaction| *interaction != Interaction::None);
+}
+
+fn spawn_control_panel(commands: &mut Commands) {
+    let row = Node {
+        flex_direction: FlexDirection::Row,
+        align_items: AlignItems::Center,
+        column_gap: Val::Px(5.),
+        ..default()
+    };
+    let font = TextFont {
+        font_size: 14.,
+        ..default()
+    };
+
+    let spawn_button = |parent: &mut ChildBuilder, label: &str, action: PanelAction| {
+        parent
+            .spawn((
+                Button,
+                action,
+                BackgroundColor(PANEL_BUTTON_COLOR),
+                Node {
+                    padding: UiRect::axes(Val::Px(8.), Val::Px(2.)),
+                    ..default()
+                },
+            ))
+            .with_child((Text::new(label), font.clone(), TextColor(Color::WHITE)));
+    };
+    let spawn_value = |parent: &mut ChildBuilder, value: PanelValue| {
+        parent.spawn((
+            value,
+            Text::default(),
+            font.clone(),
+            TextColor(Color::WHITE),
+            Node {
+                min_width: Val::Px(110.),
+                ..default()
+            },
+        ));
+    };
+    let spawn_slider = |parent: &mut ChildBuilder, slider: PanelSlider| {
+        parent
+            .spawn((
+                slider,
+                Interaction::default(),
+                RelativeCursorPosition::default(),
+                BackgroundColor(PANEL_BUTTON_COLOR),
+                Node {
+                    width: Val::Px(SLIDER_WIDTH),
+                    height: Val::Px(SLIDER_HEIGHT),
+                    ..default()
+                },
+            ))
+            .with_child((
+                SliderFill(slider),
+                BackgroundColor(PANEL_BUTTON_PRESSED_COLOR),
+                Node {
+                    width: Val::Percent(0.),
+                    height: Val::Percent(100.),
+                    ..default()
+                },
+            ));
+    };
+
+    commands
+        .spawn((
+            ControlPanel,
+            Interaction::default(),
+            BackgroundColor(Color::BLACK.with_alpha(0.6)),
+            Visibility::Hidden,
+            Node {
+                position_type: PositionType::Absolute,
+                top: Val::Px(120.),
+                right: Val::Px(10.),
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(5.),
+                padding: UiRect::all(Val::Px(5.)),
+                ..default()
+            },
+        ))
+        .with_children(|panel| {
+            panel.spawn(row.clone()).with_children(|parent| {
+                spawn_value(parent, PanelValue::Speed);
+                spawn_slider(parent, PanelSlider::Speed);
+            });
+            panel.spawn(row.clone()).with_children(|parent| {
+                spawn_value(parent, PanelValue::Density);
+                spawn_slider(parent, PanelSlider::Density);
+            });
+            panel
+                .spawn((
+                    Button,
+                    PanelAction::RuleMenu,
+                    BackgroundColor(PANEL_BUTTON_COLOR),
+                    Node {
+                        padding: UiRect::axes(Val::Px(8.), Val::Px(2.)),
+                        ..default()
+                    },
+                ))
+                .with_child((
+                    PanelValue::Rule,
+                    Text::default(),
+                    font.clone(),
+                    TextColor(Color::WHITE),
+                ));
+            panel
+                .spawn((
+                    RuleMenu,
+                    Node {
+                        display: Display::None,
+                        flex_direction: FlexDirection::Column,
+                        row_gap: Val::Px(2.),
+                        ..default()
+                    },
+                ))
+                .with_children(|parent| {
+                    for (index, preset) in RULE_PRESETS.iter().enumerate() {
+                        spawn_button(parent, preset, PanelAction::PickRule(index));
+                    }
+                });
+            panel.spawn(row).with_children(|parent| {
+                spawn_button(parent, "Clear", PanelAction::Clear);
+                spawn_button(parent, "Random", PanelAction::Random);
+                spawn_button(parent, "Step", PanelAction::Step);
+            });
+        });
+}
+
+fn handle_control_panel_toggle(
+    keys: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    mut q_panel: Query<&mut Visibility, With<ControlPanel>>,
+) {
+    if !bindings.just_pressed(&keys, Action::ControlPanel) {
+        return;
+    }
+
+    for mut visibility in q_panel.iter_mut() {
+        *visibility = match *visibility {
+            Visibility::Hidden => Visibility::Visible,
+            _ => Visibility::Hidden,
+        };
+    }
+}
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn handle_panel_buttons(
+    mut q_buttons: Query<
+        (&Interaction, &PanelAction, &mut BackgroundColor),
+        (Changed<Interaction>, With<Button>),
+    >,
+    mut q_rule_menu: Query<&mut Node, With<RuleMenu>>,
+    game_state: Res<State<GameState>>,
+    mut logic_state: ResMut<LogicState>,
+    mut paused: ResMut<Paused>,
+    (mut ruleset, neighborhood, automaton): (
+        ResMut<ActiveRuleset>,
+        Res<ActiveNeighborhood>,
+        Res<ActiveAutomaton>,
+    ),
+    one_shot_systems: Res<OneShotSystems>,
+    mut commands: Commands,
+) {
+    for (interaction, action, mut background) in q_buttons.iter_mut() {
+        background.0 = match interaction {
+            Interaction::Pressed => PANEL_BUTTON_PRESSED_COLOR,
+            Interaction::Hovered => PANEL_BUTTON_HOVER_COLOR,
+            Interaction::None => PANEL_BUTTON_COLOR,
+        };
+
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+
+        match action {
+            PanelAction::Clear => commands.run_system(one_shot_systems.clear_board),
+            PanelAction::Random => {
+                if *game_state.get() == GameState::Placing {
+                    commands.run_system(one_shot_systems.random_fill);
+                } else {
+                    info!("Random fill is only available while placing");
+                }
+            }
+            PanelAction::Step => {
+                if *game_state.get() == GameState::Placing {
+                    info!("Start the game before stepping");
+                    continue;
+                }
+
+                paused.0 = true;
+                commands.run_system(one_shot_systems.single_step);
+            }
+            PanelAction::RuleMenu => {
+                if automaton.0 != Automaton::Life {
+                    info!("Brian's Brain has fixed rules");
+                    continue;
+                }
+
+                for mut menu in q_rule_menu.iter_mut() {
+                    menu.display = match menu.display {
+                        Display::None => Display::Flex,
+                        _ => Display::None,
+                    };
+                }
+            }
+            PanelAction::PickRule(index) => {
+                for mut menu in q_rule_menu.iter_mut() {
+                    menu.display = Display::None;
+                }
+
+                let rule = life::parse_rule(RULE_PRESETS[*index]).expect("presets are valid rules");
+                if let Err(err) = life::check_rule(&rule, neighborhood.0) {
+                    info!("{err}");
+                    continue;
+                }
+
+                info!("Switched rule to {rule}");
+                ruleset.0 = rule;
+                // A generation calculated ahead of time used the previous rule.
+                if matches!(*logic_state, LogicState::Calculated) {
+                    *logic_state = LogicState::CalculationNeeded;
+                }
+            }
+        }
+    }
+}
+
+/// Where a tick of `tick_second` sits along the speed slider, from 0 at the slowest
+/// tick to 1 at the fastest. Ticks are spaced by ratio, like the speed keys.
+fn speed_slider_position(tick_second: f32) -> f32 {
+    ((tick_second / MAX_GAME_TICK_SECOND).ln() / (MIN_GAME_TICK_SECOND / MAX_GAME_TICK_SECOND).ln())
+        .clamp(0., 1.)
+}
+
+/// The tick length at `position` along the speed slider.
+fn speed_slider_tick(position: f32) -> f32 {
+    MAX_GAME_TICK_SECOND
+        * (MIN_GAME_TICK_SECOND / MAX_GAME_TICK_SECOND).powf(position.clamp(0., 1.))
+}
+
+fn handle_panel_sliders(
+    q_sliders: Query<(&Interaction, &RelativeCursorPosition, &PanelSlider)>,
+    mut fixed_time: ResMut<Time<Fixed>>,
+    mut fill_density: ResMut<FillDensity>,
+) {
+    for (interaction, cursor, slider) in q_sliders.iter() {
+        // A pressed track stays pressed while dragging off it, so the ends are clamped.
+        let Some(position) = cursor
+            .normalized
+            .filter(|_| *interaction == Interaction::Pressed)
+            .map(|position| position.x.clamp(0., 1.))
+        else {
+            continue;
+        };
+
+        match slider {
+            PanelSlider::Speed => {
+                let tick = Duration::from_secs_f32(speed_slider_tick(position));
+                if fixed_time.timestep() != tick {
+                    fixed_time.set_timestep(tick);
+                }
+            }
+            PanelSlider::Density => {
+                let density = (position / FILL_DENSITY_STEP).round() * FILL_DENSITY_STEP;
+                if fill_density.0 != density {
+                    fill_density.0 = density;
+                }
+            }
+        }
+    }
+}
+
+fn update_panel_labels(
+    fixed_time: Res<Time<Fixed>>,
+    fill_density: Res<FillDensity>,
+    ruleset: Res<ActiveRuleset>,
+    automaton: Res<ActiveAutomaton>,
+    mut q_values: Query<(&PanelValue, &mut Text)>,
+    mut q_fills: Query<(&SliderFill, &mut Node)>,
+) {
+    for (fill, mut node) in q_fills.iter_mut() {
+        let position = match fill.0 {
+            PanelSlider::Speed => speed_slider_position(fixed_time.timestep().as_secs_f32()),
+            PanelSlider::Density => fill_density.0,
+        };
+        let width = Val::Percent(position * 100.);
+
+        if node.width != width {
+            node.width = width;
+        }
+    }
+
+    for (value, mut text) in q_values.iter_mut() {
+        let label = match value {
+            PanelValue::Speed => format!("Tick {:.3}s", fixed_time.timestep().as_secs_f32()),
+            PanelValue::Density => format!("Density {:.0}%", fill_density.0 * 100.),
+            PanelValue::Rule => match automaton.0 {
+                Automaton::Life => format!("Rule {}", ruleset.0),
+                Automaton::BriansBrain => "Brian's Brain".to_string(),
+            },
+        };
+
+        if text.0 != label {
+            text.0 = label;
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn update_status_bar(
+    game_state: Res<State<GameState>>,
+    paused: Res<Paused>,
+    fixed_time: Res<Time<Fixed>>,
+    steps_per_tick: Res<StepsPerTick>,
+    step_count: Res<StepCount>,
+    ruleset: Res<ActiveRuleset>,
+    automaton: Res<ActiveAutomaton>,
+    bindings: Res<KeyBindings>,
+    mut q_status_bar: Query<&mut Text, With<StatusBar>>,
+) {
+    let mode = match (game_state.get(), paused.0) {
+        (GameState::Placing, _) => "PLACING",
+        (GameState::Ongoing, true) => "PAUSED",
+        (GameState::Ongoing, false) => "RUNNING",
+    };
+    let rule = match automaton.0 {
+        Automaton::Life => ruleset.0.to_string(),
+        Automaton::BriansBrain => "Brian's Brain".to_string(),
+    };
+    let key = |action| bindings.label(action);
+    let legend = match game_state.get() {
+        GameState::Placing => format!(
+            "{} start | Click toggle | Shift+drag paint | Right drag erase | Alt+drag select | 1-5 stamp | {random} random | Shift+{random} random in selection | {} clear | {} quit",
+            key(Action::Start),
+            key(Action::Clear),
+            key(Action::Quit),
+            random = key(Action::Random),
+        ),
+        GameState::Ongoing => format!(
+            "{} edit | {} pause | {step} step | 0-9 then {step} step that many | {} rewind | {}/{} speed | {}/{} skip | {} fit | {} quit",
+            key(Action::Start),
+            key(Action::Pause),
+            key(Action::Rewind),
+            key(Action::Faster),
+            key(Action::Slower),
+            key(Action::MoreSteps),
+            key(Action::FewerSteps),
+            key(Action::Fit),
+            key(Action::Quit),
+            step = key(Action::Step),
+        ),
+    };
+    let step_count = step_count
+        .0
+        .map(|count| format!(" | Step {count} ({} to run)", key(Action::Step)))
+        .unwrap_or_default();
+    let status = format!(
+        "{mode} | Tick {:.3}s x{}{step_count} | {rule} | {legend}",
+        fixed_time.timestep().as_secs_f32(),
+        steps_per_tick.0
+    );
+
+    for mut text in q_status_bar.iter_mut() {
+        if text.0 != status {
+            text.0 = status.clone();
+        }
+    }
+}
+
+fn handle_quit(
+    keys: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    population: Res<Population>,
+    mut quit_prompt: ResMut<QuitPrompt>,
+    mut app_exit: EventWriter<AppExit>,
+) {
+    if bindings.just_pressed(&keys, Action::Quit) {
+        if quit_prompt.0 || population.0 == 0 {
+            app_exit.send(AppExit::Success);
+        } else {
+            quit_prompt.0 = true;
+        }
+    } else if quit_prompt.0 && keys.get_just_pressed().next().is_some() {
+        quit_prompt.0 = false;
+    }
+}
+
+/// F11 switches between a window and fullscreen, and Shift+F11 between borderless and
+/// exclusive fullscreen.
+fn handle_window_mode_toggle(
+    keys: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    mut q_window: Query<&mut Window, With<PrimaryWindow>>,
+    mut fullscreen_mode: ResMut<FullscreenMode>,
+) {
+    if !bindings.just_pressed(&keys, Action::Fullscreen) {
+        return;
+    }
+
+    let Ok(mut window) = q_window.get_single_mut() else {
+        return;
+    };
+
+    if shift_pressed(&keys) {
+        *fullscreen_mode = match *fullscreen_mode {
+            FullscreenMode::Borderless => FullscreenMode::Exclusive,
+            FullscreenMode::Exclusive => FullscreenMode::Borderless,
+        };
+        info!("F11 now switches to {:?} fullscreen", *fullscreen_mode);
+
+        if window.mode == WindowMode::Windowed {
+            return;
+        }
+    }
+
+    // Only the window changes here; the camera keeps its translation and scale, and the
+    // projection, minimap and overlays follow the new resolution on their own.
+    if window.mode == WindowMode::Windowed || shift_pressed(&keys) {
+        window.mode = fullscreen_mode.window_mode(MonitorSelection::Current);
+        window.resizable = false;
+    } else {
+        window.mode = WindowMode::Windowed;
+        window.resizable = true;
+        window.resolution.set(1280., 720.);
+    }
+}
+
+fn handle_minimap_toggle(
+    keys: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    mut q_minimap_camera: Query<&mut Camera, With<MinimapCamera>>,
+) {
+    if !bindings.just_pressed(&keys, Action::Minimap) {
+        return;
+    }
+
+    for mut camera in q_minimap_camera.iter_mut() {
+        camera.is_active = !camera.is_active;
+    }
+}
+
+fn spawn_population_graph_camera(
+    commands: &mut Commands,
+    gizmo_config_store: &mut GizmoConfigStore,
+) {
+    commands.spawn((
+        Camera2d,
+        PopulationGraphCamera,
+        Camera {
+            order: 2,
+            is_active: false,
+            clear_color: ClearColorConfig::None,
+            ..default()
+        },
+        RenderLayers::layer(POPULATION_GRAPH_LAYER),
+    ));
+
+    let (graph_gizmos, _) = gizmo_config_store.config_mut::<PopulationGraphGizmos>();
+    graph_gizmos.render_layers = RenderLayers::layer(POPULATION_GRAPH_LAYER);
+}
+
+fn handle_population_graph_toggle(
+    keys: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    mut q_graph_camera: Query<&mut Camera, With<PopulationGraphCamera>>,
+) {
+    if !bindings.just_pressed(&keys, Action::PopulationGraph) {
+        return;
+    }
+
+    for mut camera in q_graph_camera.iter_mut() {
+        camera.is_active = !camera.is_active;
+    }
+}
+
+fn draw_population_graph(
+    mut gizmos: Gizmos<PopulationGraphGizmos>,
+    q_window: Query<&Window, With<PrimaryWindow>>,
+    q_graph_camera: Query<&Camera, With<PopulationGraphCamera>>,
+    population_history: Res<PopulationHistory>,
+    theme: Res<Theme>,
+) {
+    let (Ok(window), Ok(camera)) = (q_window.get_single(), q_graph_camera.get_single()) else {
+        return;
+    };
+
+    if !camera.is_active {
+        return;
+    }
+
+    let min = Vec2::new(
+        POPULATION_GRAPH_MARGIN,
+        window.height() - STATUS_BAR_HEIGHT - POPULATION_GRAPH_MARGIN - POPULATION_GRAPH_SIZE.y,
+    );
+    let rect = Rect::from_corners(min, min + POPULATION_GRAPH_SIZE);
+    let half_size = window.size() / 2.;
+    let to_world = |point: Vec2| Vec2::new(point.x - half_size.x, half_size.y - point.y);
+
+    gizmos.rect_2d(
+        Isometry2d::from_translation(to_world(rect.center())),
+        rect.size(),
+        Color::WHITE.with_alpha(0.6),
+    );
+
+    let peak = population_history
+        .0
+        .iter()
+        .map(|&(_, population)| population)
+        .max()
+        .unwrap_or(0)
+        .max(1);
+    let spacing = rect.width() / (POPULATION_GRAPH_LENGTH - 1) as f32;
+
+    gizmos.linestrip_2d(
+        population_history
+            .0
+            .iter()
+            .enumerate()
+            .map(|(index, &(_, population))| {
+                to_world(Vec2::new(
+                    rect.min.x + index as f32 * spacing,
+                    rect.max.y - rect.height() * population as f32 / peak as f32,
+                ))
+            }),
+        theme.alive,
+    );
+}
+
+fn minimap_rect(window: &Window) -> Rect {
+    let side = window.width().min(window.height()) * MINIMAP_FRACTION;
+    let max = window.size() - Vec2::new(MINIMAP_MARGIN, MINIMAP_MARGIN + STATUS_BAR_HEIGHT);
+
+    Rect::from_corners(max - Vec2::splat(side), max)
+}
+
+fn update_minimap(
+    q_window: Query<&Window, With<PrimaryWindow>>,
+    mut q_minimap_camera: Query<(&mut Camera, &mut OrthographicProjection), With<MinimapCamera>>,
+    grid_config: Res<GridConfig>,
+    mut minimap_hovered: ResMut<MinimapHovered>,
+    cell_size: Res<CellSize>,
+) {
+    minimap_hovered.0 = false;
+
+    let (Ok(window), Ok((mut minimap_camera, mut minimap_projection))) =
+        (q_window.get_single(), q_minimap_camera.get_single_mut())
+    else {
+        return;
+    };
+
+    if !minimap_camera.is_active {
+        return;
+    }
+
+    let rect = minimap_rect(window);
+    let scale_factor = window.scale_factor();
+
+    minimap_camera.viewport = Some(Viewport {
+        physical_position: (rect.min * scale_factor).as_uvec2(),
+        physical_size: (rect.size() * scale_factor).as_uvec2().max(UVec2::ONE),
+        ..default()
+    });
+    let (grid_width, grid_height) = grid_config.geometry(&cell_size).size();
+    minimap_projection.scale = grid_width.max(grid_height) / rect.width();
+    minimap_hovered.0 = window
+        .cursor_position()
+        .is_some_and(|cursor| rect.contains(cursor));
+}
+
+fn draw_minimap_viewport(
+    mut gizmos: Gizmos<MinimapGizmos>,
+    q_window: Query<&Window, With<PrimaryWindow>>,
+    q_main_camera: Query<(&Transform, &OrthographicProjection), With<MainCamera>>,
+) {
+    let (Ok(window), Ok((main_transform, main_projection))) =
+        (q_window.get_single(), q_main_camera.get_single())
+    else {
+        return;
+    };
+
+    gizmos.rect_2d(
+        Isometry2d::from_translation(main_transform.translation.truncate()),
+        window.size() * main_projection.scale,
+        Color::srgb(1., 1., 0.),
+    );
+}
+
+fn handle_minimap_click(
+    mouse_button: Res<ButtonInput<MouseButton>>,
+    minimap_hovered: Res<MinimapHovered>,
+    pointer_over_ui: Res<PointerOverUi>,
+    q_window: Query<&Window, With<PrimaryWindow>>,
+    q_minimap_camera: Query<(&Camera, &GlobalTransform), With<MinimapCamera>>,
+    mut q_main_camera: Query<&mut Transform, With<MainCamera>>,
+) {
+    if !minimap_hovered.0 || pointer_over_ui.0 || !mouse_button.pressed(MouseButton::Left) {
+        return;
+    }
+
+    let (Ok(window), Ok((minimap_camera, minimap_transform)), Ok(mut main_transform)) = (
+        q_window.get_single(),
+        q_minimap_camera.get_single(),
+        q_main_camera.get_single_mut(),
+    ) else {
+        return;
+    };
+
+    let Some(viewport_origin) = minimap_camera
+        .logical_viewport_rect()
+        .map(|viewport| viewport.min)
+    else {
+        return;
+    };
+
+    if let Some(target) = window.cursor_position().and_then(|cursor| {
+        minimap_camera
+            .viewport_to_world_2d(minimap_transform, cursor - viewport_origin)
+            .ok()
+    }) {
+        main_transform.translation.x = target.x;
+        main_transform.translation.y = target.y;
+    }
+}
+
+fn handle_diagnostics_toggle(
+    keys: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    mut q_diagnostics_text: Query<&mut Visibility, With<DiagnosticsText>>,
+) {
+    if !bindings.just_pressed(&keys, Action::Diagnostics) {
+        return;
+    }
+
+    for mut visibility in q_diagnostics_text.iter_mut() {
+        visibility.toggle_visible_hidden();
+    }
+}
+
+fn update_diagnostics_text(
+    diagnostics: Res<DiagnosticsStore>,
+    tick_rate: Res<TickRate>,
+    mut q_diagnostics_text: Query<(&mut Text, &Visibility), With<DiagnosticsText>>,
+) {
+    let fps = diagnostics
+        .get(&FrameTimeDiagnosticsPlugin::FPS)
+        .and_then(|fps| fps.smoothed())
+        .unwrap_or_default();
+    let overlay = format!(
+        "FPS: {fps:.0}\nGenerations/s: {:.1}",
+        tick_rate.generations_per_second
+    );
+
+    for (mut text, visibility) in q_diagnostics_text.iter_mut() {
+        if *visibility != Visibility::Hidden && text.0 != overlay {
+            text.0 = overlay.clone();
+        }
+    }
+}
+
+fn handle_steps_per_tick_change(
+    keys: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    mut steps_per_tick: ResMut<StepsPerTick>,
+) {
+    if bindings.just_pressed(&keys, Action::MoreSteps) {
+        steps_per_tick.0 = (steps_per_tick.0 * 2).min(MAX_STEPS_PER_TICK);
+    } else if bindings.just_pressed(&keys, Action::FewerSteps) {
+        steps_per_tick.0 = (steps_per_tick.0 / 2).max(1);
+    }
+}
+
+fn handle_speed_change(
+    keys: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    mut fixed_time: ResMut<Time<Fixed>>,
+) {
+    if bindings.just_pressed(&keys, Action::Faster) {
+        scale_tick(&mut fixed_time, 1. / GAME_TICK_SPEED_FACTOR);
+    } else if bindings.just_pressed(&keys, Action::Slower) {
+        scale_tick(&mut fixed_time, GAME_TICK_SPEED_FACTOR);
+    }
+}
+
+fn scale_tick(fixed_time: &mut Time<Fixed>, factor: f32) {
+    let tick_second = fixed_time.timestep().as_secs_f32() * factor;
+
+    fixed_time.set_timestep(Duration::from_secs_f32(
+        tick_second.clamp(MIN_GAME_TICK_SECOND, MAX_GAME_TICK_SECOND),
+    ));
+}
+
+fn handle_grid_lines_toggle(
+    keys: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    mut grid_lines: ResMut<GridLines>,
+) {
+    if bindings.just_pressed(&keys, Action::GridLines) {
+        grid_lines.0 = !grid_lines.0;
+    }
+}
+
+fn draw_grid(
+    mut gizmos: Gizmos,
+    grid_config: Res<GridConfig>,
+    grid_line_color: Res<GridLineColor>,
+    cell_size: Res<CellSize>,
+) {
+    let geometry = grid_config.geometry(&cell_size);
+    let (left, top) = geometry.top_left();
+    let (width, height) = geometry.size();
+    let right = left + width;
+    let bottom = top - height;
+
+    for col in 0..=grid_config.cols {
+        let x = left + col as f32 * cell_size.w;
+        gizmos.line_2d(Vec2::new(x, top), Vec2::new(x, bottom), grid_line_color.0);
+    }
+
+    for row in 0..=grid_config.rows {
+        let y = top - row as f32 * cell_size.h;
+        gizmos.line_2d(Vec2::new(left, y), Vec2::new(right, y), grid_line_color.0);
+    }
+}
+
+fn handle_ruler_toggle(
+    keys: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    mut ruler: ResMut<Ruler>,
+) {
+    if bindings.just_pressed(&keys, Action::Ruler) {
+        ruler.0 = !ruler.0;
+    }
+}
+
+fn update_ruler(
+    ruler: Res<Ruler>,
+    q_window: Query<&Window, With<PrimaryWindow>>,
+    q_camera: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+    grid_config: Res<GridConfig>,
+    cell_size: Res<CellSize>,
+    mut q_labels: Query<(&RulerLabel, &mut Text, &mut Node, &mut Visibility)>,
+    mut commands: Commands,
+) {
+    let (mut columns, mut rows) = (Vec::new(), Vec::new());
+
+    if let (true, Ok(window), Ok((camera, camera_transform))) =
+        (ruler.0, q_window.get_single(), q_camera.get_single())
+    {
+        let geometry = grid_config.geometry(&cell_size);
+        let to_viewport = |(x, y): (f32, f32)| {
+            camera
+                .world_to_viewport(camera_transform, Vec3::new(x, y, 0.))
+                .ok()
+        };
+        let corners = [Vec2::ZERO, window.size()]
+            .map(|corner| camera.viewport_to_world_2d(camera_transform, corner).ok());
+
+        if let (Some(origin), Some(square), [Some(top_left), Some(bottom_right)]) = (
+            to_viewport((0., 0.)),
+            to_viewport((cell_size.w, -cell_size.h)),
+            corners,
+        ) {
+            let (min_x, min_y) = geometry.nearest_cell(top_left.into());
+            let (max_x, max_y) = geometry.nearest_cell(bottom_right.into());
+            let square = square - origin;
+            let step = |pixels: f32| {
+                RULER_STEPS
+                    .into_iter()
+                    .find(|&step| step as f32 * pixels >= RULER_MIN_SPACING)
+                    .unwrap_or(RULER_STEPS[RULER_STEPS.len() - 1])
+            };
+            let (column_step, row_step) = (step(square.x), step(square.y));
+
+            columns.extend(
+                ((min_x + column_step - 1) / column_step * column_step..=max_x)
+                    .step_by(column_step as usize)
+                    .filter_map(|x| Some((x, to_viewport(geometry.cell_to_world(x, 0))?.x))),
+            );
+            rows.extend(
+                ((min_y + row_step - 1) / row_step * row_step..=max_y)
+                    .step_by(row_step as usize)
+                    .filter_map(|y| Some((y, to_viewport(geometry.cell_to_world(0, y))?.y))),
+            );
+        }
+    }
+
+    let (mut columns, mut rows) = (columns.into_iter(), rows.into_iter());
+
+    for (label, mut text, mut node, mut visibility) in q_labels.iter_mut() {
+        let next = match label.0 {
+            RulerAxis::Columns => columns.next(),
+            RulerAxis::Rows => rows.next(),
+        };
+        let Some((index, position)) = next else {
+            visibility.set_if_neq(Visibility::Hidden);
+            continue;
+        };
+        let (left, top) = ruler_label_position(label.0, position);
+
+        if text.0 != index.to_string() {
+            text.0 = index.to_string();
+        }
+        if node.left != left || node.top != top {
+            node.left = left;
+            node.top = top;
+        }
+        visibility.set_if_neq(Visibility::Inherited);
+    }
+
+    let missing = columns
+        .map(|label| (RulerAxis::Columns, label))
+        .chain(rows.map(|label| (RulerAxis::Rows, label)));
+
+    for (axis, (index, position)) in missing {
+        let (left, top) = ruler_label_position(axis, position);
+
+        commands.spawn((
+            RulerLabel(axis),
+            Text::new(index.to_string()),
+            TextFont {
+                font_size: RULER_FONT_SIZE,
+                ..default()
+            },
+            TextColor(Color::WHITE),
+            TextLayout::new_with_justify(match axis {
+                RulerAxis::Columns => JustifyText::Center,
+                RulerAxis::Rows => JustifyText::Left,
+            }),
+            BackgroundColor(Color::BLACK.with_alpha(0.4)),
+            Node {
+                position_type: PositionType::Absolute,
+                left,
+                top,
+                width: Val::Px(RULER_LABEL_WIDTH),
+                ..default()
+            },
+        ));
+    }
+}
+
+/// Where a label goes so it lines up with the centre of its column or row, given that
+/// centre's position in the window.
+fn ruler_label_position(axis: RulerAxis, position: f32) -> (Val, Val) {
+    match axis {
+        RulerAxis::Columns => (Val::Px(position - RULER_LABEL_WIDTH / 2.), Val::Px(0.)),
+        RulerAxis::Rows => (Val::Px(0.), Val::Px(position - RULER_FONT_SIZE / 2.)),
+    }
+}
+
+fn ctrl_pressed(keys: &ButtonInput<KeyCode>) -> bool {
+    keys.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight])
+}
+
+fn shift_pressed(keys: &ButtonInput<KeyCode>) -> bool {
+    keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight])
+}
+
+fn alt_pressed(keys: &ButtonInput<KeyCode>) -> bool {
+    keys.any_pressed([KeyCode::AltLeft, KeyCode::AltRight])
+}
+
+fn unix_timestamp() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default()
+}
+
+fn handle_screenshot(
+    keys: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    generation: Res<Generation>,
+    mut commands: Commands,
+) {
+    if !bindings.just_pressed(&keys, Action::Screenshot) {
+        return;
+    }
+
+    let path = format!("screenshot-gen{}-{}.png", generation.0, unix_timestamp());
+
+    info!("saving screenshot to {path}");
+    commands
+        .spawn(Screenshot::primary_window())
+        .observe(save_to_disk(path));
+}
+
+#[allow(clippy::type_complexity)]
+fn handle_theme_cycle(
+    keys: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    mut theme: ResMut<Theme>,
+    mut clear_color: ResMut<ClearColor>,
+    (ruleset, automaton): (Res<ActiveRuleset>, Res<ActiveAutomaton>),
+    mut q_squares: Query<
+        (
+            &mut Sprite,
+            Option<&Age>,
+            Option<&CellColor>,
+            Option<&Dying>,
+            Has<Immortal>,
+        ),
+        With<Square>,
+    >,
+) {
+    if !bindings.just_pressed(&keys, Action::Theme) {
+        return;
+    }
+
+    let index = THEMES
+        .iter()
+        .position(|preset| preset.name == theme.name)
+        .unwrap_or_default();
+
+    *theme = THEMES[(index + 1) % THEMES.len()];
+    clear_color.0 = theme.background;
+
+    let stages = dying_stages(automaton.0, &ruleset.0);
+
+    for (mut sprite, age, cell_color, dying, is_immortal) in q_squares.iter_mut() {
+        sprite.color = match (age, cell_color) {
+            _ if is_immortal => theme.immortal,
+            (Some(_), Some(cell_color)) => cell_color.0,
+            (Some(age), None) => age_color(&theme, age.0),
+            (None, _) => dying.map_or(theme.dead, |dying| dying_color(&theme, dying.0, stages)),
+        };
+    }
+
+    info!("switched to the {} theme", theme.name);
+}
+
+fn handle_cell_shape_toggle(
+    keys: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    mut cell_shape: ResMut<CellShape>,
+) {
+    if ctrl_pressed(&keys) || !bindings.just_pressed(&keys, Action::CellShape) {
+        return;
+    }
+
+    *cell_shape = match *cell_shape {
+        CellShape::Square => CellShape::Circle,
+        CellShape::Circle => CellShape::Square,
+    };
+}
+
+fn apply_cell_shape(
+    cell_shape: Res<CellShape>,
+    circle_texture: Res<CircleTexture>,
+    q_added_squares: Query<(), Added<Square>>,
+    mut q_square_sprites: Query<(&mut Sprite, Ref<Square>)>,
+) {
+    if !cell_shape.is_changed() && q_added_squares.is_empty() {
+        return;
+    }
+
+    let image = match *cell_shape {
+        CellShape::Square => Handle::default(),
+        CellShape::Circle => circle_texture.0.clone(),
+    };
+
+    for (mut sprite, square) in q_square_sprites.iter_mut() {
+        if cell_shape.is_changed() || square.is_added() {
+            sprite.image = image.clone();
+        }
+    }
+}
+
+fn handle_trails_toggle(
+    keys: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    theme: Res<Theme>,
+    mut trails: ResMut<Trails>,
+    mut q_trail_squares: Query<(Entity, &mut Sprite), With<DeadSince>>,
+    mut commands: Commands,
+) {
+    if !bindings.just_pressed(&keys, Action::Trails) {
+        return;
+    }
+
+    trails.0 = !trails.0;
+
+    for (entity, mut sprite) in q_trail_squares.iter_mut() {
+        sprite.color = theme.dead;
+        commands.entity(entity).remove::<DeadSince>();
+    }
+}
+
+fn handle_save(
+    keys: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    save_path: Res<SavePath>,
+    ruleset: Res<ActiveRuleset>,
+    fixed_time: Res<Time<Fixed>>,
+    credits: Res<PatternCredits>,
+    q_alive_squares: Query<&Square, With<Alive>>,
+) {
+    if !ctrl_pressed(&keys) || shift_pressed(&keys) || !bindings.just_pressed(&keys, Action::Save) {
+        return;
+    }
+
+    let cells = q_alive_squares
+        .iter()
+        .map(|square| (square.x, square.y))
+        .collect();
+    let path = save_path
+        .0
+        .clone()
+        .unwrap_or_else(|| PathBuf::from(format!("pattern-{}.rle", unix_timestamp())));
+
+    // A `.json` path keeps the credits, rule and tick alongside the cells, everything
+    // else is RLE.
+    let contents = if path
+        .extension()
+        .is_some_and(|extension| extension == "json")
+    {
+        let file = PatternFile {
+            name: credits.name(),
+            author: credits.author(),
+            rule: Some(ruleset.0.to_string()),
+            tick_seconds: Some(fixed_time.timestep().as_secs_f32()),
+            ..PatternFile::from_cells(&cells)
+        };
+
+        match pattern_file::encode(&file) {
+            Ok(contents) => contents,
+            Err(err) => {
+                error!("failed to encode pattern: {err}");
+                return;
+            }
+        }
+    } else {
+        rle::encode(&cells, &ruleset.0.to_string())
+    };
+
+    match fs::write(&path, contents) {
+        Ok(()) => info!("saved pattern to {}", path.display()),
+        Err(err) => error!("failed to save pattern to {}: {err}", path.display()),
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn handle_session_save(
+    keys: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    session_path: Res<SessionPath>,
+    grid_config: Res<GridConfig>,
+    generation: Res<Generation>,
+    ruleset: Res<ActiveRuleset>,
+    fixed_time: Res<Time<Fixed>>,
+    q_camera: Query<(&Transform, &OrthographicProjection), With<MainCamera>>,
+    q_alive_squares: Query<&Square, With<Alive>>,
+) {
+    if !ctrl_pressed(&keys) || !shift_pressed(&keys) || !bindings.just_pressed(&keys, Action::Save)
+    {
+        return;
+    }
+
+    let Ok((camera_transform, camera_projection)) = q_camera.get_single() else {
+        warn!("no camera to save the session with");
+        return;
+    };
+
+    let session = session::Session {
+        cols: grid_config.cols,
+        rows: grid_config.rows,
+        cells: q_alive_squares
+            .iter()
+            .map(|square| (square.x, square.y))
+            .collect(),
+        generation: generation.0,
+        rule: ruleset.0.to_string(),
+        tick_seconds: fixed_time.timestep().as_secs_f32(),
+        camera: session::CameraState {
+            translation: camera_transform.translation.to_array(),
+            scale: camera_projection.scale,
+        },
+    };
+
+    match session::encode(&session)
+        .and_then(|contents| fs::write(&session_path.0, contents).map_err(|err| err.to_string()))
+    {
+        Ok(()) => info!("saved session to {}", session_path.0.display()),
+        Err(err) => error!(
+            "failed to save session to {}: {err}",
+            session_path.0.display()
+        ),
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn handle_session_load(
+    keys: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    session_path: Res<SessionPath>,
+    grid_config: Res<GridConfig>,
+    theme: Res<Theme>,
+    mut generation: ResMut<Generation>,
+    mut ruleset: ResMut<ActiveRuleset>,
+    mut fixed_time: ResMut<Time<Fixed>>,
+    mut q_camera: Query<(&mut Transform, &mut OrthographicProjection), With<MainCamera>>,
+    mut q_squares: Query<(Entity, &Square, &mut Sprite, Has<Alive>)>,
+    mut render_input: ResMut<RenderInput>,
+    mut logic_state: ResMut<LogicState>,
+    (mut history, mut stable, mut settle_start, mut placement_history): (
+        ResMut<History>,
+        ResMut<Stable>,
+        ResMut<SettleStart>,
+        ResMut<PlacementHistory>,
+    ),
+    (zoom_limits, two_state_only, neighborhood, automaton): (
+        Res<ZoomLimits>,
+        Res<TwoStateOnly>,
+        Res<ActiveNeighborhood>,
+        Res<ActiveAutomaton>,
+    ),
+    mut commands: Commands,
+) {
+    if !ctrl_pressed(&keys) || !shift_pressed(&keys) || !bindings.just_pressed(&keys, Action::Load)
+    {
+        return;
+    }
+
+    let session = match fs::read_to_string(&session_path.0)
+        .map_err(|err| err.to_string())
+        .and_then(|contents| session::decode(&contents))
+    {
+        Ok(session) => session,
+        Err(err) => {
+            error!(
+                "failed to load session from {}: {err}",
+                session_path.0.display()
+            );
+            return;
+        }
+    };
+
+    let session_ruleset = match life::parse_rule(&session.rule) {
+        Ok(session_ruleset) => session_ruleset,
+        Err(err) => {
+            error!(
+                "session {} has an invalid rule: {err}",
+                session_path.0.display()
+            );
+            return;
+        }
+    };
+
+    // A session saved under Brian's Brain keeps the rule that was set aside for it.
+    if session_ruleset.to_string() != ruleset.0.to_string()
+        && let Err(err) = check_loaded_rule(
+            &session_ruleset,
+            neighborhood.0,
+            automaton.0,
+            two_state_only.0,
+        )
+    {
+        error!(
+            "session {} has the rule {session_ruleset}, which cannot be used here: {err}",
+            session_path.0.display()
+        );
+        return;
+    }
+
+    let tick_seconds = match clamp_tick_seconds(session.tick_seconds) {
+        Ok(tick_seconds) => tick_seconds,
+        Err(err) => {
+            error!(
+                "session {} has an invalid tick: {err}",
+                session_path.0.display()
+            );
+            return;
+        }
+    };
+
+    if !session.camera.scale.is_finite()
+        || !session.camera.translation.iter().all(|v| v.is_finite())
+    {
+        error!(
+            "session {} has a non-finite camera position or zoom",
+            session_path.0.display()
+        );
+        return;
+    }
+
+    if session.cols != grid_config.cols || session.rows != grid_config.rows {
+        warn!(
+            "session grid is {}x{} but the grid is {}x{}, cropping it",
+            session.cols, session.rows, grid_config.cols, grid_config.rows
+        );
+    }
+
+    let cells: HashSet<(i32, i32)> = session.cells.into_iter().collect();
+
+    // Like loading a pattern, the whole load is one undo step.
+    placement_history.finish_stroke();
+
+    for (entity, square, mut sprite, is_alive) in q_squares.iter_mut() {
+        let alive = cells.contains(&(square.x, square.y));
+        set_square_alive(&mut commands, &theme, entity, &mut sprite, alive);
+
+        if alive != is_alive {
+            placement_history.record(entity, alive);
+        }
+    }
+
+    placement_history.finish_stroke();
+
+    if let Ok((mut camera_transform, mut camera_projection)) = q_camera.get_single_mut() {
+        camera_transform.translation = Vec3::from_array(session.camera.translation);
+        camera_projection.scale = session.camera.scale.clamp(zoom_limits.min, zoom_limits.max);
+    }
+
+    ruleset.0 = session_ruleset;
+    fixed_time.set_timestep(Duration::from_secs_f32(tick_seconds));
+    render_input.entities_born.clear();
+    render_input.entities_that_died.clear();
+    render_input.entities_dying.clear();
+    *logic_state = LogicState::CalculationNeeded;
+    generation.0 = session.generation;
+    history.0.clear();
+    stable.0 = None;
+    settle_start.0 = None;
+    placement_history.replaced = true;
+    info!("loaded session from {}", session_path.0.display());
+}
+
+fn handle_load(
+    keys: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    load_source: Res<LoadSource>,
+    one_shot_systems: Res<OneShotSystems>,
+    mut commands: Commands,
+) {
+    if !ctrl_pressed(&keys) || shift_pressed(&keys) || !bindings.just_pressed(&keys, Action::Load) {
+        return;
+    }
+
+    if load_source.0.is_none() {
+        warn!(
+            "no pattern to load, start with `--load <path>`, `--load-stdin` or `--load-url <url>`"
+        );
+        return;
+    }
+
+    commands.run_system(one_shot_systems.load_pattern);
+}
+
+fn load_initial_pattern(
+    load_source: Res<LoadSource>,
+    one_shot_systems: Res<OneShotSystems>,
+    mut commands: Commands,
+) {
+    if load_source.0.is_some() {
+        commands.run_system(one_shot_systems.load_pattern);
+    }
+}
+
+fn stamp_seed_pattern(
+    seed_pattern: Res<SeedPattern>,
+    square_map: Res<SquareMap>,
+    mut q_square_sprite: Query<&mut Sprite, With<Square>>,
+    theme: Res<Theme>,
+    mut commands: Commands,
+) {
+    let Some((index, (x, y))) = seed_pattern.0 else {
+        return;
+    };
+
+    for &(dx, dy) in patterns::PATTERNS[index].cells {
+        let Some(square_id) = square_map.get(x + dx, y + dy) else {
+            continue;
+        };
+
+        if let Ok(mut sprite) = q_square_sprite.get_mut(square_id.entity) {
+            set_square_alive(&mut commands, &theme, square_id.entity, &mut sprite, true);
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn load_pattern(
+    load_source: Res<LoadSource>,
+    grid_config: Res<GridConfig>,
+    mut q_squares: Query<(Entity, &Square, &mut Sprite, Has<Alive>)>,
+    mut placement_history: ResMut<PlacementHistory>,
+    mut render_input: ResMut<RenderInput>,
+    mut logic_state: ResMut<LogicState>,
+    mut generation: ResMut<Generation>,
+    (mut history, mut stable, mut settle_start): (
+        ResMut<History>,
+        ResMut<Stable>,
+        ResMut<SettleStart>,
+    ),
+    (mut ruleset, mut fixed_time, neighborhood, automaton): (
+        ResMut<ActiveRuleset>,
+        ResMut<Time<Fixed>>,
+        Res<ActiveNeighborhood>,
+        Res<ActiveAutomaton>,
+    ),
+    (two_state_only, mut credits): (Res<TwoStateOnly>, ResMut<PatternCredits>),
+    theme: Res<Theme>,
+    mut commands: Commands,
+) {
+    let Some(source) = &load_source.0 else {
+        return;
+    };
+
+    let pattern = match read_centered_pattern(source, &grid_config) {
+        Ok(pattern) => pattern,
+        Err(err) => {
+            error!("failed to load pattern from {source}: {err}");
+            return;
+        }
+    };
+
+    placement_history.finish_stroke();
+
+    for (entity, square, mut sprite, is_alive) in q_squares.iter_mut() {
+        let alive = pattern.cells.contains(&(square.x, square.y));
+        set_square_alive(&mut commands, &theme, entity, &mut sprite, alive);
+
+        if alive != is_alive {
+            placement_history.record(entity, alive);
+        }
+    }
+
+    placement_history.finish_stroke();
+    placement_history.replaced = true;
+    credits.loaded_name = pattern.name;
+    credits.loaded_author = pattern.author;
+
+    apply_pattern_rule(
+        pattern.rule,
+        neighborhood.0,
+        automaton.0,
+        two_state_only.0,
+        &mut ruleset.0,
+    );
+    if let Some(tick_seconds) = pattern.tick_seconds {
+        fixed_time.set_timestep(Duration::from_secs_f32(tick_seconds));
+    }
+
+    render_input.entities_born.clear();
+    render_input.entities_that_died.clear();
+    render_input.entities_dying.clear();
+    *logic_state = LogicState::CalculationNeeded;
+    generation.0 = 0;
+    history.0.clear();
+    stable.0 = None;
+    settle_start.0 = None;
+    info!("loaded pattern from {source}");
+}
+
+/// A pattern centred on the grid, with the rule and tick a JSON pattern file was saved
+/// with.
+struct LoadedPattern {
+    cells: HashSet<(i32, i32)>,
+    name: Option<String>,
+    author: Option<String>,
+    rule: Option<Ruleset>,
+    tick_seconds: Option<f32>,
+}
+
+/// Reads an RLE, plaintext or JSON pattern and centres it on the grid. Files are
+/// plaintext when they end in `.cells` and JSON when they end in `.json`, while standard
+/// input is told apart by its content. Cells that fall outside the grid are kept, so
+/// callers crop them when placing.
+fn read_centered_pattern(
+    source: &PatternSource,
+    grid_config: &GridConfig,
+) -> Result<LoadedPattern, String> {
+    let file = match source {
+        PatternSource::File(path) => {
+            let contents = fs::read_to_string(path).map_err(|err| err.to_string())?;
+
+            match path.extension().and_then(|extension| extension.to_str()) {
+                Some("cells") => PatternFile::from(cells::decode(&contents)?),
+                Some("json") => pattern_file::decode(&contents)?,
+                _ => PatternFile::from(rle::decode(&contents)?),
+            }
+        }
+        PatternSource::Stdin(contents) | PatternSource::Url { contents, .. }
+            if contents.trim_start().starts_with('{') =>
+        {
+            pattern_file::decode(contents)?
+        }
+        PatternSource::Stdin(contents) | PatternSource::Url { contents, .. } => {
+            PatternFile::from(rle::decode_any(contents)?)
+        }
+    };
+
+    match (&file.name, &file.author) {
+        (Some(name), Some(author)) => info!("pattern is {name} by {author}"),
+        (Some(name), None) => info!("pattern is {name}"),
+        (None, Some(author)) => info!("pattern is by {author}"),
+        (None, None) => {}
+    }
+
+    let rule = file
+        .rule
+        .as_deref()
+        .map(life::parse_rule)
+        .transpose()
+        .map_err(|err| format!("invalid rule: {err}"))?;
+    let tick_seconds = file
+        .tick_seconds
+        .map(|seconds| seconds.clamp(MIN_GAME_TICK_SECOND, MAX_GAME_TICK_SECOND));
+    let pattern = file.pattern();
+
+    if pattern.width > grid_config.cols || pattern.height > grid_config.rows {
+        warn!(
+            "pattern is {}x{} but the grid is {}x{}, cropping it",
+            pattern.width, pattern.height, grid_config.cols, grid_config.rows
+        );
+    }
+
+    let offset_x = (grid_config.cols - pattern.width) / 2;
+    let offset_y = (grid_config.rows - pattern.height) / 2;
+
+    Ok(LoadedPattern {
+        cells: pattern
+            .cells
+            .into_iter()
+            .map(|(x, y)| (x + offset_x, y + offset_y))
+            .collect(),
+        name: file.name,
+        author: file.author,
+        rule,
+        tick_seconds,
+    })
+}
+
+/// Why a rule read from a pattern or session cannot replace the current one, if it cannot.
+fn check_loaded_rule(
+    rule: &Ruleset,
+    neighborhood: Neighborhood,
+    automaton: Automaton,
+    two_state_only: Option<&str>,
+) -> Result<(), String> {
+    if automaton != Automaton::Life {
+        return Err("Brian's Brain has fixed rules".to_string());
+    }
+
+    life::check_rule(rule, neighborhood)?;
+
+    match two_state_only.filter(|_| rule.states > 2) {
+        Some(flag) => Err(format!("`{flag}` does not support Generations rules")),
+        None => Ok(()),
+    }
+}
+
+/// Switches to the rule a pattern was saved with, keeping the current one when the
+/// pattern has none, the automaton has fixed rules or the rule needs another
+/// neighborhood.
+fn apply_pattern_rule(
+    rule: Option<Ruleset>,
+    neighborhood: Neighborhood,
+    automaton: Automaton,
+    two_state_only: Option<&str>,
+    ruleset: &mut Ruleset,
+) {
+    let Some(rule) = rule else {
+        return;
+    };
+
+    if let Err(err) = check_loaded_rule(&rule, neighborhood, automaton, two_state_only) {
+        warn!("ignoring the pattern's rule {rule}: {err}");
+    } else {
+        info!("switched rule to {rule}");
+        *ruleset = rule;
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn handle_camera_reset(
+    keys: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    q_window: Query<&Window, With<PrimaryWindow>>,
+    mut q_camera: Query<(&mut Transform, &mut OrthographicProjection), With<MainCamera>>,
+    grid_config: Res<GridConfig>,
+    zoom_limits: Res<ZoomLimits>,
+    cell_size: Res<CellSize>,
+    step_count: Res<StepCount>,
+) {
+    // Once a step count is being typed, 0 is one of its digits.
+    let zero_pressed = keys.just_pressed(KeyCode::Digit0) && step_count.0.is_none();
+
+    if !bindings.just_pressed(&keys, Action::ResetCamera) && !zero_pressed {
+        return;
+    }
+
+    let Ok((mut camera_transform, mut camera_projection)) = q_camera.get_single_mut() else {
+        warn!("no camera to reset");
+        return;
+    };
+    let geometry = grid_config.geometry(&cell_size);
+    let (center_x, center_y) = geometry.center();
+
+    camera_transform.translation.x = center_x;
+    camera_transform.translation.y = center_y;
+    camera_projection.scale = q_window
+        .get_single()
+        .map(|window| board_scale(&geometry, window))
+        .unwrap_or(1.)
+        .clamp(zoom_limits.min, zoom_limits.max);
+}
+
+/// The smallest rectangle holding all of `cells`, as its top-left and bottom-right squares.
+fn cells_bounds(cells: impl IntoIterator<Item = (i32, i32)>) -> Option<((i32, i32), (i32, i32))> {
+    cells.into_iter().fold(None, |bounds, (x, y)| {
+        let ((min_x, min_y), (max_x, max_y)) = bounds.unwrap_or(((x, y), (x, y)));
+
+        Some(((min_x.min(x), min_y.min(y)), (max_x.max(x), max_y.max(y))))
+    })
+}
+
+fn handle_follow_toggle(
+    keys: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    mut follow: ResMut<FollowCamera>,
+) {
+    if !bindings.just_pressed(&keys, Action::Follow) {
+        return;
+    }
+
+    follow.0 = !follow.0;
+    info!(
+        "{} the live cells",
+        if follow.0 {
+            "following"
+        } else {
+            "no longer following"
+        }
+    );
+}
+
+fn live_square_bounds(
+    q_alive_squares: Query<&Square, With<Alive>>,
+) -> Option<((i32, i32), (i32, i32))> {
+    cells_bounds(q_alive_squares.iter().map(|square| (square.x, square.y)))
+}
+
+/// Keeps the camera framing the live cells, whose bounds are piped in from whichever
+/// renderer holds them.
+#[allow(clippy::type_complexity)]
+fn follow_live_cells(
+    In(bounds): In<Option<((i32, i32), (i32, i32))>>,
+    q_window: Query<&Window, With<PrimaryWindow>>,
+    mut q_camera: Query<(&mut Transform, &mut OrthographicProjection), With<MainCamera>>,
+    grid_config: Res<GridConfig>,
+    cell_size: Res<CellSize>,
+    zoom_limits: Res<ZoomLimits>,
+    time: Res<Time>,
+) {
+    let (Some(bounds), Ok((mut camera_transform, mut camera_projection)), Ok(window)) =
+        (bounds, q_camera.get_single_mut(), q_window.get_single())
+    else {
+        return;
+    };
+
+    ease_camera_to_cells(
+        bounds,
+        &grid_config.geometry(&cell_size),
+        window,
+        &mut camera_transform,
+        &mut camera_projection,
+        &zoom_limits,
+        time.delta_secs(),
+    );
+}
+
+/// Moves the camera part of the way towards framing the squares within `bounds`. The
+/// scale eases by ratio rather than difference, so zooming in feels as fast as zooming
+/// out.
+fn ease_camera_to_cells(
+    ((min_x, min_y), (max_x, max_y)): ((i32, i32), (i32, i32)),
+    geometry: &GridGeometry,
+    window: &Window,
+    camera_transform: &mut Transform,
+    camera_projection: &mut OrthographicProjection,
+    zoom_limits: &ZoomLimits,
+    delta_secs: f32,
+) {
+    let ((center_x, center_y), (width, height)) =
+        geometry.cells_rect((min_x, min_y), (max_x, max_y));
+    let target_scale = ((width / window.width()).max(height / window.height()) * FIT_MARGIN)
+        .clamp(zoom_limits.min, zoom_limits.max);
+    let blend = 1. - (-FOLLOW_SMOOTHING * delta_secs).exp();
+
+    let position = camera_transform
+        .translation
+        .truncate()
+        .lerp(Vec2::new(center_x, center_y), blend);
+    camera_transform.translation.x = position.x;
+    camera_transform.translation.y = position.y;
+    camera_projection.scale *= (target_scale / camera_projection.scale).powf(blend);
+}
+
+/// The camera scale that just fits the whole board in `window`.
+fn board_scale(geometry: &GridGeometry, window: &Window) -> f32 {
+    let (grid_width, grid_height) = geometry.size();
+
+    (grid_width / window.width()).max(grid_height / window.height())
+}
+
+#[allow(clippy::too_many_arguments)]
+fn handle_zoom_presets(
+    keys: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    presets: Res<ZoomPresets>,
+    q_window: Query<&Window, With<PrimaryWindow>>,
+    mut q_camera: Query<(&mut Transform, &mut OrthographicProjection), With<MainCamera>>,
+    grid_config: Res<GridConfig>,
+    zoom_limits: Res<ZoomLimits>,
+    cell_size: Res<CellSize>,
+) {
+    let Some(&(_, preset)) = presets
+        .0
+        .iter()
+        .find(|&&(action, _)| bindings.just_pressed(&keys, action))
+    else {
+        return;
+    };
+
+    let (Ok((mut camera_transform, mut camera_projection)), Ok(window)) =
+        (q_camera.get_single_mut(), q_window.get_single())
+    else {
+        warn!("no camera or primary window to zoom with");
+        return;
+    };
+
+    let scale = preset.scale(window.scale_factor()).unwrap_or_else(|| {
+        let geometry = grid_config.geometry(&cell_size);
+        let (center_x, center_y) = geometry.center();
+
+        camera_transform.translation.x = center_x;
+        camera_transform.translation.y = center_y;
+        board_scale(&geometry, window)
+    });
+
+    camera_projection.scale = scale.clamp(zoom_limits.min, zoom_limits.max);
+
+    if camera_projection.scale != scale {
+        info!("that zoom is past the zoom limits, zooming as far as they allow");
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn handle_fit_to_screen(
+    keys: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    q_window: Query<&Window, With<PrimaryWindow>>,
+    mut q_camera: Query<(&mut Transform, &mut OrthographicProjection), With<MainCamera>>,
+    q_alive_squares: Query<&Square, With<Alive>>,
+    selection: Res<Selection>,
+    grid_config: Res<GridConfig>,
+    zoom_limits: Res<ZoomLimits>,
+    cell_size: Res<CellSize>,
+) {
+    if !bindings.just_pressed(&keys, Action::Fit) {
+        return;
+    }
+
+    let bounds = selection
+        .bounds()
+        .or_else(|| cells_bounds(q_alive_squares.iter().map(|square| (square.x, square.y))));
+    let Some(((min_x, min_y), (max_x, max_y))) = bounds else {
+        info!("nothing to fit, the board is empty");
+        return;
+    };
+
+    let (Ok((mut camera_transform, mut camera_projection)), Ok(window)) =
+        (q_camera.get_single_mut(), q_window.get_single())
+    else {
+        warn!("no camera or primary window to fit the view with");
+        return;
+    };
+
+    let ((center_x, center_y), (width, height)) = grid_config
+        .geometry(&cell_size)
+        .cells_rect((min_x, min_y), (max_x, max_y));
+
+    camera_transform.translation.x = center_x;
+    camera_transform.translation.y = center_y;
+    camera_projection.scale = ((width / window.width()).max(height / window.height()) * FIT_MARGIN)
+        .clamp(zoom_limits.min, zoom_limits.max);
+}
+
+fn handle_drag_pan(
+    mouse_button: Res<ButtonInput<MouseButton>>,
+    mut evr_motion: EventReader<MouseMotion>,
+    mut q_camera: Query<(&mut Transform, &OrthographicProjection), With<MainCamera>>,
+) {
+    let delta: Vec2 = evr_motion.read().map(|ev| ev.delta).sum();
+
+    if !mouse_button.pressed(MouseButton::Middle) {
+        return;
+    }
+
+    let Ok((mut camera_transform, camera_projection)) = q_camera.get_single_mut() else {
+        warn!("no camera to pan");
+        return;
+    };
+    camera_transform.translation.x -= delta.x * camera_projection.scale;
+    camera_transform.translation.y += delta.y * camera_projection.scale;
+}
+
+fn handle_move(
+    keys: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    mut query_camera: Query<&mut Transform, With<MainCamera>>,
+    cell_size: Res<CellSize>,
+    time: Res<Time>,
+    pan: Res<PanSettings>,
+    mut pan_target: ResMut<PanTarget>,
+) {
+    if ctrl_pressed(&keys) {
+        return;
+    }
+
+    let mut direction = Vec2::ZERO;
+
+    if bindings.pressed(&keys, Action::PanRight) {
+        direction.x += 1.;
+    }
+
+    if bindings.pressed(&keys, Action::PanLeft) {
+        direction.x -= 1.;
+    }
+
+    if bindings.pressed(&keys, Action::PanUp) {
+        direction.y += 1.;
+    }
+
+    if bindings.pressed(&keys, Action::PanDown) {
+        direction.y -= 1.;
+    }
+
+    if direction == Vec2::ZERO {
+        return;
+    }
+
+    let Ok(mut camera_transform) = query_camera.get_single_mut() else {
+        warn!("no camera to move");
+        return;
+    };
+    let delta = direction * Vec2::new(cell_size.w, cell_size.h) * pan.speed * time.delta_secs();
+
+    if pan.smoothing > 0. {
+        let current = camera_transform.translation.truncate();
+
+        if pan_target.target.is_none() {
+            pan_target.last = current;
+        }
+
+        pan_target.target = Some(pan_target.target.unwrap_or(current) + delta);
+    } else {
+        camera_transform.translation += delta.extend(0.);
+    }
+}
+
+/// Tab and Shift+Tab glide the camera to the next or previous cluster of live cells.
+#[allow(clippy::too_many_arguments)]
+fn handle_cluster_tour(
+    keys: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    q_camera: Query<&Transform, With<MainCamera>>,
+    q_alive_squares: Query<&Square, With<Alive>>,
+    grid_config: Res<GridConfig>,
+    cell_size: Res<CellSize>,
+    mut cluster_tour: ResMut<ClusterTour>,
+    mut pan_target: ResMut<PanTarget>,
+) {
+    if !bindings.just_pressed(&keys, Action::ClusterTour) {
+        return;
+    }
+
+    let clusters = life::clusters(q_alive_squares.iter().map(|square| (square.x, square.y)));
+
+    if clusters.is_empty() {
+        info!("no clusters to visit, the board is empty");
+        return;
+    }
+
+    // Clusters move and merge between presses, so the tour only remembers a position in
+    // the current ordering.
+    let index = match (cluster_tour.0, shift_pressed(&keys)) {
+        (None, false) => 0,
+        (None, true) => clusters.len() - 1,
+        (Some(index), false) => (index + 1) % clusters.len(),
+        (Some(index), true) => (index + clusters.len() - 1) % clusters.len(),
+    };
+    cluster_tour.0 = Some(index);
+
+    let Ok(camera_transform) = q_camera.get_single() else {
+        return;
+    };
+
+    let cluster = &clusters[index];
+    let (min, max) = cluster
+        .iter()
+        .fold((cluster[0], cluster[0]), |(min, max), &(x, y)| {
+            ((min.0.min(x), min.1.min(y)), (max.0.max(x), max.1.max(y)))
+        });
+    let (center, _) = grid_config.geometry(&cell_size).cells_rect(min, max);
+
+    pan_target.target = Some(Vec2::from(center));
+    pan_target.last = camera_transform.translation.truncate();
+    info!(
+        "visiting cluster {} of {} ({} cells)",
+        index + 1,
+        clusters.len(),
+        cluster.len()
+    );
+}
+
+fn smooth_pan(
+    mut query_camera: Query<&mut Transform, With<MainCamera>>,
+    time: Res<Time>,
+    pan: Res<PanSettings>,
+    mut pan_target: ResMut<PanTarget>,
+) {
+    let Some(target) = pan_target.target else {
+        return;
+    };
+
+    let Ok(mut camera_transform) = query_camera.get_single_mut() else {
+        pan_target.target = None;
+        return;
+    };
+
+    if camera_transform.translation.truncate() != pan_target.last {
+        pan_target.target = None;
+        return;
+    }
+
+    // Exponential decay closes the same fraction of the remaining distance per second
+    // whatever the frame rate is.
+    let blend = 1. - (-pan.smoothing * time.delta_secs()).exp();
+    let mut position = camera_transform.translation.truncate().lerp(target, blend);
+
+    if position.distance(target) < PAN_SETTLE_DISTANCE {
+        position = target;
+        pan_target.target = None;
+    }
+
+    camera_transform.translation.x = position.x;
+    camera_transform.translation.y = position.y;
+    pan_target.last = position;
+}
+
+fn cursor_to_world(
+    window: &Window,
+    camera: &Camera,
+    camera_transform: &GlobalTransform,
+) -> Option<Vec2> {
+    window
+        .cursor_position()
+        .and_then(|cursor| camera.viewport_to_world(camera_transform, cursor).ok())
+        .map(|ray| ray.origin.truncate())
+}
+
+fn cursor_to_square(
+    window: &Window,
+    camera: &Camera,
+    camera_transform: &GlobalTransform,
+    grid_config: &GridConfig,
+    cell_size: &CellSize,
+) -> Option<(i32, i32)> {
+    let position = cursor_to_world(window, camera, camera_transform)?;
+
+    grid_config
+        .geometry(cell_size)
+        .world_to_cell(position.into())
+}
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn handle_click(
+    mouse_button: Res<ButtonInput<MouseButton>>,
+    keys: Res<ButtonInput<KeyCode>>,
+    q_window: Query<&Window, With<PrimaryWindow>>,
+    q_camera: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+    square_map: Res<SquareMap>,
+    grid_config: Res<GridConfig>,
+    mut last_painted_square: ResMut<LastPaintedSquare>,
+    mut q_square_sprite: Query<(&mut Sprite, Has<Alive>, Has<Immortal>), With<Square>>,
+    mut placement_history: ResMut<PlacementHistory>,
+    theme: Res<Theme>,
+    mut commands: Commands,
+    minimap_hovered: Res<MinimapHovered>,
+    pointer_over_ui: Res<PointerOverUi>,
+    cell_size: Res<CellSize>,
+    (paint_color, immortal_brush, brush_size): (
+        Res<PaintColor>,
+        Res<ImmortalBrush>,
+        Res<BrushSize>,
+    ),
+) {
+    if minimap_hovered.0 || pointer_over_ui.0 {
+        return;
+    }
+
+    if alt_pressed(&keys) {
+        return;
+    }
+
+    let drag_paint = shift_pressed(&keys);
+
+    let dragging = drag_paint && mouse_button.pressed(MouseButton::Left);
+
+    if !mouse_button.just_pressed(MouseButton::Left) && !dragging {
+        return;
+    }
+
+    // A new press starts a new stroke, whichever button the last one was made with.
+    if mouse_button.just_pressed(MouseButton::Left) {
+        last_painted_square.0 = None;
+    }
+
+    let (Ok((camera, camera_transform)), Ok(window)) =
+        (q_camera.get_single(), q_window.get_single())
+    else {
+        warn!("no camera or primary window to place squares with");
+        return;
+    };
+
+    let Some((square_x, square_y)) =
+        cursor_to_square(window, camera, camera_transform, &grid_config, &cell_size)
+    else {
+        last_painted_square.0 = None;
+        return;
+    };
+
+    let current = (square_x, square_y);
+
+    if !drag_paint {
+        // The clicked square decides whether the whole brush places or clears.
+        let Some(place) = square_map
+            .get(current.0, current.1)
+            .and_then(|square_id| q_square_sprite.get(square_id.entity).ok())
+            .map(|(_, is_alive, is_immortal)| !is_alive || (immortal_brush.0 && !is_immortal))
+        else {
+            return;
+        };
+
+        for (x, y) in brush_squares(current, brush_size.side(), &grid_config) {
+            let Some(square_id) = square_map.get(x, y) else {
+                continue;
+            };
+            let Ok((mut square_sprite, is_alive, is_immortal)) =
+                q_square_sprite.get_mut(square_id.entity)
+            else {
+                warn!("square entity {} has no sprite", square_id.entity);
+                continue;
+            };
+
+            if place && (!is_alive || (immortal_brush.0 && !is_immortal)) {
+                place_square(
+                    &mut commands,
+                    &theme,
+                    &paint_color,
+                    &immortal_brush,
+                    square_id.entity,
+                    &mut square_sprite,
+                );
+                placement_history.record(square_id.entity, true);
+            } else if !place && is_alive {
+                set_square_alive(
+                    &mut commands,
+                    &theme,
+                    square_id.entity,
+                    &mut square_sprite,
+                    false,
+                );
+                placement_history.record(square_id.entity, false);
+            }
+        }
+
+        return;
+    }
+
+    for (x, y) in brush_stroke(
+        last_painted_square.0.unwrap_or(current),
+        current,
+        brush_size.side(),
+        &grid_config,
+    ) {
+        if let Some(square_id) = square_map.get(x, y) {
+            let Ok((mut square_sprite, is_alive, is_immortal)) =
+                q_square_sprite.get_mut(square_id.entity)
+            else {
+                warn!("square entity {} has no sprite", square_id.entity);
+                continue;
+            };
+
+            if !is_alive || (immortal_brush.0 && !is_immortal) {
+                place_square(
+                    &mut commands,
+                    &theme,
+                    &paint_color,
+                    &immortal_brush,
+                    square_id.entity,
+                    &mut square_sprite,
+                );
+                placement_history.record(square_id.entity, true);
+            }
+        }
+    }
+
+    last_painted_square.0 = Some(current);
+}
+
+/// Brings a square to life by hand, as an immortal square with [`ImmortalBrush`] on and
+/// otherwise in the color picked with [`PaintColor`], if any.
+fn place_square(
+    commands: &mut Commands,
+    theme: &Theme,
+    paint_color: &PaintColor,
+    immortal_brush: &ImmortalBrush,
+    entity: Entity,
+    sprite: &mut Sprite,
+) {
+    set_square_alive(commands, theme, entity, sprite, true);
+
+    if immortal_brush.0 {
+        sprite.color = theme.immortal;
+        commands.entity(entity).insert(Immortal).remove::<Age>();
+    } else if let Some(color) = paint_color.color() {
+        sprite.color = color;
+        commands.entity(entity).insert(CellColor(color));
+    }
+}
+
+fn handle_immortal_brush_toggle(
+    keys: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    mut immortal_brush: ResMut<ImmortalBrush>,
+) {
+    if !bindings.just_pressed(&keys, Action::ImmortalBrush) {
+        return;
+    }
+
+    immortal_brush.0 = !immortal_brush.0;
+    info!(
+        "{} immortal squares",
+        if immortal_brush.0 {
+            "placing"
+        } else {
+            "no longer placing"
+        }
+    );
+}
+
+/// Shift+] and Shift+[ grow and shrink the brush, since [ and ] alone turn the stamp.
+fn handle_brush_size(
+    keys: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    mut brush_size: ResMut<BrushSize>,
+) {
+    if ctrl_pressed(&keys) || !shift_pressed(&keys) {
+        return;
+    }
+
+    let index = if bindings.just_pressed(&keys, Action::BrushLarger) {
+        (brush_size.0 + 1).min(BRUSH_SIZES.len() - 1)
+    } else if bindings.just_pressed(&keys, Action::BrushSmaller) {
+        brush_size.0.saturating_sub(1)
+    } else {
+        return;
+    };
+
+    if index != brush_size.0 {
+        brush_size.0 = index;
+        info!("brush size {0}x{0}", brush_size.side());
+    }
+}
+
+fn handle_paint_color_cycle(
+    keys: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    mut paint_color: ResMut<PaintColor>,
+) {
+    if !bindings.just_pressed(&keys, Action::PaintColor) {
+        return;
+    }
+
+    paint_color.0 = match paint_color.0 {
+        None => Some(0),
+        Some(index) if index + 1 < CELL_PALETTE.len() => Some(index + 1),
+        Some(_) => None,
+    };
+
+    match paint_color.0 {
+        Some(index) => info!("painting squares with palette color {}", index + 1),
+        None => info!("painting squares with the theme color"),
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn handle_erase(
+    mouse_button: Res<ButtonInput<MouseButton>>,
+    q_window: Query<&Window, With<PrimaryWindow>>,
+    q_camera: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+    square_map: Res<SquareMap>,
+    grid_config: Res<GridConfig>,
+    mut last_painted_square: ResMut<LastPaintedSquare>,
+    mut q_alive_sprite: Query<&mut Sprite, (With<Square>, With<Alive>)>,
+    mut placement_history: ResMut<PlacementHistory>,
+    theme: Res<Theme>,
+    mut commands: Commands,
+    minimap_hovered: Res<MinimapHovered>,
+    pointer_over_ui: Res<PointerOverUi>,
+    cell_size: Res<CellSize>,
+    brush_size: Res<BrushSize>,
+) {
+    if minimap_hovered.0 || pointer_over_ui.0 {
+        return;
+    }
+
+    if !mouse_button.pressed(MouseButton::Right) {
+        return;
+    }
+
+    if mouse_button.just_pressed(MouseButton::Right) {
+        last_painted_square.0 = None;
+    }
+
+    let (Ok((camera, camera_transform)), Ok(window)) =
+        (q_camera.get_single(), q_window.get_single())
+    else {
+        warn!("no camera or primary window to place squares with");
+        return;
+    };
+
+    let Some((square_x, square_y)) =
+        cursor_to_square(window, camera, camera_transform, &grid_config, &cell_size)
+    else {
+        last_painted_square.0 = None;
+        return;
+    };
+
+    let current = (square_x, square_y);
+
+    for (x, y) in brush_stroke(
+        last_painted_square.0.unwrap_or(current),
+        current,
+        brush_size.side(),
+        &grid_config,
+    ) {
+        let Some(square_id) = square_map.get(x, y) else {
+            continue;
+        };
+
+        if let Ok(mut square_sprite) = q_alive_sprite.get_mut(square_id.entity) {
+            set_square_alive(
+                &mut commands,
+                &theme,
+                square_id.entity,
+                &mut square_sprite,
+                false,
+            );
+            placement_history.record(square_id.entity, false);
+        }
+    }
+
+    last_painted_square.0 = Some(current);
+}
+
+/// The squares of a `side` by `side` block centered on `center`, cut off at the edges of
+/// the board.
+fn brush_squares(
+    center: (i32, i32),
+    side: i32,
+    grid_config: &GridConfig,
+) -> impl Iterator<Item = (i32, i32)> {
+    let radius = side / 2;
+    let xs = (center.0 - radius).max(0)..=(center.0 + radius).min(grid_config.cols - 1);
+    let ys = (center.1 - radius).max(0)..=(center.1 + radius).min(grid_config.rows - 1);
+
+    ys.flat_map(move |y| xs.clone().map(move |x| (x, y)))
+}
+
+/// Every square the brush covers dragging from `from` to `to`, each listed once even where
+/// neighboring blocks overlap.
+fn brush_stroke(
+    from: (i32, i32),
+    to: (i32, i32),
+    side: i32,
+    grid_config: &GridConfig,
+) -> HashSet<(i32, i32)> {
+    line_squares(from, to)
+        .into_iter()
+        .flat_map(|square| brush_squares(square, side, grid_config))
+        .collect()
+}
+
+fn line_squares(from: (i32, i32), to: (i32, i32)) -> Vec<(i32, i32)> {
+    let (mut x, mut y) = from;
+    let dx = (to.0 - x).abs();
+    let dy = -(to.1 - y).abs();
+    let step_x = if x < to.0 { 1 } else { -1 };
+    let step_y = if y < to.1 { 1 } else { -1 };
+    let mut error = dx + dy;
+    let mut squares = vec![(x, y)];
+
+    while (x, y) != to {
+        let double_error = 2 * error;
+
+        if double_error >= dy {
+            error += dy;
+            x += step_x;
+        }
+
+        if double_error <= dx {
+            error += dx;
+            y += step_y;
+        }
+
+        squares.push((x, y));
+    }
+
+    squares
+}
+
+fn handle_cursor_move(
+    keys: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    grid_config: Res<GridConfig>,
+    mut cursor_cell: ResMut<CursorCell>,
+) {
+    // Ctrl+arrows nudge the live squares instead.
+    if ctrl_pressed(&keys) {
+        return;
+    }
+
+    let Some((dx, dy)) = arrow_direction(&keys, &bindings) else {
+        return;
+    };
+
+    cursor_cell.x = (cursor_cell.x + dx).clamp(0, grid_config.cols - 1);
+    cursor_cell.y = (cursor_cell.y + dy).clamp(0, grid_config.rows - 1);
+}
+
+fn arrow_direction(keys: &ButtonInput<KeyCode>, bindings: &KeyBindings) -> Option<(i32, i32)> {
+    if bindings.just_pressed(keys, Action::CursorLeft) {
+        Some((-1, 0))
+    } else if bindings.just_pressed(keys, Action::CursorRight) {
+        Some((1, 0))
+    } else if bindings.just_pressed(keys, Action::CursorUp) {
+        Some((0, -1))
+    } else if bindings.just_pressed(keys, Action::CursorDown) {
+        Some((0, 1))
+    } else {
+        None
+    }
+}
+
+/// Ctrl+arrows move every live square one square over, keeping its color and whether it
+/// is immortal. Squares wrap around the edges of a wrapping board; on any other board a
+/// pattern already touching the edge it would move past stays put.
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn handle_nudge(
+    keys: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    boundary: Res<ActiveBoundary>,
+    grid_config: Res<GridConfig>,
+    square_map: Res<SquareMap>,
+    q_live_squares: Query<(&Square, Option<&CellColor>, Has<Immortal>), With<Alive>>,
+    mut q_square_sprite: Query<(&mut Sprite, Has<Alive>), With<Square>>,
+    mut placement_history: ResMut<PlacementHistory>,
+    theme: Res<Theme>,
+    mut commands: Commands,
+) {
+    if !ctrl_pressed(&keys) {
+        return;
+    }
+
+    let Some((dx, dy)) = arrow_direction(&keys, &bindings) else {
+        return;
+    };
+
+    let live: Vec<((i32, i32), Option<Color>, bool)> = q_live_squares
+        .iter()
+        .map(|(square, cell_color, is_immortal)| {
+            (
+                (square.x, square.y),
+                cell_color.map(|cell_color| cell_color.0),
+                is_immortal,
+            )
+        })
+        .collect();
+    let on_board = |(x, y): (i32, i32)| {
+        (0..grid_config.cols).contains(&x) && (0..grid_config.rows).contains(&y)
+    };
+
+    if boundary.0 != BoundaryCondition::Wrap
+        && live.iter().any(|&((x, y), ..)| !on_board((x + dx, y + dy)))
+    {
+        info!("the live squares already touch that edge");
+        return;
+    }
+
+    let moved: HashMap<(i32, i32), (Option<Color>, bool)> = live
+        .iter()
+        .map(|&((x, y), cell_color, is_immortal)| {
+            (
+                (
+                    (x + dx).rem_euclid(grid_config.cols),
+                    (y + dy).rem_euclid(grid_config.rows),
+                ),
+                (cell_color, is_immortal),
+            )
+        })
+        .collect();
+
+    for &(cell, ..) in live.iter().filter(|(cell, ..)| !moved.contains_key(cell)) {
+        let Some(square_id) = square_map.get(cell.0, cell.1) else {
+            continue;
+        };
+        let Ok((mut sprite, _)) = q_square_sprite.get_mut(square_id.entity) else {
+            continue;
+        };
+
+        set_square_alive(&mut commands, &theme, square_id.entity, &mut sprite, false);
+        placement_history.record(square_id.entity, false);
+    }
+
+    for (&(x, y), &(cell_color, is_immortal)) in moved.iter() {
+        let Some(square_id) = square_map.get(x, y) else {
+            continue;
+        };
+        let Ok((mut sprite, was_alive)) = q_square_sprite.get_mut(square_id.entity) else {
+            continue;
+        };
+
+        // Squares that stay alive still take on whatever the square before them had.
+        set_square_alive(&mut commands, &theme, square_id.entity, &mut sprite, true);
+
+        if is_immortal {
+            sprite.color = theme.immortal;
+            commands
+                .entity(square_id.entity)
+                .insert(Immortal)
+                .remove::<Age>();
+        } else if let Some(color) = cell_color {
+            sprite.color = color;
+            commands.entity(square_id.entity).insert(CellColor(color));
+        }
+
+        if !was_alive {
+            placement_history.record(square_id.entity, true);
+        }
+    }
+
+    placement_history.finish_stroke();
+}
+
+#[allow(clippy::too_many_arguments)]
+fn handle_cursor_toggle(
+    keys: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    cursor_cell: Res<CursorCell>,
+    square_map: Res<SquareMap>,
+    mut q_square_sprite: Query<(&mut Sprite, Has<Alive>), With<Square>>,
+    mut placement_history: ResMut<PlacementHistory>,
+    theme: Res<Theme>,
+    mut commands: Commands,
+) {
+    if !bindings.just_pressed(&keys, Action::CursorToggle) {
+        return;
+    }
+
+    let Some(square_id) = square_map.get(cursor_cell.x, cursor_cell.y) else {
+        return;
+    };
+    let Ok((mut sprite, is_alive)) = q_square_sprite.get_mut(square_id.entity) else {
+        warn!("square entity {} has no sprite", square_id.entity);
+        return;
+    };
+
+    set_square_alive(
+        &mut commands,
+        &theme,
+        square_id.entity,
+        &mut sprite,
+        !is_alive,
+    );
+    placement_history.record(square_id.entity, !is_alive);
+    placement_history.finish_stroke();
+}
+
+fn update_cursor_highlight(
+    game_state: Res<State<GameState>>,
+    cursor_cell: Res<CursorCell>,
+    grid_config: Res<GridConfig>,
+    cell_size: Res<CellSize>,
+    mut q_highlight: Query<(&mut Transform, &mut Sprite, &mut Visibility), With<CursorHighlight>>,
+) {
+    let Ok((mut transform, mut sprite, mut visibility)) = q_highlight.get_single_mut() else {
+        return;
+    };
+
+    if !matches!(game_state.get(), GameState::Placing) {
+        *visibility = Visibility::Hidden;
+        return;
+    }
+
+    *visibility = Visibility::Inherited;
+    sprite.custom_size = Some(Vec2::new(cell_size.w, cell_size.h));
+    let (x, y) = grid_config
+        .geometry(&cell_size)
+        .cell_to_world(cursor_cell.x, cursor_cell.y);
+    transform.translation.x = x;
+    transform.translation.y = y;
+}
+
+/// Outlines the square a click would toggle. Gizmos only last a frame, so the outline
+/// disappears by itself once the cursor leaves the grid or the window.
+fn highlight_hover(
+    mut gizmos: Gizmos,
+    q_window: Query<&Window, With<PrimaryWindow>>,
+    q_camera: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+    grid_config: Res<GridConfig>,
+    cell_size: Res<CellSize>,
+    minimap_hovered: Option<Res<MinimapHovered>>,
+    pointer_over_ui: Res<PointerOverUi>,
+) {
+    if pointer_over_ui.0 || minimap_hovered.is_some_and(|hovered| hovered.0) {
+        return;
+    }
+
+    let (Ok((camera, camera_transform)), Ok(window)) =
+        (q_camera.get_single(), q_window.get_single())
+    else {
+        return;
+    };
+
+    let Some((x, y)) = cursor_to_square(window, camera, camera_transform, &grid_config, &cell_size)
+    else {
+        return;
+    };
+
+    let (center, size) = grid_config.geometry(&cell_size).cells_rect((x, y), (x, y));
+
+    gizmos.rect_2d(
+        Isometry2d::from_translation(Vec2::from(center)),
+        Vec2::from(size),
+        Color::WHITE.with_alpha(0.6),
+    );
+}
+
+#[allow(clippy::too_many_arguments)]
+fn update_cell_tooltip(
+    game_state: Res<State<GameState>>,
+    paused: Res<Paused>,
+    q_window: Query<&Window, With<PrimaryWindow>>,
+    q_camera: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+    (grid_config, cell_size, boundary, neighborhood): (
+        Res<GridConfig>,
+        Res<CellSize>,
+        Res<ActiveBoundary>,
+        Res<ActiveNeighborhood>,
+    ),
+    square_map: Res<SquareMap>,
+    q_squares: Query<(Has<Alive>, Has<Dying>), With<Square>>,
+    minimap_hovered: Res<MinimapHovered>,
+    pointer_over_ui: Res<PointerOverUi>,
+    mut q_tooltip: Query<(&mut Text, &mut Node, &mut Visibility), With<CellTooltip>>,
+) {
+    let Ok((mut text, mut node, mut visibility)) = q_tooltip.get_single_mut() else {
+        return;
+    };
+
+    let inspecting = *game_state.get() == GameState::Placing || paused.0;
+    let (Ok((camera, camera_transform)), Ok(window)) =
+        (q_camera.get_single(), q_window.get_single())
+    else {
+        return;
+    };
+    let hovered = window.cursor_position().zip(cursor_to_square(
+        window,
+        camera,
+        camera_transform,
+        &grid_config,
+        &cell_size,
+    ));
+
+    let (Some((cursor, (x, y))), true) = (
+        hovered,
+        inspecting && !pointer_over_ui.0 && !minimap_hovered.0,
+    ) else {
+        visibility.set_if_neq(Visibility::Hidden);
+        return;
+    };
+
+    let state = |x, y| {
+        square_map
+            .get(x, y)
+            .and_then(|square| q_squares.get(square.entity).ok())
+            .unwrap_or_default()
+    };
+    let status = match state(x, y) {
+        (true, _) => "alive",
+        (_, true) => "dying",
+        _ => "dead",
+    };
+    let live_neighbors = life::neighbors(
+        x,
+        y,
+        grid_config.cols,
+        grid_config.rows,
+        boundary.0,
+        neighborhood.0,
+    )
+    .into_iter()
+    .filter(|&(x, y)| state(x, y).0)
+    .count() as u32
+        + life::off_board_neighbors(
+            x,
+            y,
+            grid_config.cols,
+            grid_config.rows,
+            boundary.0,
+            neighborhood.0,
+        );
+    let tooltip = format!("({x}, {y}) {status}\n{live_neighbors} live neighbors");
+
+    if text.0 != tooltip {
+        text.0 = tooltip;
+    }
+
+    node.left = Val::Px(cursor.x + 16.);
+    node.top = Val::Px(cursor.y + 16.);
+    visibility.set_if_neq(Visibility::Inherited);
+}
+
+fn finish_placement_stroke(
+    mouse_button: Res<ButtonInput<MouseButton>>,
+    mut placement_history: ResMut<PlacementHistory>,
+    mut last_painted_square: ResMut<LastPaintedSquare>,
+) {
+    if mouse_button.any_just_released([MouseButton::Left, MouseButton::Right]) {
+        placement_history.finish_stroke();
+        last_painted_square.0 = None;
+    }
+}
+
+/// Starting the simulation mid-drag ends the stroke, so the next one does not draw a
+/// line from where this one stopped.
+fn reset_last_painted_square(mut last_painted_square: ResMut<LastPaintedSquare>) {
+    last_painted_square.0 = None;
+}
+
+fn handle_undo_redo(
+    keys: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    mut placement_history: ResMut<PlacementHistory>,
+    mut q_square_sprite: Query<&mut Sprite, With<Square>>,
+    theme: Res<Theme>,
+    mut commands: Commands,
+) {
+    if !ctrl_pressed(&keys) {
+        return;
+    }
+
+    if bindings.just_pressed(&keys, Action::Undo) {
+        let Some(action) = placement_history.undo.pop() else {
+            return;
+        };
+
+        for &(entity, alive) in action.iter().rev() {
+            if let Ok(mut sprite) = q_square_sprite.get_mut(entity) {
+                set_square_alive(&mut commands, &theme, entity, &mut sprite, !alive);
+                placement_history.note_edit(entity, !alive);
+            }
+        }
+
+        placement_history.redo.push(action);
+    } else if bindings.just_pressed(&keys, Action::Redo) {
+        let Some(action) = placement_history.redo.pop() else {
+            return;
+        };
+
+        for &(entity, alive) in action.iter() {
+            if let Ok(mut sprite) = q_square_sprite.get_mut(entity) {
+                set_square_alive(&mut commands, &theme, entity, &mut sprite, alive);
+                placement_history.note_edit(entity, alive);
+            }
+        }
+
+        placement_history.undo.push(action);
+    }
+}
+
+fn reset_placement_history(mut placement_history: ResMut<PlacementHistory>) {
+    *placement_history = PlacementHistory {
+        edits: placement_history.edits.take(),
+        replaced: placement_history.replaced,
+        ..default()
+    };
+}
+
+fn set_square_alive(
+    commands: &mut Commands,
+    theme: &Theme,
+    entity: Entity,
+    sprite: &mut Sprite,
+    alive: bool,
+) {
+    if alive {
+        sprite.color = age_color(theme, 0);
+        commands
+            .entity(entity)
+            .insert((Alive, Age(0)))
+            .remove::<(DeadSince, Dying, CellColor, Immortal)>();
+    } else {
+        sprite.color = theme.dead;
+        commands
+            .entity(entity)
+            .remove::<(Alive, Age, DeadSince, Dying, CellColor, Immortal)>();
+    }
+}
+
+fn handle_random_fill(
+    keys: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    one_shot_systems: Res<OneShotSystems>,
+    mut commands: Commands,
+) {
+    if !bindings.just_pressed(&keys, Action::Random) {
+        return;
+    }
+
+    if shift_pressed(&keys) {
+        commands.run_system(one_shot_systems.random_fill_selection);
+    } else {
+        commands.run_system(one_shot_systems.random_fill);
+    }
+}
+
+fn random_fill(
+    fill_density: Res<FillDensity>,
+    mut rng: ResMut<SimulationRng>,
+    mut q_squares: Query<(Entity, &mut Sprite, Has<Alive>), With<Square>>,
+    mut placement_history: ResMut<PlacementHistory>,
+    mut credits: ResMut<PatternCredits>,
+    theme: Res<Theme>,
+    mut commands: Commands,
+) {
+    let density = fill_density.0.clamp(0., 1.) as f64;
+    placement_history.finish_stroke();
+    credits.forget_loaded();
+
+    for (entity, mut sprite, is_alive) in q_squares.iter_mut() {
+        let alive = rng.0.gen_bool(density);
+        set_square_alive(&mut commands, &theme, entity, &mut sprite, alive);
+
+        if alive != is_alive {
+            placement_history.record(entity, alive);
+        }
+    }
+
+    placement_history.finish_stroke();
+    placement_history.replaced = true;
+}
+
+/// Like [`random_fill`], but only inside the selection, leaving the rest of the board
+/// as it is.
+#[allow(clippy::too_many_arguments)]
+fn random_fill_selection(
+    selection: Res<Selection>,
+    square_map: Res<SquareMap>,
+    fill_density: Res<FillDensity>,
+    mut rng: ResMut<SimulationRng>,
+    mut q_square_sprite: Query<(&mut Sprite, Has<Alive>), With<Square>>,
+    mut placement_history: ResMut<PlacementHistory>,
+    theme: Res<Theme>,
+    mut commands: Commands,
+) {
+    let Some(((min_x, min_y), (max_x, max_y))) = selection.bounds() else {
+        info!("select a region with Alt+drag before filling it");
+        return;
+    };
+
+    let density = fill_density.0.clamp(0., 1.) as f64;
+    placement_history.finish_stroke();
+
+    // Visit the squares in a fixed order so a seeded run always fills the same cells.
+    for y in min_y..=max_y {
+        for x in min_x..=max_x {
+            let Some(square_id) = square_map.get(x, y) else {
+                continue;
+            };
+
+            if let Ok((mut sprite, is_alive)) = q_square_sprite.get_mut(square_id.entity) {
+                let alive = rng.0.gen_bool(density);
+                set_square_alive(&mut commands, &theme, square_id.entity, &mut sprite, alive);
+
+                if alive != is_alive {
+                    placement_history.record(square_id.entity, alive);
+                }
+            }
+        }
+    }
+
+    placement_history.finish_stroke();
+}
+
+#[allow(clippy::too_many_arguments)]
+fn handle_selection(
+    mouse_button: Res<ButtonInput<MouseButton>>,
+    keys: Res<ButtonInput<KeyCode>>,
+    q_window: Query<&Window, With<PrimaryWindow>>,
+    q_camera: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+    grid_config: Res<GridConfig>,
+    mut selection: ResMut<Selection>,
+    minimap_hovered: Res<MinimapHovered>,
+    pointer_over_ui: Res<PointerOverUi>,
+    cell_size: Res<CellSize>,
+) {
+    if minimap_hovered.0 || pointer_over_ui.0 {
+        return;
+    }
+
+    if !alt_pressed(&keys) || !mouse_button.pressed(MouseButton::Left) {
+        return;
+    }
+
+    let (Ok((camera, camera_transform)), Ok(window)) =
+        (q_camera.get_single(), q_window.get_single())
+    else {
+        warn!("no camera or primary window to select squares with");
+        return;
+    };
+
+    let Some(position) = cursor_to_world(window, camera, camera_transform) else {
+        return;
+    };
+
+    // Dragging past the edge keeps the selection on the nearest square, but a new
+    // selection has to start on the board.
+    let geometry = grid_config.geometry(&cell_size);
+    let current = if mouse_button.just_pressed(MouseButton::Left) {
+        let Some(square) = geometry.world_to_cell(position.into()) else {
+            return;
+        };
+
+        square
+    } else {
+        geometry.nearest_cell(position.into())
+    };
+
+    selection.0 = match selection.0 {
+        Some((anchor, _)) if !mouse_button.just_pressed(MouseButton::Left) => {
+            Some((anchor, current))
+        }
+        _ => Some((current, current)),
+    };
+}
+
+fn draw_selection(
+    mut gizmos: Gizmos,
+    selection: Res<Selection>,
+    grid_config: Res<GridConfig>,
+    cell_size: Res<CellSize>,
+) {
+    let Some(((min_x, min_y), (max_x, max_y))) = selection.bounds() else {
+        return;
+    };
+
+    let (center, size) = grid_config
+        .geometry(&cell_size)
+        .cells_rect((min_x, min_y), (max_x, max_y));
+    let (center, size) = (Vec2::from(center), Vec2::from(size));
+
+    gizmos.rect_2d(
+        Isometry2d::from_translation(center),
+        size,
+        Color::srgb(0., 0.6, 1.),
+    );
+}
+
+#[allow(clippy::too_many_arguments)]
+fn handle_copy_paste(
+    keys: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    q_window: Query<&Window, With<PrimaryWindow>>,
+    q_camera: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+    square_map: Res<SquareMap>,
+    grid_config: Res<GridConfig>,
+    selection: Res<Selection>,
+    stamp_orientation: Res<StampOrientation>,
+    mut clipboard: ResMut<Clipboard>,
+    mut active_stamp: ResMut<ActiveStamp>,
+    q_squares: Query<&Square, With<Alive>>,
+    mut q_square_sprite: Query<(&mut Sprite, Has<Alive>), With<Square>>,
+    mut placement_history: ResMut<PlacementHistory>,
+    theme: Res<Theme>,
+    mut commands: Commands,
+    cell_size: Res<CellSize>,
+) {
+    if !ctrl_pressed(&keys) {
+        return;
+    }
+
+    if bindings.just_pressed(&keys, Action::Copy) {
+        let Some(((min_x, min_y), (max_x, max_y))) = selection.bounds() else {
+            warn!("nothing selected to copy");
+            return;
+        };
+
+        *clipboard = Clipboard {
+            width: max_x - min_x + 1,
+            height: max_y - min_y + 1,
+            cells: q_squares
+                .iter()
+                .filter(|square| {
+                    (min_x..=max_x).contains(&square.x) && (min_y..=max_y).contains(&square.y)
+                })
+                .map(|square| (square.x - min_x, square.y - min_y))
+                .collect(),
+        };
+        *active_stamp = ActiveStamp::Clipboard;
+        info!(
+            "copied a {}x{} region with {} live squares",
+            clipboard.width,
+            clipboard.height,
+            clipboard.cells.len()
+        );
+    } else if bindings.just_pressed(&keys, Action::Paste) {
+        if clipboard.width == 0 {
+            warn!("clipboard is empty");
+            return;
+        }
+
+        let (Ok((camera, camera_transform)), Ok(window)) =
+            (q_camera.get_single(), q_window.get_single())
+        else {
+            warn!("no camera or primary window to paste with");
+            return;
+        };
+
+        let Some((square_x, square_y)) =
+            cursor_to_square(window, camera, camera_transform, &grid_config, &cell_size)
+        else {
+            return;
+        };
+
+        for dy in 0..clipboard.height {
+            for dx in 0..clipboard.width {
+                let (x, y) = stamp_orientation
+                    .0
+                    .apply((dx, dy), clipboard.width, clipboard.height);
+                let Some(square_id) = square_map.get(square_x + x, square_y + y) else {
+                    continue;
+                };
+                let Ok((mut square_sprite, is_alive)) = q_square_sprite.get_mut(square_id.entity)
+                else {
+                    warn!("square entity {} has no sprite", square_id.entity);
+                    continue;
+                };
+                let alive = clipboard.cells.contains(&(dx, dy));
+
+                if alive != is_alive {
+                    set_square_alive(
+                        &mut commands,
+                        &theme,
+                        square_id.entity,
+                        &mut square_sprite,
+                        alive,
+                    );
+                    placement_history.record(square_id.entity, alive);
+                }
+            }
+        }
+
+        placement_history.finish_stroke();
+        info!("pasted clipboard at ({square_x}, {square_y})");
+    }
+}
+
+fn handle_stamp_orientation(
+    keys: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    mut stamp_orientation: ResMut<StampOrientation>,
+) {
+    if ctrl_pressed(&keys) || shift_pressed(&keys) {
+        return;
+    }
+
+    if bindings.just_pressed(&keys, Action::RotateClockwise) {
+        stamp_orientation.0.rotate_clockwise();
+    }
+
+    if bindings.just_pressed(&keys, Action::RotateCounterclockwise) {
+        stamp_orientation.0.rotate_counterclockwise();
+    }
+
+    if bindings.just_pressed(&keys, Action::MirrorHorizontally) {
+        stamp_orientation.0.mirror_horizontally();
+    }
+
+    if bindings.just_pressed(&keys, Action::MirrorVertically) {
+        stamp_orientation.0.mirror_vertically();
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn draw_stamp_preview(
+    mut gizmos: Gizmos,
+    q_window: Query<&Window, With<PrimaryWindow>>,
+    q_camera: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+    grid_config: Res<GridConfig>,
+    clipboard: Res<Clipboard>,
+    active_stamp: Res<ActiveStamp>,
+    stamp_orientation: Res<StampOrientation>,
+    cell_size: Res<CellSize>,
+) {
+    let Some(stamp) = active_stamp_pattern(*active_stamp, &clipboard) else {
+        return;
+    };
+
+    let (Ok((camera, camera_transform)), Ok(window)) =
+        (q_camera.get_single(), q_window.get_single())
+    else {
+        return;
+    };
+
+    let Some((square_x, square_y)) =
+        cursor_to_square(window, camera, camera_transform, &grid_config, &cell_size)
+    else {
+        return;
+    };
+
+    let (stamp_width, stamp_height) = stamp_orientation.0.size(stamp.width, stamp.height);
+    let (center, size) = grid_config.geometry(&cell_size).cells_rect(
+        (square_x, square_y),
+        (square_x + stamp_width - 1, square_y + stamp_height - 1),
+    );
+    let (center, size) = (Vec2::from(center), Vec2::from(size));
+
+    gizmos.rect_2d(
+        Isometry2d::from_translation(center),
+        size,
+        Color::srgb(1., 0.6, 0.),
+    );
+}
+
+fn active_stamp_pattern(active_stamp: ActiveStamp, clipboard: &Clipboard) -> Option<rle::Pattern> {
+    match active_stamp {
+        ActiveStamp::Nothing => None,
+        ActiveStamp::Clipboard => Some(rle::Pattern {
+            width: clipboard.width,
+            height: clipboard.height,
+            cells: clipboard.cells.iter().copied().collect(),
+        }),
+        ActiveStamp::Pattern(index) => {
+            let pattern = &patterns::PATTERNS[index];
+            let (width, height) = pattern.size();
+
+            Some(rle::Pattern {
+                width,
+                height,
+                cells: pattern.cells.to_vec(),
+            })
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn preview_pattern(
+    q_window: Query<&Window, With<PrimaryWindow>>,
+    q_camera: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+    square_map: Res<SquareMap>,
+    grid_config: Res<GridConfig>,
+    clipboard: Res<Clipboard>,
+    active_stamp: Res<ActiveStamp>,
+    stamp_orientation: Res<StampOrientation>,
+    mut pattern_preview: ResMut<PatternPreview>,
+    theme: Res<Theme>,
+    mut q_square_sprite: Query<(&mut Sprite, Has<Alive>), With<Square>>,
+    cell_size: Res<CellSize>,
+) {
+    let cursor_square = match (q_camera.get_single(), q_window.get_single()) {
+        (Ok((camera, camera_transform)), Ok(window)) => {
+            cursor_to_square(window, camera, camera_transform, &grid_config, &cell_size)
+        }
+        _ => None,
+    };
+
+    let targets: Vec<Entity> = match (
+        cursor_square,
+        active_stamp_pattern(*active_stamp, &clipboard),
+    ) {
+        (Some((square_x, square_y)), Some(stamp)) => stamp
+            .cells
+            .into_iter()
+            .map(|cell| stamp_orientation.0.apply(cell, stamp.width, stamp.height))
+            .filter_map(|(dx, dy)| square_map.get(square_x + dx, square_y + dy))
+            .map(|square_id| square_id.entity)
+            .collect(),
+        _ => Vec::new(),
+    };
+
+    for &entity in pattern_preview
+        .0
+        .iter()
+        .filter(|entity| !targets.contains(entity))
+    {
+        if let Ok((mut sprite, false)) = q_square_sprite.get_mut(entity) {
+            sprite.color = theme.dead;
+        }
+    }
+
+    for &entity in targets.iter() {
+        if let Ok((mut sprite, false)) = q_square_sprite.get_mut(entity) {
+            sprite.color = theme.dead.mix(&Color::srgb(0.5, 0.5, 0.5), 0.5);
+        }
+    }
+
+    pattern_preview.0 = targets;
+}
+
+fn clear_pattern_preview(
+    mut pattern_preview: ResMut<PatternPreview>,
+    theme: Res<Theme>,
+    mut q_square_sprite: Query<(&mut Sprite, Has<Alive>), With<Square>>,
+) {
+    for entity in pattern_preview.0.drain(..) {
+        if let Ok((mut sprite, false)) = q_square_sprite.get_mut(entity) {
+            sprite.color = theme.dead;
+        }
+    }
+}
+
+fn handle_neighbor_overlay_toggle(
+    keys: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    mut neighbor_overlay: ResMut<NeighborOverlay>,
+) {
+    if bindings.just_pressed(&keys, Action::NeighborOverlay) {
+        neighbor_overlay.enabled = !neighbor_overlay.enabled;
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn update_neighbor_overlay(
+    mut commands: Commands,
+    mut neighbor_overlay: ResMut<NeighborOverlay>,
+    grid_config: Res<GridConfig>,
+    cell_size: Res<CellSize>,
+    boundary: Res<ActiveBoundary>,
+    neighborhood: Res<ActiveNeighborhood>,
+    q_added_alive: Query<(), Added<Alive>>,
+    mut removed_alive: RemovedComponents<Alive>,
+    q_alive_squares: Query<&Square, With<Alive>>,
+) {
+    let board_changed = !q_added_alive.is_empty() || removed_alive.read().count() > 0;
+
+    if !neighbor_overlay.is_changed() && !cell_size.is_changed() && !board_changed {
+        return;
+    }
+
+    for entity in neighbor_overlay.labels.drain(..) {
+        commands.entity(entity).despawn();
+    }
+
+    if !neighbor_overlay.enabled {
+        return;
+    }
+
+    let cells: HashSet<(i32, i32)> = q_alive_squares
+        .iter()
+        .map(|square| (square.x, square.y))
+        .collect();
+    let neighbor_counts = life::neighbor_counts(
+        &cells,
+        grid_config.cols,
+        grid_config.rows,
+        boundary.0,
+        neighborhood.0,
+    );
+
+    let geometry = grid_config.geometry(&cell_size);
+
+    for ((x, y), count) in neighbor_counts {
+        let label = commands
+            .spawn((
+                Text2d::new(count.to_string()),
+                TextFont {
+                    font_size: cell_size.h * 0.6,
+                    ..default()
+                },
+                TextColor(Color::srgb(1., 0.3, 0.3)),
+                Transform::from_translation(Vec2::from(geometry.cell_to_world(x, y)).extend(2.)),
+            ))
+            .id();
+
+        neighbor_overlay.labels.push(label);
+    }
+}
+
+fn clear_neighbor_overlay(mut commands: Commands, mut neighbor_overlay: ResMut<NeighborOverlay>) {
+    for entity in neighbor_overlay.labels.drain(..) {
+        commands.entity(entity).despawn();
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn handle_pattern_spawn(
+    keys: Res<ButtonInput<KeyCode>>,
+    q_window: Query<&Window, With<PrimaryWindow>>,
+    q_camera: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+    square_map: Res<SquareMap>,
+    grid_config: Res<GridConfig>,
+    mut q_square_sprite: Query<(&mut Sprite, Has<Alive>), With<Square>>,
+    stamp_orientation: Res<StampOrientation>,
+    mut active_stamp: ResMut<ActiveStamp>,
+    mut placement_history: ResMut<PlacementHistory>,
+    theme: Res<Theme>,
+    mut commands: Commands,
+    cell_size: Res<CellSize>,
+) {
+    let Some(index) = PATTERN_KEYS.iter().position(|key| keys.just_pressed(*key)) else {
+        return;
+    };
+    let pattern = &patterns::PATTERNS[index];
+
+    *active_stamp = ActiveStamp::Pattern(index);
+
+    let (Ok((camera, camera_transform)), Ok(window)) =
+        (q_camera.get_single(), q_window.get_single())
+    else {
+        warn!("no camera or primary window to place squares with");
+        return;
+    };
+
+    let Some((square_x, square_y)) =
+        cursor_to_square(window, camera, camera_transform, &grid_config, &cell_size)
+    else {
+        return;
+    };
+
+    let (width, height) = pattern.size();
+
+    for &offset in pattern.cells {
+        let (dx, dy) = stamp_orientation.0.apply(offset, width, height);
+        let x = square_x + dx;
+        let y = square_y + dy;
+
+        if let Some(square_id) = square_map.get(x, y) {
+            let Ok((mut square_sprite, is_alive)) = q_square_sprite.get_mut(square_id.entity)
+            else {
+                warn!("square entity {} has no sprite", square_id.entity);
+                continue;
+            };
+
+            if !is_alive {
+                set_square_alive(
+                    &mut commands,
+                    &theme,
+                    square_id.entity,
+                    &mut square_sprite,
+                    true,
+                );
+                placement_history.record(square_id.entity, true);
+            }
+        }
+    }
+
+    placement_history.finish_stroke();
+    info!("placed {} at ({square_x}, {square_y})", pattern.name);
+}
+
+fn editing_allowed(
+    game_state: Res<State<GameState>>,
+    edit_while_running: Res<EditWhileRunning>,
+) -> bool {
+    matches!(game_state.get(), GameState::Placing) || edit_while_running.0
+}
+
+/// Throws away a generation that was calculated ahead from a board that has since been
+/// edited, or that may run past the generation a counted step should pause at, so the
+/// next tick calculates it again.
+fn discard_stale_calculation(
+    placement_history: Res<PlacementHistory>,
+    pause_at: Res<PauseAtGeneration>,
+    mut logic_state: ResMut<LogicState>,
+    mut history: ResMut<History>,
+    render_input: Res<RenderInput>,
+) {
+    if !(placement_history.is_changed() || pause_at.is_changed())
+        || !matches!(*logic_state, LogicState::Calculated)
+    {
+        return;
+    }
+
+    let kept = history
+        .0
+        .len()
+        .saturating_sub(render_input.generations as usize);
+    history.0.truncate(kept);
+    *logic_state = LogicState::CalculationNeeded;
+}
+
+fn calculate_ahead(
+    mut logic_state: ResMut<LogicState>,
+    mut commands: Commands,
+    one_shot_systems: Res<OneShotSystems>,
+    paused: Res<Paused>,
+) {
+    if !paused.0 && matches!(*logic_state, LogicState::CalculationNeeded) {
+        *logic_state = LogicState::CalculatingCurrently;
+        commands.run_system(one_shot_systems.game_logic)
+    }
+}
+
+/// Recovers from a calculation that was queued but never ran, which would otherwise stop
+/// [`calculate_ahead`] from ever queueing another one and freeze the simulation.
+fn watch_logic_state(mut logic_state: ResMut<LogicState>, mut watchdog: ResMut<LogicWatchdog>) {
+    if !matches!(*logic_state, LogicState::CalculatingCurrently) {
+        watchdog.0 = 0;
+        return;
+    }
+
+    watchdog.0 += 1;
+
+    if watchdog.0 > LOGIC_STALL_FRAMES {
+        error!(
+            "the next generation was still being calculated after {LOGIC_STALL_FRAMES} frames, calculating it again"
+        );
+        *logic_state = LogicState::CalculationNeeded;
+        watchdog.0 = 0;
+    }
+}
+
+fn game_loop(
+    logic_state: Res<LogicState>,
+    mut commands: Commands,
+    one_shot_systems: Res<OneShotSystems>,
+    real_time: Res<Time<Real>>,
+    paused: Res<Paused>,
+    mut tick_rate: ResMut<TickRate>,
+) {
+    if paused.0 {
+        tick_rate.last_tick = None;
+        return;
+    }
+
+    // A slow frame runs several fixed ticks back to back; only the first of them
+    // finds a generation that was calculated ahead, so the rest calculate their own.
+    if !matches!(*logic_state, LogicState::Calculated) {
+        commands.run_system(one_shot_systems.game_logic);
+    }
+
+    tick_rate.record_tick(real_time.elapsed());
+    commands.run_system(one_shot_systems.render);
+}
+
+fn handle_single_step(
+    keys: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    paused: Res<Paused>,
+    step_count: Res<StepCount>,
+    mut commands: Commands,
+    one_shot_systems: Res<OneShotSystems>,
+) {
+    if !paused.0 || step_count.0.is_some() || !bindings.just_pressed(&keys, Action::Step) {
+        return;
+    }
+
+    commands.run_system(one_shot_systems.single_step);
+}
+
+/// Advances exactly one generation, from `.` or the control panel's Step button.
+fn single_step(
+    mut logic_state: ResMut<LogicState>,
+    render_input: Res<RenderInput>,
+    mut history: ResMut<History>,
+    mut single_step: ResMut<SingleStep>,
+    one_shot_systems: Res<OneShotSystems>,
+    mut commands: Commands,
+) {
+    // A fast-forward batch calculated ahead of time is more than one generation, so it
+    // is dropped from the rewind history and recalculated.
+    if matches!(*logic_state, LogicState::Calculated) && render_input.generations != 1 {
+        let kept = history
+            .0
+            .len()
+            .saturating_sub(render_input.generations as usize);
+        history.0.truncate(kept);
+        *logic_state = LogicState::CalculationNeeded;
+    }
+
+    // Reuse a generation that was already calculated ahead of time, so it is not
+    // recorded in the rewind history twice.
+    if !matches!(*logic_state, LogicState::Calculated) {
+        single_step.0 = true;
+        commands.run_system(one_shot_systems.game_logic);
+    }
+
+    commands.run_system(one_shot_systems.render);
+}
+
+#[allow(clippy::too_many_arguments)]
+fn handle_rewind(
+    keys: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    mut history: ResMut<History>,
+    mut q_squares: Query<(Entity, &Square, &mut Sprite)>,
+    mut render_input: ResMut<RenderInput>,
+    mut logic_state: ResMut<LogicState>,
+    mut generation: ResMut<Generation>,
+    theme: Res<Theme>,
+    (ruleset, automaton): (Res<ActiveRuleset>, Res<ActiveAutomaton>),
+    mut commands: Commands,
+) {
+    if !bindings.just_pressed(&keys, Action::Rewind) {
+        return;
+    }
+
+    // Once the next batch has been calculated, its newest entries start with the
+    // board currently on screen rather than the one before it.
+    if !matches!(*logic_state, LogicState::CalculationNeeded) {
+        let kept = history
+            .0
+            .len()
+            .saturating_sub(render_input.generations as usize);
+        history.0.truncate(kept);
+    }
+
+    let Some(cells) = history.0.pop_back() else {
+        info!("no earlier generation to rewind to");
+        *logic_state = LogicState::CalculationNeeded;
+        return;
+    };
+
+    // A square that is not alive on a board is dying at the stage of the most recent of
+    // the earlier boards it was alive on, as long as that is within its dying states.
+    let stages = dying_stages(automaton.0, &ruleset.0);
+
+    for (entity, square, mut sprite) in q_squares.iter_mut() {
+        let alive = cells.contains(&(square.x, square.y));
+        set_square_alive(&mut commands, &theme, entity, &mut sprite, alive);
+
+        let stage = history
+            .0
+            .iter()
+            .rev()
+            .take(stages as usize)
+            .position(|earlier| earlier.contains(&(square.x, square.y)));
+
+        if let Some(stage) = stage.filter(|_| !alive) {
+            let stage = stage as u8 + 1;
+            sprite.color = dying_color(&theme, stage, stages);
+            commands.entity(entity).insert(Dying(stage));
+        }
+    }
+
+    render_input.entities_born.clear();
+    render_input.entities_that_died.clear();
+    render_input.entities_dying.clear();
+    *logic_state = LogicState::CalculationNeeded;
+    generation.0 = generation.0.saturating_sub(1);
+}
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn game_logic(
+    mut commands: Commands,
+    mut square_map: ResMut<SquareMap>,
+    mut grid_config: ResMut<GridConfig>,
+    boundary: Res<ActiveBoundary>,
+    expand_grid_enabled: Res<ExpandGrid>,
+    theme: Res<Theme>,
+    (ruleset, neighborhood, automaton): (
+        Res<ActiveRuleset>,
+        Res<ActiveNeighborhood>,
+        Res<ActiveAutomaton>,
+    ),
+    mut q_squares: Query<(&mut Square, Has<Alive>, Option<&Dying>)>,
+    mut logic_state: ResMut<LogicState>,
+    mut render_input: ResMut<RenderInput>,
+    (mut history, mut board, sparse, mut sparse_buffers): (
+        ResMut<History>,
+        ResMut<BoardBuffers>,
+        Res<SparseBackend>,
+        ResMut<SparseBuffers>,
+    ),
+    (mut stable, mut settle_start, auto_stop_on_stable): (
+        ResMut<Stable>,
+        ResMut<SettleStart>,
+        Res<AutoStopOnStable>,
+    ),
+    (mut paused, rule_chance, mut rng): (ResMut<Paused>, Res<RuleChance>, ResMut<SimulationRng>),
+    (steps_per_tick, mut single_step, generation, pause_at): (
+        Res<StepsPerTick>,
+        ResMut<SingleStep>,
+        Res<Generation>,
+        Res<PauseAtGeneration>,
+    ),
+    cell_size: Res<CellSize>,
+    (q_cell_colors, q_immortal): (Query<(Entity, &CellColor)>, Query<Entity, With<Immortal>>),
+) {
+    let span = info_span!(
+        "game_logic",
+        generations = field::Empty,
+        candidates = field::Empty,
+        born = field::Empty,
+        died = field::Empty
+    )
+    .entered();
+
+    render_input.entities_born.clear();
+    render_input.entities_that_died.clear();
+    render_input.entities_dying.clear();
+    render_input.entities_recolored.clear();
+
+    let touches_edge = q_squares
+        .iter()
+        .any(|(square, is_alive, _)| is_alive && on_edge((square.x, square.y), &grid_config));
+    let can_expand = grid_config.cols.max(grid_config.rows) + 2 <= MAX_EXPANDED_DIMENSION;
+
+    if expand_grid_enabled.0 && touches_edge && can_expand {
+        expand_grid(
+            &mut commands,
+            &mut square_map,
+            &mut grid_config,
+            &cell_size,
+            &theme,
+            &mut q_squares,
+        );
+
+        for cells in history.0.iter_mut() {
+            *cells = cells.iter().map(|&(x, y)| (x + 1, y + 1)).collect();
+        }
+    }
+
+    let cells: HashSet<(i32, i32)> = q_squares
+        .iter()
+        .filter(|(_, is_alive, _)| *is_alive)
+        .map(|(square, _, _)| (square.x, square.y))
+        .collect();
+    let initial_colors: HashMap<(i32, i32), Color> = q_cell_colors
+        .iter()
+        .filter_map(|(entity, color)| {
+            let (square, _, _) = q_squares.get(entity).ok()?;
+            Some(((square.x, square.y), color.0))
+        })
+        .collect();
+    let mut colors = initial_colors.clone();
+    let immortal: Vec<(i32, i32)> = q_immortal
+        .iter()
+        .filter_map(|entity| {
+            let (square, _, _) = q_squares.get(entity).ok()?;
+            Some((square.x, square.y))
+        })
+        .collect();
+    let mut next_cells = cells.clone();
+    let mut generations = 0;
+    // Squares whose state could have changed: the whole board for the dense backend,
+    // only the neighborhoods of live cells for the sparse one.
+    let mut candidates = 0;
+
+    if !sparse.0 {
+        board.board_mut().load_cells(
+            grid_config.cols,
+            grid_config.rows,
+            &mut cells.iter().copied(),
+        );
+
+        for (square, _, dying) in q_squares.iter() {
+            if let Some(dying) = dying {
+                board.set(square.x, square.y, CellState::Dying(dying.0));
+            }
+        }
+    }
+
+    // Only the final board of a fast-forward batch is rendered, but every generation
+    // still goes through stability detection and the rewind history.
+    // A counted step never calculates past the generation it pauses at.
+    let steps = pause_at.0.map_or(MAX_STEPS_PER_TICK, |target| {
+        target
+            .saturating_sub(generation.0)
+            .min(MAX_STEPS_PER_TICK as u64) as u32
+    });
+
+    let batch = if std::mem::take(&mut single_step.0) {
+        1
+    } else {
+        steps_per_tick.0.clamp(1, MAX_STEPS_PER_TICK)
+    };
+
+    while generations < batch.min(steps) {
+        // Once the rewind history is full, the board that falls off its front is
+        // recycled as the buffer for the next generation.
+        let mut stepped = if history.0.len() == HISTORY_LENGTH {
+            history.0.pop_front().unwrap_or_default()
+        } else {
+            HashSet::new()
+        };
+        let scan = info_span!("neighbor_scan", sparse = sparse.0).entered();
+        if sparse.0 {
+            life::step_into(
+                &next_cells,
+                grid_config.cols,
+                grid_config.rows,
+                boundary.0,
+                neighborhood.0,
+                &ruleset.0,
+                &mut sparse_buffers.0,
+                &mut stepped,
+            );
+            stepped.extend(immortal.iter().copied());
+            candidates += sparse_buffers.0.candidates();
+        } else {
+            board.step(automaton.0, boundary.0, neighborhood.0, &ruleset.0);
+            for &(x, y) in &immortal {
+                board.set(x, y, CellState::Alive);
+            }
+            stepped.clear();
+            stepped.extend(board.board().alive_cells());
+            candidates += (grid_config.cols * grid_config.rows) as usize;
+        }
+        scan.exit();
+
+        if !rule_chance.is_exact() {
+            let (unborn, killed) =
+                roll_rule_chance(&next_cells, &stepped, &immortal, &rule_chance, &mut rng.0);
+            let death_state = chance_death_state(automaton.0, &ruleset.0);
+
+            for (cells, state) in [(unborn, CellState::Dead), (killed, death_state)] {
+                for (x, y) in cells {
+                    stepped.remove(&(x, y));
+
+                    if !sparse.0 {
+                        board.set(x, y, state);
+                    }
+                }
+            }
+        }
+
+        if !colors.is_empty() {
+            let born_colors: Vec<((i32, i32), Option<Color>)> = stepped
+                .difference(&next_cells)
+                .map(|&(x, y)| {
+                    let parents = life::neighbors(
+                        x,
+                        y,
+                        grid_config.cols,
+                        grid_config.rows,
+                        boundary.0,
+                        neighborhood.0,
+                    );
+
+                    (
+                        (x, y),
+                        blend_colors(parents.iter().filter_map(|cell| colors.get(cell))),
+                    )
+                })
+                .collect();
+
+            colors.retain(|cell, _| stepped.contains(cell));
+            colors.extend(
+                born_colors
+                    .into_iter()
+                    .filter_map(|(cell, color)| Some((cell, color?))),
+            );
+        }
+
+        let period = if stepped == next_cells {
+            Some(1)
+        } else if history.0.back() == Some(&stepped) {
+            Some(2)
+        } else {
+            None
+        };
+
+        // `stepped` is the board after this generation, which repeats the one `period`
+        // generations earlier, so that is where the board settled.
+        let stepped_generation = generation.0 + generations as u64 + 1;
+
+        if settle_start.0.is_none() || (period.is_none() && stable.0.is_some()) {
+            settle_start.0 = Some(stepped_generation - 1);
+        }
+
+        if let (Some(period), None) = (period, stable.0) {
+            info!("board became stable with period {period}");
+
+            if auto_stop_on_stable.0 {
+                paused.0 = true;
+
+                let settled_at = stepped_generation - period as u64;
+                let start = settle_start.0.unwrap_or_default().min(settled_at);
+                info!(
+                    "stopped after settling in {} generations, from generation {start} to {settled_at}, with population {}",
+                    settled_at - start,
+                    stepped.len()
+                );
+            }
+        }
+
+        stable.0 = period;
+
+        history
+            .0
+            .push_back(std::mem::replace(&mut next_cells, stepped));
+        generations += 1;
+
+        if paused.0
+            || (expand_grid_enabled.0 && next_cells.iter().any(|&cell| on_edge(cell, &grid_config)))
+        {
+            break;
+        }
+    }
+
+    render_input.generations = generations as u64;
+    render_input.died_out = !cells.is_empty() && next_cells.is_empty();
+
+    render_input.entities_that_died.extend(
+        cells
+            .difference(&next_cells)
+            .filter_map(|&(x, y)| square_map.get(x, y))
+            .map(|square| square.entity),
+    );
+    render_input.entities_born.extend(
+        next_cells
+            .difference(&cells)
+            .filter_map(|&(x, y)| square_map.get(x, y))
+            .map(|square| square.entity),
+    );
+    render_input.entities_recolored.extend(
+        next_cells
+            .iter()
+            .filter(|cell| colors.get(cell) != initial_colors.get(cell))
+            .filter_map(|&(x, y)| {
+                Some((square_map.get(x, y)?.entity, colors.get(&(x, y)).copied()))
+            }),
+    );
+    if !sparse.0 {
+        render_input.entities_dying.extend(
+            board
+                .dying_cells()
+                .filter_map(|((x, y), stage)| Some((square_map.get(x, y)?.entity, stage))),
+        );
+    }
+
+    span.record("generations", generations);
+    span.record("candidates", candidates);
+    span.record("born", render_input.entities_born.len());
+    span.record("died", render_input.entities_that_died.len());
+    *logic_state = LogicState::Calculated;
+}
+
+#[allow(clippy::too_many_arguments)]
+fn sync_comparison_board(
+    mut commands: Commands,
+    mut board: ResMut<ComparisonBoard>,
+    game_state: Res<State<GameState>>,
+    generation: Res<Generation>,
+    grid_config: Res<GridConfig>,
+    cell_size: Res<CellSize>,
+    theme: Res<Theme>,
+    (cell_shape, circle_texture): (Res<CellShape>, Res<CircleTexture>),
+    boundary: Res<ActiveBoundary>,
+    neighborhood: Res<ActiveNeighborhood>,
+    mut placement_history: ResMut<PlacementHistory>,
+    (q_squares, q_alive_squares): (Query<&Square>, Query<&Square, With<Alive>>),
+    mut q_comparison_sprites: Query<&mut Sprite, With<ComparisonSquare>>,
+) {
+    let board = &mut *board;
+    let resized = board.cols != grid_config.cols || board.rows != grid_config.rows;
+
+    if resized || cell_size.is_changed() {
+        for entity in board.squares.drain(..).chain(board.label.take()) {
+            commands.entity(entity).despawn();
+        }
+
+        board.cols = grid_config.cols;
+        board.rows = grid_config.rows;
+
+        // The comparison board continues the main board's layout past its right edge.
+        let geometry = grid_config.geometry(&cell_size);
+        let offset = grid_config.cols + COMPARISON_GAP;
+
+        for row in 0..grid_config.rows {
+            for col in 0..grid_config.cols {
+                let square = commands
+                    .spawn((
+                        ComparisonSquare,
+                        Sprite {
+                            color: theme.dead,
+                            custom_size: Some(Vec2::new(cell_size.w, cell_size.h)),
+                            ..default()
+                        },
+                        Transform::from_translation(
+                            Vec2::from(geometry.cell_to_world(offset + col, row)).extend(0.),
+                        ),
+                    ))
+                    .id();
+
+                board.squares.push(square);
+            }
+        }
+
+        let label = commands
+            .spawn((
+                Text2d::new(board.ruleset.to_string()),
+                TextFont {
+                    font_size: cell_size.h,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+                Transform::from_translation(
+                    Vec2::from(geometry.cell_to_world(offset, -2))
+                        .midpoint(Vec2::from(
+                            geometry.cell_to_world(offset + grid_config.cols - 1, -1),
+                        ))
+                        .extend(0.),
+                ),
+            ))
+            .id();
+
+        board.label = Some(label);
+        board.repaint = true;
+
+        // The new squares only exist once the commands are applied, so they are painted
+        // on the next frame.
+        return;
+    }
+
+    // Taking the edits is not an edit itself, so it must not look like one to the systems
+    // that watch the main board's history for changes.
+    let placement_history = placement_history.bypass_change_detection();
+    let edits = placement_history
+        .edits
+        .as_mut()
+        .map(std::mem::take)
+        .unwrap_or_default();
+    let replaced = std::mem::take(&mut placement_history.replaced);
+
+    let previous = board.cells.clone();
+    let mirror = |board: &mut ComparisonBoard| {
+        board.cells = q_alive_squares
+            .iter()
+            .map(|square| (square.x, square.y))
+            .collect();
+        board.history.clear();
+        board.generation = generation.0;
+    };
+
+    if matches!(game_state.get(), GameState::Placing) || replaced {
+        mirror(board);
+    } else {
+        if generation.0 < board.generation {
+            let rewound = (board.generation - generation.0) as usize;
+
+            if rewound <= board.history.len() {
+                let kept = board.history.len() - rewound;
+                board.cells = board.history.drain(kept..).next().unwrap_or_default();
+                board.generation = generation.0;
+            } else {
+                info!("the comparison board has no earlier generation, copying the main board");
+                mirror(board);
+            }
+        }
+
+        let mut next = HashSet::new();
+
+        while board.generation < generation.0 {
+            life::step_into(
+                &board.cells,
+                board.cols,
+                board.rows,
+                boundary.0,
+                neighborhood.0,
+                &board.ruleset,
+                &mut board.buffers,
+                &mut next,
+            );
+
+            if board.history.len() == HISTORY_LENGTH {
+                board.history.pop_front();
+            }
+            board.history.push_back(std::mem::replace(
+                &mut board.cells,
+                std::mem::take(&mut next),
+            ));
+            board.generation += 1;
+        }
+
+        // The main board was stepped before it was edited this frame, so the comparison
+        // board is too.
+        for (entity, alive) in edits {
+            let Ok(square) = q_squares.get(entity) else {
+                continue;
+            };
+
+            if alive {
+                board.cells.insert((square.x, square.y));
+            } else {
+                board.cells.remove(&(square.x, square.y));
+            }
+        }
+    }
+
+    let repaint_all = board.repaint || theme.is_changed() || cell_shape.is_changed();
+    board.repaint = false;
+
+    if !repaint_all && previous == board.cells {
+        return;
+    }
+
+    let image = match *cell_shape {
+        CellShape::Square => Handle::default(),
+        CellShape::Circle => circle_texture.0.clone(),
+    };
+
+    for y in 0..board.rows {
+        for x in 0..board.cols {
+            let alive = board.cells.contains(&(x, y));
+
+            if !repaint_all && alive == previous.contains(&(x, y)) {
+                continue;
+            }
+
+            let Ok(mut sprite) =
+                q_comparison_sprites.get_mut(board.squares[(y * board.cols + x) as usize])
+            else {
+                continue;
+            };
+
+            sprite.color = if alive { theme.alive } else { theme.dead };
+            sprite.image = image.clone();
+        }
+    }
+}
+
+fn on_edge((x, y): (i32, i32), grid_config: &GridConfig) -> bool {
+    x == 0 || y == 0 || x == grid_config.cols - 1 || y == grid_config.rows - 1
+}
+
+/// Averages colors in linear space, or returns `None` when there are none.
+fn blend_colors<'a>(colors: impl IntoIterator<Item = &'a Color>) -> Option<Color> {
+    let (sum, count) = colors
+        .into_iter()
+        .fold((Vec4::ZERO, 0), |(sum, count), color| {
+            (sum + color.to_linear().to_vec4(), count + 1)
+        });
+
+    (count > 0).then(|| LinearRgba::from_vec4(sum / count as f32).into())
+}
+
+fn age_color(theme: &Theme, age: u32) -> Color {
+    let t = age.min(OLD_AGE) as f32 / OLD_AGE as f32;
+
+    theme.alive.mix(&theme.aged, t)
+}
+
+/// Applies [`RuleChance`] to the generation the rule stepped from `previous` to `next`,
+/// returning the births that did not happen and the survivors that died anyway. `keep`
+/// squares always survive. Cells are rolled for in sorted order so seeded runs repeat.
+#[allow(clippy::type_complexity)]
+fn roll_rule_chance(
+    previous: &HashSet<(i32, i32)>,
+    next: &HashSet<(i32, i32)>,
+    keep: &[(i32, i32)],
+    chance: &RuleChance,
+    rng: &mut StdRng,
+) -> (Vec<(i32, i32)>, Vec<(i32, i32)>) {
+    let mut born: Vec<_> = next.difference(previous).copied().collect();
+    let mut survived: Vec<_> = next
+        .intersection(previous)
+        .copied()
+        .filter(|cell| !keep.contains(cell))
+        .collect();
+    born.sort_unstable();
+    survived.sort_unstable();
+
+    born.retain(|_| !rng.gen_bool(chance.birth));
+    survived.retain(|_| rng.gen_bool(chance.death));
+
+    (born, survived)
+}
+
+/// The state a cell the rule kept alive is left in when [`RuleChance`] kills it.
+fn chance_death_state(automaton: Automaton, ruleset: &Ruleset) -> CellState {
+    if dying_stages(automaton, ruleset) > 0 {
+        CellState::Dying(1)
+    } else {
+        CellState::Dead
+    }
+}
+
+/// How many dying states a square goes through before it is dead.
+fn dying_stages(automaton: Automaton, ruleset: &Ruleset) -> u8 {
+    match automaton {
+        Automaton::Life => ruleset.states.saturating_sub(2),
+        Automaton::BriansBrain => 1,
+    }
+}
+
+/// Fades from the dying color toward the dead one as a square goes through its stages.
+fn dying_color(theme: &Theme, stage: u8, stages: u8) -> Color {
+    theme.dying.mix(
+        &theme.dead,
+        stage.saturating_sub(1) as f32 / stages.max(1) as f32,
+    )
+}
+
+fn trail_color(theme: &Theme, dead_since: u32) -> Color {
+    theme.alive.mix(
+        &theme.dead,
+        (dead_since + 1) as f32 / (TRAIL_LENGTH + 1) as f32,
+    )
+}
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn handle_rendering(
+    render_input: Res<RenderInput>,
+    theme: Res<Theme>,
+    trails: Res<Trails>,
+    (ruleset, automaton): (Res<ActiveRuleset>, Res<ActiveAutomaton>),
+    mut commands: Commands,
+    mut q_squares: Query<(
+        Entity,
+        &mut Sprite,
+        Option<&mut Age>,
+        Option<&mut DeadSince>,
+        Option<&CellColor>,
+        Has<Dying>,
+        Has<Immortal>,
+    )>,
+    mut logic_state: ResMut<LogicState>,
+    mut generation: ResMut<Generation>,
+    (mut paused, mut pause_at): (ResMut<Paused>, ResMut<PauseAtGeneration>),
+    mut died_out: EventWriter<BoardDiedOut>,
+) {
+    let _span = info_span!(
+        "handle_rendering",
+        born = render_input.entities_born.len(),
+        died = render_input.entities_that_died.len(),
+        dying = render_input.entities_dying.len()
+    )
+    .entered();
+    let generations = render_input.generations as u32;
+    let stages = dying_stages(automaton.0, &ruleset.0);
+    let dying_squares: HashSet<Entity> = render_input
+        .entities_dying
+        .iter()
+        .map(|&(entity, _)| entity)
+        .collect();
+    let mut faded_squares = Vec::new();
+
+    for (entity, mut sprite, age, dead_since, cell_color, is_dying, is_immortal) in
+        q_squares.iter_mut()
+    {
+        if is_dying && !dying_squares.contains(&entity) {
+            sprite.color = theme.dead;
+            faded_squares.push(entity);
+        }
+
+        if is_immortal {
+            sprite.color = theme.immortal;
+        } else if let Some(mut age) = age {
+            age.0 = age.0.saturating_add(generations);
+            sprite.color = cell_color.map_or_else(|| age_color(&theme, age.0), |color| color.0);
+        } else if let Some(mut dead_since) = dead_since {
+            dead_since.0 = dead_since.0.saturating_add(generations);
+
+            if dead_since.0 >= TRAIL_LENGTH {
+                sprite.color = theme.dead;
+                faded_squares.push(entity);
+            } else {
+                sprite.color = trail_color(&theme, dead_since.0);
+            }
+        }
+    }
+
+    for dead_square in render_input.entities_that_died.iter() {
+        match q_squares.get_mut(*dead_square) {
+            Ok((_, mut sprite, ..)) if dying_squares.contains(dead_square) => {
+                sprite.color = theme.dying
+            }
+            Ok((_, mut sprite, ..)) if trails.0 => sprite.color = trail_color(&theme, 0),
+            Ok((_, mut sprite, ..)) => sprite.color = theme.dead,
+            Err(_) => warn!("square entity {dead_square} has no sprite"),
+        }
+    }
+
+    let dead_squares = render_input.entities_that_died.clone();
+    let born_squares = render_input.entities_born.clone();
+    commands.queue(move |world: &mut World| {
+        for dead_square in dead_squares {
+            if let Ok(mut entity) = world.get_entity_mut(dead_square) {
+                entity.remove::<(Alive, Age, CellColor)>();
+            }
+        }
+
+        for square in born_squares.into_iter().chain(faded_squares) {
+            if let Ok(mut entity) = world.get_entity_mut(square) {
+                entity.remove::<(DeadSince, Dying)>();
+            }
+        }
+    });
+
+    if trails.0 {
+        commands.insert_batch(
+            render_input
+                .entities_that_died
+                .iter()
+                .filter(|dead_square| !dying_squares.contains(dead_square))
+                .map(|&dead_square| (dead_square, DeadSince(0)))
+                .collect::<Vec<_>>(),
+        );
+    }
+
+    for square_born in render_input.entities_born.iter() {
+        match q_squares.get_mut(*square_born) {
+            Ok((_, mut sprite, ..)) => sprite.color = age_color(&theme, 0),
+            Err(_) => warn!("square entity {square_born} has no sprite"),
+        }
+    }
+
+    commands.insert_batch(
+        render_input
+            .entities_born
+            .iter()
+            .map(|&square_born| (square_born, (Alive, Age(0))))
+            .collect::<Vec<_>>(),
+    );
+
+    for &(square, color) in render_input.entities_recolored.iter() {
+        let Ok((_, mut sprite, age, ..)) = q_squares.get_mut(square) else {
+            continue;
+        };
+
+        match color {
+            Some(color) => {
+                sprite.color = color;
+                commands.entity(square).insert(CellColor(color));
+            }
+            None => {
+                sprite.color = age_color(&theme, age.map_or(0, |age| age.0));
+                commands.entity(square).remove::<CellColor>();
+            }
+        }
+    }
+
+    for &(dying_square, stage) in render_input.entities_dying.iter() {
+        if let Ok((_, mut sprite, ..)) = q_squares.get_mut(dying_square) {
+            sprite.color = dying_color(&theme, stage, stages);
+        }
+    }
+
+    commands.insert_batch(
+        render_input
+            .entities_dying
+            .iter()
+            .map(|&(dying_square, stage)| (dying_square, Dying(stage)))
+            .collect::<Vec<_>>(),
+    );
+
+    generation.0 += render_input.generations;
+    if render_input.died_out {
+        died_out.send(BoardDiedOut);
+    }
+    pause_at_target(generation.0, &mut paused, &mut pause_at);
+    *logic_state = LogicState::CalculationNeeded;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<CliArgs, String> {
+        parse_args(
+            args.iter().map(|arg| arg.to_string()),
+            &Preferences::default(),
+        )
+    }
+
+    #[test]
+    fn zoom_presets_fit_the_zoom_limits() {
+        for scale_factor in [1., 2.] {
+            let scales: Vec<f32> = ZoomPresets::default()
+                .0
+                .iter()
+                .filter_map(|&(_, preset)| preset.scale(scale_factor))
+                .collect();
+
+            assert_eq!(scales.len(), 3);
+            assert!(
+                scales
+                    .iter()
+                    .all(|scale| (MIN_ZOOM..=MAX_ZOOM).contains(scale)),
+                "{scales:?}"
+            );
+            assert!(
+                scales.windows(2).all(|pair| pair[0] > pair[1]),
+                "{scales:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn texture_limits_only_apply_to_windows() {
+        let wide = ["--cols", "9000", "--rows", "100"];
+
+        assert!(parse(&wide).is_err());
+        assert!(parse(&[&wide[..], &["--run-for", "1"]].concat()).is_ok());
+        assert!(parse(&[&wide[..], &["--run-for", "1", "--sparse"]].concat()).is_ok());
+        assert!(parse(&[&wide[..], &["--bench", "1"]].concat()).is_ok());
+        assert!(parse(&[&wide[..], &["--tui"]].concat()).is_ok());
+    }
+
+    #[test]
+    fn boards_are_limited_in_squares() {
+        assert!(parse(&["--cols", "8192", "--rows", "8192"]).is_ok());
+        assert!(parse(&["--cols", "8192", "--rows", "8193"]).is_err());
+        assert!(parse(&["--cols", "46341", "--rows", "46341", "--tui"]).is_err());
+        assert!(parse(&["--cols", "2147483647", "--rows", "2"]).is_err());
+        assert!(parse(&["--cols", "0"]).is_err());
+    }
+
+    #[test]
+    fn tick_lengths_are_clamped_and_must_be_finite() {
+        assert_eq!(parse(&["--tick", "0.5"]).unwrap().tick_seconds, 0.5);
+        assert_eq!(
+            parse(&["--tick", "10"]).unwrap().tick_seconds,
+            MAX_GAME_TICK_SECOND
+        );
+
+        for tick in ["nan", "inf", "-inf"] {
+            assert!(parse(&["--tick", tick]).is_err(), "{tick}");
+        }
+    }
+
+    #[test]
+    fn saved_ticks_must_be_finite() {
+        let preferences = Preferences {
+            tick_seconds: f32::NAN,
+            ..Preferences::default()
+        };
+
+        assert!(parse_args(std::iter::empty(), &preferences).is_err());
+    }
+
+    #[test]
+    fn the_bitset_board_only_runs_two_state_life() {
+        assert_eq!(
+            parse(&["--board", "bitset"]).unwrap().board,
+            BoardKind::Bitset
+        );
+
+        for args in [
+            &["--board", "bitset", "--rule", "B2/S345/C4"][..],
+            &["--board", "bitset", "--automaton", "brians-brain"],
+            &["--board", "bitset", "--sparse"],
+            &["--board", "bitset", "--render", "texture"],
+            &["--board", "packed"],
+        ] {
+            assert!(parse(args).is_err(), "{args:?}");
+        }
+    }
+
+    #[test]
+    fn both_boards_step_the_same_generations() {
+        let r_pentomino = [(11, 10), (12, 10), (10, 11), (11, 11), (11, 12)];
+        let ruleset = Ruleset::default();
+        let mut dense = BoardBuffers::new(BoardKind::Dense);
+        let mut bitset = BoardBuffers::new(BoardKind::Bitset);
+
+        for board in [&mut dense, &mut bitset] {
+            board
+                .board_mut()
+                .load_cells(80, 40, &mut r_pentomino.into_iter());
+            board.set(0, 0, CellState::Dying(1));
+        }
+
+        for _ in 0..100 {
+            for board in [&mut dense, &mut bitset] {
+                board.step(
+                    Automaton::Life,
+                    BoundaryCondition::Wrap,
+                    Neighborhood::Moore,
+                    &ruleset,
+                );
+            }
+
+            assert_eq!(
+                dense.board().alive_cells().collect::<Vec<_>>(),
+                bitset.board().alive_cells().collect::<Vec<_>>()
+            );
+        }
+        assert!(bitset.dying_cells().next().is_none());
+    }
+
+    #[test]
+    fn rule_chance_defaults_to_following_the_rule() {
+        assert!(RuleChance::default().is_exact());
+        assert!(
+            !RuleChance {
+                birth: 0.5,
+                ..RuleChance::default()
+            }
+            .is_exact()
+        );
+    }
+
+    #[test]
+    fn no_cells_are_born_at_a_birth_chance_of_zero() {
+        let previous = HashSet::from([(0, 1), (1, 1), (2, 1)]);
+        let next = HashSet::from([(1, 0), (1, 1), (1, 2)]);
+        let chance = RuleChance {
+            birth: 0.,
+            death: 0.,
+        };
+        let mut rng = StdRng::seed_from_u64(7);
+
+        let (unborn, killed) = roll_rule_chance(&previous, &next, &[], &chance, &mut rng);
+
+        assert_eq!(unborn, [(1, 0), (1, 2)]);
+        assert!(killed.is_empty());
+
+        let certain_death = RuleChance {
+            birth: 1.,
+            death: 1.,
+        };
+        let (unborn, killed) =
+            roll_rule_chance(&previous, &next, &[(1, 1)], &certain_death, &mut rng);
+
+        assert!(unborn.is_empty());
+        assert!(killed.is_empty(), "kept squares always survive");
+    }
+
+    #[test]
+    fn loaded_rules_must_suit_the_automaton_and_neighborhood() {
+        let rule = |rule| life::parse_rule(rule).unwrap();
+        let life = Automaton::Life;
+
+        assert!(check_loaded_rule(&rule("B36/S23"), Neighborhood::Moore, life, None).is_ok());
+        assert!(
+            check_loaded_rule(&rule("B5678/S1"), Neighborhood::VonNeumann, life, None).is_err()
+        );
+        assert!(
+            check_loaded_rule(
+                &rule("B36/S23"),
+                Neighborhood::Moore,
+                Automaton::BriansBrain,
+                None
+            )
+            .is_err()
+        );
+        assert!(
+            check_loaded_rule(
+                &rule("B2/S/C3"),
+                Neighborhood::Moore,
+                life,
+                Some("--sparse")
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn two_state_modes_ignore_a_pattern_s_generations_rule() {
+        let mut ruleset = Ruleset::default();
+
+        apply_pattern_rule(
+            life::parse_rule("B2/S/C3").ok(),
+            Neighborhood::Moore,
+            Automaton::Life,
+            Some("--sparse"),
+            &mut ruleset,
+        );
+        assert_eq!(ruleset.to_string(), "B3/S23");
+
+        apply_pattern_rule(
+            life::parse_rule("B2/S/C3").ok(),
+            Neighborhood::Moore,
+            Automaton::Life,
+            None,
+            &mut ruleset,
+        );
+        assert_eq!(ruleset.states, 3);
+
+        let sparse = parse(&["--sparse"]).unwrap();
+        assert_eq!(two_state_flag(&sparse), Some("--sparse"));
+        assert_eq!(two_state_flag(&parse(&[]).unwrap()), None);
+    }
+
+    /// A headless app holding everything the logic and render one-shot systems use, with
+    /// `live` squares alive on a `cols` by `rows` board.
+    fn game_logic_app(cols: i32, rows: i32, live: &[(i32, i32)]) -> App {
+        let mut app = App::new();
+        app.add_event::<BoardDiedOut>()
+            .insert_resource(GridConfig { cols, rows })
+            .insert_resource(CellSize { w: 1., h: 1. })
+            .insert_resource(ActiveBoundary(BoundaryCondition::Dead))
+            .insert_resource(ActiveRuleset(Ruleset::default()))
+            .insert_resource(ActiveNeighborhood(Neighborhood::Moore))
+            .insert_resource(ActiveAutomaton(Automaton::Life))
+            .insert_resource(ExpandGrid(false))
+            .insert_resource(THEMES[0])
+            .insert_resource(Trails(false))
+            .insert_resource(LogicState::CalculationNeeded)
+            .insert_resource(RenderInput {
+                entities_that_died: Vec::new(),
+                entities_born: Vec::new(),
+                entities_dying: Vec::new(),
+                entities_recolored: Vec::new(),
+                generations: 0,
+                died_out: false,
+            })
+            .insert_resource(BoardBuffers::new(BoardKind::Dense))
+            .insert_resource(SparseBackend(false))
+            .insert_resource(AutoStopOnStable(false))
+            .insert_resource(Paused(false))
+            .insert_resource(RuleChance::default())
+            .insert_resource(SimulationRng(StdRng::seed_from_u64(0)))
+            .insert_resource(StepsPerTick(1))
+            .insert_resource(Generation(0))
+            .init_resource::<History>()
+            .init_resource::<SparseBuffers>()
+            .init_resource::<Stable>()
+            .init_resource::<SettleStart>()
+            .init_resource::<SingleStep>()
+            .init_resource::<PauseAtGeneration>()
+            .init_resource::<LogicWatchdog>()
+            .init_resource::<OneShotSystems>();
+
+        let world = app.world_mut();
+        let mut map = Vec::new();
+        for y in 0..rows {
+            for x in 0..cols {
+                let mut square = world.spawn((Square { x, y }, Sprite::default()));
+                if live.contains(&(x, y)) {
+                    square.insert((Alive, Age(0)));
+                }
+                map.push(SquareIdentifier {
+                    entity: square.id(),
+                });
+            }
+        }
+        world.insert_resource(SquareMap { cols, rows, map });
+
+        app
+    }
+
+    fn live_squares(app: &mut App) -> HashSet<(i32, i32)> {
+        app.world_mut()
+            .query_filtered::<&Square, With<Alive>>()
+            .iter(app.world())
+            .map(|square| (square.x, square.y))
+            .collect()
+    }
+
+    #[test]
+    fn one_logic_and_render_cycle_advances_one_generation() {
+        let mut app = game_logic_app(5, 5, &[(1, 2), (2, 2), (3, 2)]);
+        let systems = app.world().resource::<OneShotSystems>();
+        let (game_logic, render) = (systems.game_logic, systems.render);
+
+        app.world_mut().run_system(game_logic).unwrap();
+        assert!(matches!(
+            *app.world().resource::<LogicState>(),
+            LogicState::Calculated
+        ));
+        assert_eq!(app.world().resource::<Generation>().0, 0);
+
+        app.world_mut().run_system(render).unwrap();
+        assert!(matches!(
+            *app.world().resource::<LogicState>(),
+            LogicState::CalculationNeeded
+        ));
+        assert_eq!(app.world().resource::<Generation>().0, 1);
+        assert_eq!(
+            live_squares(&mut app),
+            HashSet::from([(2, 1), (2, 2), (2, 3)])
+        );
+    }
+
+    #[test]
+    fn a_stalled_calculation_is_queued_again() {
+        let mut app = game_logic_app(3, 3, &[]);
+        app.add_systems(Update, watch_logic_state);
+        *app.world_mut().resource_mut::<LogicState>() = LogicState::CalculatingCurrently;
+
+        for _ in 0..LOGIC_STALL_FRAMES {
+            app.update();
+            assert!(matches!(
+                *app.world().resource::<LogicState>(),
+                LogicState::CalculatingCurrently
+            ));
+        }
+
+        app.update();
+        assert!(matches!(
+            *app.world().resource::<LogicState>(),
+            LogicState::CalculationNeeded
+        ));
+        assert_eq!(app.world().resource::<LogicWatchdog>().0, 0);
+    }
+
+    #[test]
+    fn the_comparison_board_keeps_its_own_history_and_gets_the_same_edits() {
+        let blinker = [(1, 2), (2, 2), (3, 2)];
+        let mut app = game_logic_app(6, 6, &blinker);
+        app.insert_resource(State::new(GameState::Ongoing))
+            .insert_resource(CellShape::Square)
+            .insert_resource(CircleTexture(Handle::default()))
+            .insert_resource(PlacementHistory {
+                edits: Some(Vec::new()),
+                replaced: true,
+                ..default()
+            })
+            .insert_resource(ComparisonBoard {
+                ruleset: life::parse_rule("B2/S").unwrap(),
+                cols: 0,
+                rows: 0,
+                cells: HashSet::new(),
+                generation: 0,
+                history: VecDeque::new(),
+                squares: Vec::new(),
+                label: None,
+                repaint: false,
+                buffers: life::StepBuffers::default(),
+            });
+        let world = app.world_mut();
+        let systems = world.resource::<OneShotSystems>();
+        let (game_logic, render) = (systems.game_logic, systems.render);
+        let sync = world.register_system(sync_comparison_board);
+        let comparison = |world: &World| world.resource::<ComparisonBoard>().cells.clone();
+
+        // The first run only spawns the squares, the second copies the main board.
+        world.run_system(sync).unwrap();
+        world.run_system(sync).unwrap();
+        assert_eq!(comparison(world), HashSet::from(blinker));
+
+        let seeds = |cells: &HashSet<(i32, i32)>| {
+            let mut next = HashSet::new();
+            life::step_into(
+                cells,
+                6,
+                6,
+                BoundaryCondition::Dead,
+                Neighborhood::Moore,
+                &life::parse_rule("B2/S").unwrap(),
+                &mut life::StepBuffers::default(),
+                &mut next,
+            );
+            next
+        };
+        let first = seeds(&HashSet::from(blinker));
+
+        for _ in 0..2 {
+            world.run_system(game_logic).unwrap();
+            world.run_system(render).unwrap();
+            world.run_system(sync).unwrap();
+        }
+        assert_eq!(comparison(world), seeds(&first));
+
+        // A rewind goes back through the comparison board's own generations.
+        world.resource_mut::<Generation>().0 = 1;
+        world.run_system(sync).unwrap();
+        assert_eq!(comparison(world), first);
+        assert_ne!(comparison(world), live_squares(&mut app));
+
+        let world = app.world_mut();
+        let corner = world.resource::<SquareMap>().get(0, 0).unwrap().entity;
+        world
+            .resource_mut::<PlacementHistory>()
+            .record(corner, true);
+        world.run_system(sync).unwrap();
+        assert!(comparison(world).contains(&(0, 0)));
+    }
+
+    #[test]
+    fn loaded_credits_are_kept_unless_a_flag_overrides_them() {
+        let mut app = game_logic_app(5, 5, &[]);
+        let cli_args = parse(&["--pattern-author", "Richard K. Guy"]).unwrap();
+        app.insert_resource(LoadSource(Some(PatternSource::Stdin(
+            r#"{"name": "Glider", "author": "unknown", "width": 3, "height": 3,
+                "cells": [[1, 0], [2, 1], [0, 2], [1, 2], [2, 2]]}"#
+                .to_string(),
+        ))))
+        .insert_resource(PatternCredits {
+            name_flag: cli_args.pattern_name,
+            author_flag: cli_args.pattern_author,
+            ..default()
+        })
+        .insert_resource(TwoStateOnly(None))
+        .init_resource::<PlacementHistory>()
+        .init_resource::<Time<Fixed>>();
+        let world = app.world_mut();
+        let load_pattern = world.resource::<OneShotSystems>().load_pattern;
+
+        world.run_system(load_pattern).unwrap();
+
+        let credits = world.resource::<PatternCredits>();
+        assert_eq!(credits.name().as_deref(), Some("Glider"));
+        assert_eq!(credits.author().as_deref(), Some("Richard K. Guy"));
+    }
+
+    #[test]
+    fn speed_slider_spans_the_tick_limits() {
+        assert_eq!(speed_slider_tick(0.), MAX_GAME_TICK_SECOND);
+        assert!((speed_slider_tick(1.) - MIN_GAME_TICK_SECOND).abs() < 1e-6);
+        assert_eq!(speed_slider_position(MAX_GAME_TICK_SECOND * 2.), 0.);
+        assert_eq!(speed_slider_position(MIN_GAME_TICK_SECOND / 2.), 1.);
+
+        for position in [0.1, 0.5, 0.9] {
+            assert!((speed_slider_position(speed_slider_tick(position)) - position).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn pressed_sliders_follow_the_cursor() {
+        let mut app = App::new();
+        app.init_resource::<Time<Fixed>>()
+            .insert_resource(FillDensity(DEFAULT_FILL_DENSITY));
+        let world = app.world_mut();
+        let cursor_at = |x| RelativeCursorPosition {
+            normalized: Some(Vec2::new(x, 0.5)),
+            ..default()
+        };
+        world.spawn((PanelSlider::Speed, Interaction::Pressed, cursor_at(1.5)));
+        let density = world
+            .spawn((PanelSlider::Density, Interaction::Hovered, cursor_at(0.42)))
+            .id();
+        let sliders = world.register_system(handle_panel_sliders);
+
+        world.run_system(sliders).unwrap();
+        assert_eq!(
+            world.resource::<Time<Fixed>>().timestep(),
+            Duration::from_secs_f32(MIN_GAME_TICK_SECOND)
+        );
+        assert_eq!(world.resource::<FillDensity>().0, DEFAULT_FILL_DENSITY);
+
+        world.entity_mut(density).insert(Interaction::Pressed);
+        world.run_system(sliders).unwrap();
+        assert!((world.resource::<FillDensity>().0 - 0.4).abs() < 1e-6);
+    }
+
+    #[test]
+    fn rule_menu_picks_presets_that_fit_the_neighborhood() {
+        let mut app = game_logic_app(1, 1, &[]);
+        app.insert_resource(State::new(GameState::Placing));
+        let world = app.world_mut();
+        let menu = world
+            .spawn((
+                RuleMenu,
+                Node {
+                    display: Display::Flex,
+                    ..default()
+                },
+            ))
+            .id();
+        let buttons = world.register_system(handle_panel_buttons);
+        let pick = |world: &mut World, index| {
+            world.spawn((
+                Button,
+                Interaction::Pressed,
+                PanelAction::PickRule(index),
+                BackgroundColor(PANEL_BUTTON_COLOR),
+            ));
+            world.run_system(buttons).unwrap();
+            world.resource::<ActiveRuleset>().0.to_string()
+        };
+
+        assert_eq!(pick(world, 1), RULE_PRESETS[1]);
+        assert_eq!(world.get::<Node>(menu).unwrap().display, Display::None);
+
+        world.resource_mut::<ActiveNeighborhood>().0 = Neighborhood::VonNeumann;
+        assert_eq!(pick(world, 3), RULE_PRESETS[1]);
+    }
 }
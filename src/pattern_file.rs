@@ -0,0 +1,186 @@
+//! The native pattern format: JSON holding the live cells along with who made the
+//! pattern and how it is meant to be run, for curated pattern libraries.
+
+use crate::rle::{MAX_PATTERN_SIZE, Pattern};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug, Default)]
+pub struct PatternFile {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+    /// The rule in B/S notation, see [`crate::life::parse_rule`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rule: Option<String>,
+    /// Seconds per generation the pattern looks best at.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tick_seconds: Option<f32>,
+    pub width: i32,
+    pub height: i32,
+    pub cells: Vec<(i32, i32)>,
+}
+
+impl PatternFile {
+    /// A pattern file holding `cells` cropped to their bounding box, with no metadata.
+    pub fn from_cells(cells: &HashSet<(i32, i32)>) -> Self {
+        let bounds = cells.iter().fold(None, |bounds, &(x, y)| {
+            let ((min_x, min_y), (max_x, max_y)) = bounds.unwrap_or(((x, y), (x, y)));
+
+            Some(((min_x.min(x), min_y.min(y)), (max_x.max(x), max_y.max(y))))
+        });
+        let Some(((min_x, min_y), (max_x, max_y))) = bounds else {
+            return PatternFile::default();
+        };
+
+        let mut cropped: Vec<_> = cells.iter().map(|&(x, y)| (x - min_x, y - min_y)).collect();
+        cropped.sort_unstable_by_key(|&(x, y)| (y, x));
+
+        PatternFile {
+            width: max_x - min_x + 1,
+            height: max_y - min_y + 1,
+            cells: cropped,
+            ..PatternFile::default()
+        }
+    }
+
+    pub fn pattern(&self) -> Pattern {
+        Pattern {
+            width: self.width,
+            height: self.height,
+            cells: self.cells.clone(),
+        }
+    }
+}
+
+impl From<Pattern> for PatternFile {
+    /// A pattern file holding an RLE or plaintext pattern, with no metadata.
+    fn from(pattern: Pattern) -> Self {
+        PatternFile {
+            width: pattern.width,
+            height: pattern.height,
+            cells: pattern.cells,
+            ..PatternFile::default()
+        }
+    }
+}
+
+/// Encodes a pattern file as pretty-printed JSON.
+///
+/// ```
+/// use std::collections::HashSet;
+/// use game_of_life::pattern_file::{self, PatternFile};
+///
+/// let blinker = HashSet::from([(4, 2), (5, 2), (6, 2)]);
+/// let file = PatternFile {
+///     name: Some("Blinker".to_string()),
+///     rule: Some("B3/S23".to_string()),
+///     ..PatternFile::from_cells(&blinker)
+/// };
+///
+/// let decoded = pattern_file::decode(&pattern_file::encode(&file).unwrap()).unwrap();
+/// assert_eq!(decoded, file);
+/// assert_eq!((decoded.width, decoded.height), (3, 1));
+/// assert_eq!(decoded.cells, [(0, 0), (1, 0), (2, 0)]);
+/// ```
+pub fn encode(file: &PatternFile) -> Result<String, String> {
+    serde_json::to_string_pretty(file).map_err(|err| err.to_string())
+}
+
+/// Decodes a pattern file, checking that its size is one a board can hold and that its
+/// cells fit in it.
+///
+/// ```
+/// use game_of_life::pattern_file;
+///
+/// let glider = pattern_file::decode(
+///     r#"{"name": "Glider", "width": 3, "height": 3,
+///         "cells": [[1, 0], [2, 1], [0, 2], [1, 2], [2, 2]]}"#,
+/// )
+/// .unwrap();
+/// assert_eq!(glider.name.as_deref(), Some("Glider"));
+/// assert_eq!(glider.rule, None);
+///
+/// assert!(pattern_file::decode(r#"{"width": 1, "height": 1, "cells": [[1, 0]]}"#).is_err());
+/// ```
+pub fn decode(input: &str) -> Result<PatternFile, String> {
+    let file: PatternFile = serde_json::from_str(input).map_err(|err| err.to_string())?;
+
+    // Patterns are centered by halving the board size minus theirs, which a negative or
+    // huge size would overflow.
+    let min_size = if file.cells.is_empty() { 0 } else { 1 };
+    if ![file.width, file.height]
+        .iter()
+        .all(|size| (min_size..=MAX_PATTERN_SIZE).contains(size))
+    {
+        return Err(format!(
+            "pattern size {}x{} must be between {min_size} and {MAX_PATTERN_SIZE} on each side",
+            file.width, file.height
+        ));
+    }
+
+    if let Some(&(x, y)) = file
+        .cells
+        .iter()
+        .find(|&&(x, y)| x < 0 || y < 0 || x >= file.width || y >= file.height)
+    {
+        return Err(format!(
+            "cell ({x}, {y}) is outside the pattern size {}x{}",
+            file.width, file.height
+        ));
+    }
+
+    if let Some(seconds) = file
+        .tick_seconds
+        .filter(|seconds| !seconds.is_finite() || *seconds <= 0.)
+    {
+        return Err(format!("`tick_seconds` must be positive, got {seconds}"));
+    }
+
+    Ok(file)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn crops_cells_to_their_bounds() {
+        let file = PatternFile::from_cells(&HashSet::from([(-2, 5), (1, 3), (0, 4)]));
+
+        assert_eq!((file.width, file.height), (4, 3));
+        assert_eq!(file.cells, [(3, 0), (2, 1), (0, 2)]);
+        assert_eq!(
+            PatternFile::from_cells(&HashSet::new()),
+            PatternFile::default()
+        );
+    }
+
+    #[test]
+    fn rejects_sizes_a_board_cannot_hold() {
+        assert!(decode(r#"{"width": 0, "height": 0, "cells": []}"#).is_ok());
+        assert!(decode(r#"{"width": 0, "height": 1, "cells": [[0, 0]]}"#).is_err());
+        assert!(decode(r#"{"width": -2147483648, "height": 1, "cells": []}"#).is_err());
+        assert!(
+            decode(&format!(
+                r#"{{"width": {}, "height": 1, "cells": []}}"#,
+                MAX_PATTERN_SIZE + 1
+            ))
+            .is_err()
+        );
+        assert!(
+            decode(&format!(
+                r#"{{"width": {MAX_PATTERN_SIZE}, "height": 1, "cells": [[0, 0]]}}"#
+            ))
+            .is_ok()
+        );
+    }
+
+    #[test]
+    fn rejects_cells_outside_the_size_and_bad_ticks() {
+        assert!(decode(r#"{"width": 2, "height": 2, "cells": [[0, 2]]}"#).is_err());
+        assert!(decode(r#"{"width": 2, "height": 2, "cells": [[-1, 0]]}"#).is_err());
+        assert!(decode(r#"{"width": 1, "height": 1, "cells": [], "tick_seconds": 0}"#).is_err());
+    }
+}
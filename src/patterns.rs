@@ -0,0 +1,217 @@
+pub struct Pattern {
+    pub name: &'static str,
+    pub cells: &'static [(i32, i32)],
+}
+
+impl Pattern {
+    pub fn size(&self) -> (i32, i32) {
+        let width = self.cells.iter().map(|&(x, _)| x + 1).max().unwrap_or(0);
+        let height = self.cells.iter().map(|&(_, y)| y + 1).max().unwrap_or(0);
+
+        (width, height)
+    }
+}
+
+// Rotations and mirrors are accumulated into a single integer matrix, so each key
+// press acts on the pattern as currently shown rather than on the original.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Orientation {
+    xx: i32,
+    xy: i32,
+    yx: i32,
+    yy: i32,
+}
+
+impl Default for Orientation {
+    fn default() -> Self {
+        Orientation {
+            xx: 1,
+            xy: 0,
+            yx: 0,
+            yy: 1,
+        }
+    }
+}
+
+impl Orientation {
+    fn then(self, xx: i32, xy: i32, yx: i32, yy: i32) -> Self {
+        Orientation {
+            xx: xx * self.xx + xy * self.yx,
+            xy: xx * self.xy + xy * self.yy,
+            yx: yx * self.xx + yy * self.yx,
+            yy: yx * self.xy + yy * self.yy,
+        }
+    }
+
+    pub fn rotate_clockwise(&mut self) {
+        *self = self.then(0, -1, 1, 0);
+    }
+
+    pub fn rotate_counterclockwise(&mut self) {
+        *self = self.then(0, 1, -1, 0);
+    }
+
+    pub fn mirror_horizontally(&mut self) {
+        *self = self.then(-1, 0, 0, 1);
+    }
+
+    pub fn mirror_vertically(&mut self) {
+        *self = self.then(1, 0, 0, -1);
+    }
+
+    fn map(&self, (x, y): (i32, i32)) -> (i32, i32) {
+        (self.xx * x + self.xy * y, self.yx * x + self.yy * y)
+    }
+
+    pub fn size(&self, width: i32, height: i32) -> (i32, i32) {
+        if self.xx == 0 {
+            (height, width)
+        } else {
+            (width, height)
+        }
+    }
+
+    pub fn apply(&self, offset: (i32, i32), width: i32, height: i32) -> (i32, i32) {
+        let corners = [
+            (0, 0),
+            (width - 1, 0),
+            (0, height - 1),
+            (width - 1, height - 1),
+        ]
+        .map(|corner| self.map(corner));
+        let min_x = corners.iter().map(|&(x, _)| x).min().unwrap_or(0);
+        let min_y = corners.iter().map(|&(_, y)| y).min().unwrap_or(0);
+        let (x, y) = self.map(offset);
+
+        (x - min_x, y - min_y)
+    }
+}
+
+pub const GLIDER: Pattern = Pattern {
+    name: "glider",
+    cells: &[(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)],
+};
+
+pub const BLINKER: Pattern = Pattern {
+    name: "blinker",
+    cells: &[(0, 0), (1, 0), (2, 0)],
+};
+
+pub const LIGHTWEIGHT_SPACESHIP: Pattern = Pattern {
+    name: "lwss",
+    cells: &[
+        (1, 0),
+        (4, 0),
+        (0, 1),
+        (0, 2),
+        (4, 2),
+        (0, 3),
+        (1, 3),
+        (2, 3),
+        (3, 3),
+    ],
+};
+
+pub const PULSAR: Pattern = Pattern {
+    name: "pulsar",
+    cells: &[
+        (2, 0),
+        (3, 0),
+        (4, 0),
+        (8, 0),
+        (9, 0),
+        (10, 0),
+        (0, 2),
+        (5, 2),
+        (7, 2),
+        (12, 2),
+        (0, 3),
+        (5, 3),
+        (7, 3),
+        (12, 3),
+        (0, 4),
+        (5, 4),
+        (7, 4),
+        (12, 4),
+        (2, 5),
+        (3, 5),
+        (4, 5),
+        (8, 5),
+        (9, 5),
+        (10, 5),
+        (2, 7),
+        (3, 7),
+        (4, 7),
+        (8, 7),
+        (9, 7),
+        (10, 7),
+        (0, 8),
+        (5, 8),
+        (7, 8),
+        (12, 8),
+        (0, 9),
+        (5, 9),
+        (7, 9),
+        (12, 9),
+        (0, 10),
+        (5, 10),
+        (7, 10),
+        (12, 10),
+        (2, 12),
+        (3, 12),
+        (4, 12),
+        (8, 12),
+        (9, 12),
+        (10, 12),
+    ],
+};
+
+pub const GOSPER_GLIDER_GUN: Pattern = Pattern {
+    name: "gosper-gun",
+    cells: &[
+        (24, 0),
+        (22, 1),
+        (24, 1),
+        (12, 2),
+        (13, 2),
+        (20, 2),
+        (21, 2),
+        (34, 2),
+        (35, 2),
+        (11, 3),
+        (15, 3),
+        (20, 3),
+        (21, 3),
+        (34, 3),
+        (35, 3),
+        (0, 4),
+        (1, 4),
+        (10, 4),
+        (16, 4),
+        (20, 4),
+        (21, 4),
+        (0, 5),
+        (1, 5),
+        (10, 5),
+        (14, 5),
+        (16, 5),
+        (17, 5),
+        (22, 5),
+        (24, 5),
+        (10, 6),
+        (16, 6),
+        (24, 6),
+        (11, 7),
+        (15, 7),
+        (12, 8),
+        (13, 8),
+    ],
+};
+
+pub const PATTERNS: [Pattern; 5] = [
+    GLIDER,
+    BLINKER,
+    LIGHTWEIGHT_SPACESHIP,
+    PULSAR,
+    GOSPER_GLIDER_GUN,
+];
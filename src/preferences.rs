@@ -0,0 +1,72 @@
+use bevy::prelude::Resource;
+use serde::{Deserialize, Serialize, Serializer};
+
+/// Settings kept between runs. Values are stored the way they are written on the
+/// command line, so they are checked by the same parsing as the flags that override them.
+/// Keys the file leaves out keep their [`Default`] value.
+#[derive(Resource, Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Preferences {
+    #[serde(serialize_with = "shortest_decimal")]
+    pub tick_seconds: f32,
+    pub theme: String,
+    pub boundary: String,
+    pub rule: String,
+}
+
+/// Going through the shortest decimal form keeps `0.1` from being written as
+/// `0.10000000149011612`.
+fn shortest_decimal<S: Serializer>(seconds: &f32, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_f64(seconds.to_string().parse().unwrap_or_default())
+}
+
+pub fn encode(preferences: &Preferences) -> Result<String, String> {
+    toml_edit::ser::to_string(preferences).map_err(|err| err.to_string())
+}
+
+pub fn decode(input: &str) -> Result<Preferences, String> {
+    let preferences: Preferences = toml_edit::de::from_str(input).map_err(|err| err.to_string())?;
+
+    if !preferences.tick_seconds.is_finite() {
+        return Err(format!(
+            "`tick_seconds` must be a finite number, got {}",
+            preferences.tick_seconds
+        ));
+    }
+
+    Ok(preferences)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_through_toml() {
+        let preferences = Preferences {
+            tick_seconds: 0.1,
+            ..Preferences::default()
+        };
+        let encoded = encode(&preferences).unwrap();
+
+        assert!(encoded.contains("tick_seconds = 0.1\n"), "{encoded}");
+        assert_eq!(decode(&encoded), Ok(preferences));
+    }
+
+    #[test]
+    fn missing_keys_keep_their_defaults() {
+        let preferences = decode("rule = \"B36/S23\"\ntick_seconds = 1\n").unwrap();
+
+        assert_eq!(preferences.rule, "B36/S23");
+        assert_eq!(preferences.tick_seconds, 1.);
+        assert_eq!(preferences.theme, Preferences::default().theme);
+    }
+
+    #[test]
+    fn rejects_values_of_the_wrong_type_and_non_finite_ticks() {
+        assert!(decode("theme = 3").is_err());
+        assert!(decode("tick_seconds = \"fast\"").is_err());
+        assert!(decode("tick_seconds = nan").is_err());
+        assert!(decode("tick_seconds = inf").is_err());
+    }
+}
@@ -0,0 +1,282 @@
+use std::collections::HashSet;
+
+const MAX_LINE_LENGTH: usize = 70;
+
+/// The largest width, height or run count a pattern may have. This is far beyond any
+/// grid the app can show, and it keeps the arithmetic on positions clear of overflow.
+pub const MAX_PATTERN_SIZE: i32 = 1 << 16;
+
+/// Encodes live cells as an RLE pattern, cropped to their bounding box.
+///
+/// ```
+/// use std::collections::HashSet;
+/// use game_of_life::rle;
+///
+/// let blinker = HashSet::from([(0, 0), (1, 0), (2, 0)]);
+/// assert_eq!(rle::encode(&blinker, "B3/S23"), "x = 3, y = 1, rule = B3/S23\n3o!\n");
+/// ```
+pub fn encode(cells: &HashSet<(i32, i32)>, rule: &str) -> String {
+    let Some(min_x) = cells.iter().map(|&(x, _)| x).min() else {
+        return format!("x = 0, y = 0, rule = {rule}\n!\n");
+    };
+    let max_x = cells.iter().map(|&(x, _)| x).max().unwrap();
+    let min_y = cells.iter().map(|&(_, y)| y).min().unwrap();
+    let max_y = cells.iter().map(|&(_, y)| y).max().unwrap();
+
+    let mut tokens = Vec::new();
+    let mut line_ends = 0;
+
+    for y in min_y..=max_y {
+        let mut runs: Vec<(u32, char)> = Vec::new();
+
+        for x in min_x..=max_x {
+            let tag = if cells.contains(&(x, y)) { 'o' } else { 'b' };
+
+            match runs.last_mut() {
+                Some((count, last_tag)) if *last_tag == tag => *count += 1,
+                _ => runs.push((1, tag)),
+            }
+        }
+
+        if let Some((_, 'b')) = runs.last() {
+            runs.pop();
+        }
+
+        if runs.is_empty() {
+            line_ends += 1;
+            continue;
+        }
+
+        if line_ends > 0 {
+            tokens.push(run_token(line_ends, '$'));
+        }
+
+        tokens.extend(runs.into_iter().map(|(count, tag)| run_token(count, tag)));
+        line_ends = 1;
+    }
+
+    tokens.push("!".to_string());
+
+    let mut body = String::new();
+    let mut line_length = 0;
+
+    for token in tokens {
+        if line_length + token.len() > MAX_LINE_LENGTH {
+            body.push('\n');
+            line_length = 0;
+        }
+
+        line_length += token.len();
+        body.push_str(&token);
+    }
+
+    format!(
+        "x = {}, y = {}, rule = {rule}\n{body}\n",
+        max_x - min_x + 1,
+        max_y - min_y + 1
+    )
+}
+
+fn run_token(count: u32, tag: char) -> String {
+    if count == 1 {
+        tag.to_string()
+    } else {
+        format!("{count}{tag}")
+    }
+}
+
+pub struct Pattern {
+    pub width: i32,
+    pub height: i32,
+    pub cells: Vec<(i32, i32)>,
+}
+
+/// Decodes an RLE pattern, ignoring `#` comment lines.
+///
+/// ```
+/// use game_of_life::rle;
+///
+/// let glider = rle::decode("x = 3, y = 3\nbo$2bo$3o!").unwrap();
+/// assert_eq!((glider.width, glider.height), (3, 3));
+/// assert_eq!(glider.cells.len(), 5);
+/// ```
+pub fn decode(input: &str) -> Result<Pattern, String> {
+    let mut lines = input
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'));
+
+    let header = lines.next().ok_or("missing RLE header")?;
+    let (width, height) = parse_header(header)?;
+
+    let mut cells = Vec::new();
+    let mut x: i32 = 0;
+    let mut y: i32 = 0;
+    let mut count: Option<i32> = None;
+
+    'body: for line in lines {
+        for c in line.chars() {
+            if let Some(digit) = c.to_digit(10) {
+                count = Some(
+                    count
+                        .unwrap_or(0)
+                        .checked_mul(10)
+                        .and_then(|count| count.checked_add(digit as i32))
+                        .filter(|&count| count <= MAX_PATTERN_SIZE)
+                        .ok_or("run count too large")?,
+                );
+                continue;
+            }
+
+            let run = count.take().unwrap_or(1);
+
+            match c {
+                'b' => x = x.checked_add(run).ok_or("row too long")?,
+                'o' => {
+                    if x.checked_add(run).is_none_or(|end| end > width) || y >= height {
+                        return Err(format!(
+                            "cells at row {y} exceed the header size {width}x{height}"
+                        ));
+                    }
+
+                    cells.extend((x..x + run).map(|cell_x| (cell_x, y)));
+                    x += run;
+                }
+                '$' => {
+                    x = 0;
+                    y = y.checked_add(run).ok_or("too many rows")?;
+                }
+                '!' => break 'body,
+                c if c.is_whitespace() => {}
+                _ => return Err(format!("unexpected character `{c}` in RLE body")),
+            }
+        }
+    }
+
+    Ok(Pattern {
+        width,
+        height,
+        cells,
+    })
+}
+
+/// Decodes a pattern in either RLE or plaintext (`.cells`) format, telling them apart
+/// by their content: RLE has `#` comments and an `x = ...` header, plaintext has `!`
+/// comments and rows of `.` and `O`.
+///
+/// ```
+/// use game_of_life::rle;
+///
+/// let glider = rle::decode_any("#N Glider\nx = 3, y = 3\nbo$2bo$3o!").unwrap();
+/// assert_eq!(glider.cells.len(), 5);
+///
+/// let block = rle::decode_any("!Name: Block\nOO\nOO\n").unwrap();
+/// assert_eq!(block.cells.len(), 4);
+///
+/// let blinker = rle::decode_any("OOO\n").unwrap();
+/// assert_eq!((blinker.width, blinker.height), (3, 1));
+/// ```
+pub fn decode_any(input: &str) -> Result<Pattern, String> {
+    let first_line = input
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .unwrap_or_default();
+    let is_rle = first_line.starts_with('#')
+        || first_line
+            .strip_prefix('x')
+            .is_some_and(|rest| rest.trim_start().starts_with('='));
+
+    if is_rle {
+        decode(input)
+    } else {
+        crate::cells::decode(input)
+    }
+}
+
+fn parse_header(header: &str) -> Result<(i32, i32), String> {
+    let mut width = None;
+    let mut height = None;
+
+    for field in header.split(',') {
+        let Some((key, value)) = field.split_once('=') else {
+            return Err(format!("invalid RLE header field `{}`", field.trim()));
+        };
+
+        let target = match key.trim() {
+            "x" => &mut width,
+            "y" => &mut height,
+            _ => continue,
+        };
+
+        match value.trim().parse::<i32>() {
+            Ok(dimension) if dimension > MAX_PATTERN_SIZE => {
+                return Err(format!(
+                    "RLE header value {dimension} is larger than {MAX_PATTERN_SIZE}"
+                ));
+            }
+            Ok(dimension) if dimension >= 0 => *target = Some(dimension),
+            _ => return Err(format!("invalid RLE header value `{}`", value.trim())),
+        }
+    }
+
+    match (width, height) {
+        (Some(width), Some(height)) => Ok((width, height)),
+        _ => Err(format!("RLE header `{header}` is missing x or y")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_run_counts_that_overflow() {
+        let err = decode("x = 3, y = 1\n99999999999o!").err();
+        assert_eq!(err.as_deref(), Some("run count too large"));
+    }
+
+    #[test]
+    fn rejects_header_sizes_over_the_limit() {
+        assert!(decode("x = 2147483647, y = 1\no!").is_err());
+        assert!(decode(&format!("x = {MAX_PATTERN_SIZE}, y = 1\no!")).is_ok());
+    }
+
+    #[test]
+    fn rejects_runs_that_add_up_past_the_row() {
+        let body = format!("{MAX_PATTERN_SIZE}b").repeat(40_000);
+        assert!(decode(&format!("x = 1, y = 1\n{body}o!")).is_err());
+    }
+
+    #[test]
+    fn encodes_cells_cropped_to_their_bounding_box() {
+        let glider = HashSet::from([(11, 10), (12, 11), (10, 12), (11, 12), (12, 12)]);
+
+        assert_eq!(
+            encode(&glider, "B3/S23"),
+            "x = 3, y = 3, rule = B3/S23\nbo$2bo$3o!\n"
+        );
+        assert_eq!(
+            encode(&HashSet::new(), "B3/S23"),
+            "x = 0, y = 0, rule = B3/S23\n!\n"
+        );
+    }
+
+    #[test]
+    fn decodes_what_it_encodes() {
+        let cells = HashSet::from([(0, 0), (3, 0), (1, 2), (2, 2), (3, 4)]);
+        let pattern = decode(&encode(&cells, "B3/S23")).unwrap();
+
+        assert_eq!((pattern.width, pattern.height), (4, 5));
+        assert_eq!(pattern.cells.into_iter().collect::<HashSet<_>>(), cells);
+    }
+
+    #[test]
+    fn skips_comments_and_rejects_stray_characters() {
+        let glider = decode("#N Glider\n#C a comment\nx = 3, y = 3\nbo$2bo$3o!").unwrap();
+
+        assert_eq!(glider.cells, [(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)]);
+        assert!(decode("x = 3, y = 3\nbo$2bz$3o!").is_err());
+        assert!(decode("bo$2bo$3o!").is_err());
+    }
+}
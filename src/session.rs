@@ -0,0 +1,26 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize)]
+pub struct Session {
+    pub cols: i32,
+    pub rows: i32,
+    pub cells: Vec<(i32, i32)>,
+    pub generation: u64,
+    pub rule: String,
+    pub tick_seconds: f32,
+    pub camera: CameraState,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct CameraState {
+    pub translation: [f32; 3],
+    pub scale: f32,
+}
+
+pub fn encode(session: &Session) -> Result<String, String> {
+    serde_json::to_string_pretty(session).map_err(|err| err.to_string())
+}
+
+pub fn decode(input: &str) -> Result<Session, String> {
+    serde_json::from_str(input).map_err(|err| err.to_string())
+}
@@ -0,0 +1,388 @@
+//! Optionally draws the board through a shader that gives every square rounded,
+//! anti-aliased edges, which holds up much better than hard-edged sprites when zoomed
+//! in. The squares keep their usual colors, so nothing else needs to know about it: in
+//! sprite mode the sprite colors are copied into an image, while texture mode hands its
+//! board image straight to the shader.
+
+use super::key_bindings::{Action, KeyBindings};
+use super::{CellShape, CellSize, GridConfig, Square, ctrl_pressed};
+use bevy::prelude::*;
+use bevy::render::render_asset::RenderAssetUsages;
+use bevy::render::render_resource::{
+    AsBindGroup, Extent3d, ShaderRef, TextureDimension, TextureFormat,
+};
+use bevy::render::renderer::RenderDevice;
+use bevy::sprite::{AlphaMode2d, Material2d, Material2dPlugin};
+
+const SMOOTH_CELLS_SHADER: Handle<Shader> =
+    Handle::weak_from_u128(0x7f3a_91c2_4d5e_4b08_a6f1_2c9e_83d0_b547);
+
+const SQUARE_CORNER_RADIUS: f32 = 0.15;
+const CIRCLE_CORNER_RADIUS: f32 = 0.5;
+
+const SMOOTH_CELLS_WGSL: &str = r#"
+#import bevy_sprite::mesh2d_vertex_output::VertexOutput
+
+// Columns and rows of the board, then the corner radius as a fraction of a square.
+@group(2) @binding(0) var<uniform> grid: vec4<f32>;
+@group(2) @binding(1) var board: texture_2d<f32>;
+
+// Gap left between neighboring squares, as a fraction of a square.
+const MARGIN: f32 = 0.04;
+
+@fragment
+fn fragment(in: VertexOutput) -> @location(0) vec4<f32> {
+    let position = in.uv * grid.xy;
+    let cell = clamp(vec2<i32>(floor(position)), vec2<i32>(0), vec2<i32>(grid.xy) - 1);
+    let color = textureLoad(board, cell, 0);
+
+    // Signed distance to the edge of a rounded square filling the cell, in squares.
+    let radius = grid.z * (1.0 - 2.0 * MARGIN);
+    let corner = abs(fract(position) - 0.5) - (0.5 - MARGIN - radius);
+    let distance = length(max(corner, vec2<f32>(0.0))) + min(max(corner.x, corner.y), 0.0) - radius;
+
+    // Coverage over a one pixel wide edge. Once squares are only a few pixels across the
+    // edges cannot be resolved anyway, so they fade back into solid squares.
+    let pixel = max(fwidth(position.x), 1e-6);
+    let coverage = clamp(0.5 - distance / pixel, 0.0, 1.0);
+    let detail = smoothstep(0.5, 0.2, pixel);
+
+    return vec4<f32>(color.rgb, color.a * mix(1.0, coverage, detail));
+}
+"#;
+
+/// Whether the board is drawn through the smooth cells shader, and whether the GPU can
+/// hold the whole board in one texture for it.
+#[derive(Resource)]
+pub(super) struct SmoothCells {
+    pub(super) enabled: bool,
+    supported: bool,
+}
+
+/// The image the shader reads square colors from, one texel per square.
+#[derive(Resource)]
+pub(super) struct SmoothCellsImage(pub(super) Handle<Image>);
+
+/// Entities that draw the board without the shader, hidden while it is in use.
+#[derive(Component)]
+pub(super) struct PlainBoard;
+
+#[derive(Component)]
+struct SmoothBoard(Handle<SmoothCellsMaterial>);
+
+#[derive(Asset, TypePath, AsBindGroup, Clone)]
+struct SmoothCellsMaterial {
+    #[uniform(0)]
+    grid: Vec4,
+    #[texture(1)]
+    board: Handle<Image>,
+}
+
+impl Material2d for SmoothCellsMaterial {
+    fn fragment_shader() -> ShaderRef {
+        SMOOTH_CELLS_SHADER.into()
+    }
+
+    fn alpha_mode(&self) -> AlphaMode2d {
+        AlphaMode2d::Blend
+    }
+}
+
+/// Adds the smooth cells rendering. With `copy_sprites`, the colors of the square sprites
+/// are copied into the image the shader reads; otherwise the render mode must insert a
+/// [`SmoothCellsImage`] during startup and keep it up to date.
+pub(super) struct SmoothCellsPlugin {
+    pub(super) enabled: bool,
+    pub(super) copy_sprites: bool,
+}
+
+impl Plugin for SmoothCellsPlugin {
+    fn build(&self, app: &mut App) {
+        app.world_mut().resource_mut::<Assets<Shader>>().insert(
+            &SMOOTH_CELLS_SHADER,
+            Shader::from_wgsl(SMOOTH_CELLS_WGSL, file!()),
+        );
+
+        app.add_plugins(Material2dPlugin::<SmoothCellsMaterial>::default())
+            .insert_resource(SmoothCells {
+                enabled: self.enabled,
+                supported: false,
+            })
+            .add_systems(Startup, check_smooth_cells_support)
+            .add_systems(PostStartup, spawn_smooth_board)
+            .add_systems(
+                Update,
+                (
+                    handle_smooth_cells_toggle,
+                    apply_smooth_cells.after(handle_smooth_cells_toggle),
+                    refresh_smooth_cells_material,
+                ),
+            );
+
+        if self.copy_sprites {
+            app.add_systems(
+                Startup,
+                create_smooth_cells_image.after(check_smooth_cells_support),
+            )
+            .add_systems(
+                Update,
+                copy_sprite_colors
+                    .after(apply_smooth_cells)
+                    .run_if(|smooth_cells: Res<SmoothCells>| smooth_cells.enabled),
+            );
+        }
+    }
+}
+
+/// The shader reads one texel per square, so boards with more columns or rows than the
+/// GPU's largest texture keep the plain squares. The render device is only known once
+/// the renderer has started, after plugins are built.
+fn check_smooth_cells_support(
+    render_device: Option<Res<RenderDevice>>,
+    grid_config: Res<GridConfig>,
+    mut smooth_cells: ResMut<SmoothCells>,
+) {
+    let Some(render_device) = render_device else {
+        smooth_cells.enabled = false;
+        return;
+    };
+
+    let max_size = render_device.limits().max_texture_dimension_2d;
+    smooth_cells.supported = grid_config.cols.max(grid_config.rows) as u32 <= max_size;
+
+    if smooth_cells.enabled && !smooth_cells.supported {
+        warn!(
+            "the board is larger than this GPU's largest texture of {max_size}x{max_size}, falling back to plain squares"
+        );
+        smooth_cells.enabled = false;
+    }
+}
+
+fn create_smooth_cells_image(
+    mut commands: Commands,
+    mut images: ResMut<Assets<Image>>,
+    grid_config: Res<GridConfig>,
+    smooth_cells: Res<SmoothCells>,
+) {
+    if !smooth_cells.supported {
+        return;
+    }
+
+    let image = images.add(blank_image(&grid_config));
+
+    commands.insert_resource(SmoothCellsImage(image));
+}
+
+fn blank_image(grid_config: &GridConfig) -> Image {
+    Image::new_fill(
+        Extent3d {
+            width: grid_config.cols as u32,
+            height: grid_config.rows as u32,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        &[0, 0, 0, 0],
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::default(),
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+fn spawn_smooth_board(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<SmoothCellsMaterial>>,
+    smooth_cells: Res<SmoothCells>,
+    smooth_cells_image: Option<Res<SmoothCellsImage>>,
+    grid_config: Res<GridConfig>,
+    cell_size: Res<CellSize>,
+    cell_shape: Option<Res<CellShape>>,
+) {
+    if !smooth_cells.supported {
+        return;
+    }
+
+    let Some(smooth_cells_image) = smooth_cells_image else {
+        warn!("no board image for smooth cells");
+        return;
+    };
+
+    let material = materials.add(SmoothCellsMaterial {
+        grid: grid_uniform(&grid_config, cell_shape.as_deref()),
+        board: smooth_cells_image.0.clone(),
+    });
+
+    commands.spawn((
+        SmoothBoard(material.clone()),
+        Mesh2d(meshes.add(Rectangle::new(1., 1.))),
+        MeshMaterial2d(material),
+        board_transform(&grid_config, &cell_size),
+        if smooth_cells.enabled {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        },
+    ));
+}
+
+fn grid_uniform(grid_config: &GridConfig, cell_shape: Option<&CellShape>) -> Vec4 {
+    let corner_radius = match cell_shape {
+        Some(CellShape::Circle) => CIRCLE_CORNER_RADIUS,
+        _ => SQUARE_CORNER_RADIUS,
+    };
+
+    Vec4::new(
+        grid_config.cols as f32,
+        grid_config.rows as f32,
+        corner_radius,
+        0.,
+    )
+}
+
+fn board_transform(grid_config: &GridConfig, cell_size: &CellSize) -> Transform {
+    let geometry = grid_config.geometry(cell_size);
+
+    Transform::from_translation(Vec2::from(geometry.center()).extend(0.))
+        .with_scale(Vec2::from(geometry.size()).extend(1.))
+}
+
+fn handle_smooth_cells_toggle(
+    keys: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    mut smooth_cells: ResMut<SmoothCells>,
+) {
+    if ctrl_pressed(&keys) || !bindings.just_pressed(&keys, Action::SmoothCells) {
+        return;
+    }
+
+    if !smooth_cells.supported {
+        info!("the board is too large for this GPU to draw smooth cells");
+        return;
+    }
+
+    smooth_cells.enabled = !smooth_cells.enabled;
+    info!(
+        "smooth cells {}",
+        if smooth_cells.enabled { "on" } else { "off" }
+    );
+}
+
+#[allow(clippy::type_complexity)]
+fn apply_smooth_cells(
+    smooth_cells: Res<SmoothCells>,
+    cell_shape: Option<Res<CellShape>>,
+    grid_config: Res<GridConfig>,
+    mut materials: ResMut<Assets<SmoothCellsMaterial>>,
+    mut q_plain: Query<
+        (&mut Visibility, Ref<InheritedVisibility>),
+        (Or<(With<Square>, With<PlainBoard>)>, Without<SmoothBoard>),
+    >,
+    mut q_smooth: Query<(&mut Visibility, &SmoothBoard)>,
+) {
+    let shape_changed = cell_shape.as_ref().is_some_and(|shape| shape.is_changed());
+
+    if shape_changed {
+        for (_, smooth_board) in q_smooth.iter() {
+            if let Some(material) = materials.get_mut(&smooth_board.0) {
+                material.grid = grid_uniform(&grid_config, cell_shape.as_deref());
+            }
+        }
+    }
+
+    let (smooth, plain) = if smooth_cells.enabled {
+        (Visibility::Inherited, Visibility::Hidden)
+    } else {
+        (Visibility::Hidden, Visibility::Inherited)
+    };
+
+    // Squares spawned by growing the grid start out visible.
+    for (mut visibility, inherited) in q_plain.iter_mut() {
+        if smooth_cells.is_changed() || inherited.is_added() {
+            visibility.set_if_neq(plain);
+        }
+    }
+
+    for (mut visibility, _) in q_smooth.iter_mut() {
+        visibility.set_if_neq(smooth);
+    }
+}
+
+/// A material keeps using the texture its image had when the material was last prepared,
+/// so it has to be touched whenever the board image is rewritten.
+fn refresh_smooth_cells_material(
+    mut image_events: EventReader<AssetEvent<Image>>,
+    smooth_cells_image: Option<Res<SmoothCellsImage>>,
+    mut materials: ResMut<Assets<SmoothCellsMaterial>>,
+    q_smooth: Query<&SmoothBoard>,
+) {
+    let Some(smooth_cells_image) = smooth_cells_image else {
+        image_events.clear();
+        return;
+    };
+
+    let modified = image_events
+        .read()
+        .any(|event| event.is_modified(&smooth_cells_image.0));
+
+    if modified {
+        for smooth_board in q_smooth.iter() {
+            materials.get_mut(&smooth_board.0);
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn copy_sprite_colors(
+    smooth_cells: Res<SmoothCells>,
+    smooth_cells_image: Res<SmoothCellsImage>,
+    grid_config: Res<GridConfig>,
+    cell_size: Res<CellSize>,
+    cell_shape: Option<Res<CellShape>>,
+    mut images: ResMut<Assets<Image>>,
+    mut materials: ResMut<Assets<SmoothCellsMaterial>>,
+    q_squares: Query<(&Square, Ref<Sprite>)>,
+    mut q_smooth: Query<(&mut Transform, &SmoothBoard)>,
+) {
+    let Some(image) = images.get(&smooth_cells_image.0) else {
+        return;
+    };
+
+    // Growing the grid needs a bigger image and a bigger mesh to draw it on.
+    let resized =
+        image.width() != grid_config.cols as u32 || image.height() != grid_config.rows as u32;
+    // Sprites change color while the shader is off too, so turning it on repaints them all.
+    let repaint = resized || smooth_cells.is_changed();
+
+    // Borrowing the image mutably uploads it again, so leave it alone when nothing changed.
+    if !repaint && !q_squares.iter().any(|(_, sprite)| sprite.is_changed()) {
+        return;
+    }
+
+    let Some(image) = images.get_mut(&smooth_cells_image.0) else {
+        return;
+    };
+
+    if resized {
+        *image = blank_image(&grid_config);
+
+        for (mut transform, smooth_board) in q_smooth.iter_mut() {
+            *transform = board_transform(&grid_config, &cell_size);
+
+            if let Some(material) = materials.get_mut(&smooth_board.0) {
+                material.grid = grid_uniform(&grid_config, cell_shape.as_deref());
+            }
+        }
+    }
+
+    for (square, sprite) in q_squares.iter() {
+        if !(repaint || sprite.is_changed()) {
+            continue;
+        }
+
+        let index = (square.y * grid_config.cols + square.x) as usize * 4;
+
+        if let Some(texel) = image.data.get_mut(index..index + 4) {
+            texel.copy_from_slice(&sprite.color.to_srgba().to_u8_array());
+        }
+    }
+}
@@ -0,0 +1,128 @@
+//! Drawing a board as text, for watching a run over SSH or anywhere without a GPU.
+
+use crate::life::LifeBoard;
+
+/// Moves the cursor home and clears the screen, so each frame replaces the last.
+pub const CLEAR_SCREEN: &str = "\x1b[H\x1b[2J";
+
+/// Draws a window of a board with half-block characters, two rows of squares per line
+/// so that squares come out roughly square in most terminal fonts.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct TerminalRenderer {
+    pub x: i32,
+    pub y: i32,
+    pub width: i32,
+    pub height: i32,
+}
+
+impl TerminalRenderer {
+    /// A window of at most `width` by `height` squares over the middle of a `cols` by
+    /// `rows` board.
+    pub fn centered(cols: i32, rows: i32, width: i32, height: i32) -> Self {
+        let width = width.min(cols);
+        let height = height.min(rows);
+
+        TerminalRenderer {
+            x: (cols - width) / 2,
+            y: (rows - height) / 2,
+            width,
+            height,
+        }
+    }
+
+    /// Appends the window to `frame`, one line per two rows of squares.
+    ///
+    /// ```
+    /// use game_of_life::life::Board;
+    /// use game_of_life::terminal::TerminalRenderer;
+    ///
+    /// let mut board = Board::default();
+    /// board.load(3, 3, [(0, 0), (1, 1), (2, 1), (2, 2)]);
+    ///
+    /// let mut frame = String::new();
+    /// TerminalRenderer::centered(3, 3, 3, 3).render(&board, &mut frame);
+    /// assert_eq!(frame, "▀▄▄\n  ▀\n");
+    /// ```
+    pub fn render(&self, board: &dyn LifeBoard, frame: &mut String) {
+        for y in (self.y..self.y + self.height).step_by(2) {
+            for x in self.x..self.x + self.width {
+                let top = board.alive(x, y);
+                let bottom = y + 1 < self.y + self.height && board.alive(x, y + 1);
+
+                frame.push(match (top, bottom) {
+                    (true, true) => '█',
+                    (true, false) => '▀',
+                    (false, true) => '▄',
+                    (false, false) => ' ',
+                });
+            }
+
+            frame.push('\n');
+        }
+    }
+
+    /// Like [`TerminalRenderer::render`], but clears the screen first and ends with a
+    /// status line.
+    pub fn frame(&self, board: &dyn LifeBoard, status: &str) -> String {
+        let mut frame = String::from(CLEAR_SCREEN);
+        self.render(board, &mut frame);
+        frame.push_str(status);
+        frame.push('\n');
+
+        frame
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::life::Board;
+
+    fn board(cols: i32, rows: i32, cells: &[(i32, i32)]) -> Board {
+        let mut board = Board::default();
+        board.load(cols, rows, cells.iter().copied());
+        board
+    }
+
+    #[test]
+    fn centered_windows_fit_inside_the_board() {
+        assert_eq!(
+            TerminalRenderer::centered(10, 6, 80, 48),
+            TerminalRenderer {
+                x: 0,
+                y: 0,
+                width: 10,
+                height: 6,
+            }
+        );
+        assert_eq!(
+            TerminalRenderer::centered(10, 6, 4, 48),
+            TerminalRenderer {
+                x: 3,
+                y: 0,
+                width: 4,
+                height: 6,
+            }
+        );
+    }
+
+    #[test]
+    fn odd_heights_leave_the_last_line_without_a_bottom_half() {
+        let board = board(2, 4, &[(0, 2), (1, 3)]);
+        let renderer = TerminalRenderer::centered(2, 4, 2, 3);
+
+        let mut frame = String::new();
+        renderer.render(&board, &mut frame);
+
+        // (1, 3) is just below the window, so the last line only shows row 2.
+        assert_eq!(frame, "  \n▀ \n");
+    }
+
+    #[test]
+    fn frames_clear_the_screen_and_end_with_the_status() {
+        let board = board(2, 2, &[(0, 0), (1, 1)]);
+        let frame = TerminalRenderer::centered(2, 2, 2, 2).frame(&board, "generation 7");
+
+        assert_eq!(frame, format!("{CLEAR_SCREEN}▀▄\ngeneration 7\n"));
+    }
+}
@@ -0,0 +1,463 @@
+//! Draws the whole board as a single image with one texel per square, for boards too
+//! large to give every square its own sprite. The dense board is the only copy of
+//! the cells, so editing is limited to toggling and painting squares, random fill and
+//! clearing.
+
+use super::key_bindings::{Action, KeyBindings};
+use super::{
+    ActiveAutomaton, ActiveBoundary, ActiveNeighborhood, ActiveRuleset, BoardDiedOut, BrushSize,
+    CellSize, CliArgs, EditingSet, FillDensity, Generation, GridConfig, HudText, LastPaintedSquare,
+    LoadSource, MAX_STEPS_PER_TICK, MainCamera, OngoingSet, PauseAtGeneration, Paused, PlacingSet,
+    PointerOverUi, Population, RuleChance, SeedPattern, SimulationRng, StepCount, StepsPerTick,
+    Theme, TickRate, TwoStateOnly, add_shared_setup, apply_pattern_rule, brush_squares,
+    brush_stroke, cells_bounds, chance_death_state, cursor_to_square, dying_color, dying_stages,
+    follow_live_cells, following_live_cells, handle_extinction, handle_follow_toggle,
+    handle_step_count, pause_at_target, random_cells, read_centered_pattern, record_population,
+    roll_rule_chance, shift_pressed, smooth, spawn_population_graph_camera, update_hud_text,
+};
+use bevy::image::ImageSampler;
+use bevy::prelude::*;
+use bevy::render::render_asset::RenderAssetUsages;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+use bevy::window::PrimaryWindow;
+use game_of_life::life::{self, Automaton, CellState};
+use game_of_life::patterns;
+use std::collections::HashSet;
+use std::time::Duration;
+
+#[derive(Resource)]
+struct BoardTexture(Handle<Image>);
+
+/// The cells of the board. The texture shows dying states, so it always steps the dense
+/// board rather than the bitset.
+#[derive(Resource, Default)]
+struct DenseBoard(life::Board);
+
+impl DenseBoard {
+    /// Steps the board one generation under the active rule, then applies
+    /// [`RuleChance`] to the births and survivals it made.
+    fn step(
+        &mut self,
+        boundary: &ActiveBoundary,
+        neighborhood: &ActiveNeighborhood,
+        ruleset: &ActiveRuleset,
+        automaton: &ActiveAutomaton,
+        rule_chance: &RuleChance,
+        rng: &mut SimulationRng,
+    ) {
+        let previous: HashSet<_> = if rule_chance.is_exact() {
+            HashSet::new()
+        } else {
+            self.0.live_cells().collect()
+        };
+
+        match automaton.0 {
+            Automaton::Life => self.0.step(boundary.0, neighborhood.0, &ruleset.0),
+            Automaton::BriansBrain => self.0.step_brians_brain(boundary.0, neighborhood.0),
+        }
+
+        if !rule_chance.is_exact() {
+            let next = self.0.live_cells().collect();
+            let (unborn, killed) = roll_rule_chance(&previous, &next, &[], rule_chance, &mut rng.0);
+            let death_state = chance_death_state(automaton.0, &ruleset.0);
+
+            for (cells, state) in [(unborn, CellState::Dead), (killed, death_state)] {
+                for (x, y) in cells {
+                    self.0.set(x, y, state);
+                }
+            }
+        }
+    }
+}
+
+/// Set whenever the board buffers change, so the image is only rewritten then.
+#[derive(Resource)]
+struct BoardDirty(bool);
+
+pub(super) fn run(mut cli_args: CliArgs) {
+    let mut app = App::new();
+    add_shared_setup(&mut app, &mut cli_args);
+
+    app.add_plugins(smooth::SmoothCellsPlugin {
+        enabled: cli_args.smooth,
+        copy_sprites: false,
+    })
+    .init_resource::<DenseBoard>()
+    .insert_resource(BoardDirty(true))
+    .add_systems(Startup, setup)
+    .add_systems(FixedUpdate, step_board.in_set(OngoingSet))
+    .add_systems(
+        Update,
+        (
+            paint_board_texture,
+            update_hud_text.after(paint_board_texture),
+            live_cell_bounds
+                .pipe(follow_live_cells)
+                .run_if(following_live_cells)
+                .after(handle_follow_toggle),
+            handle_clear,
+            record_population.after(paint_board_texture),
+        ),
+    )
+    .add_systems(Update, handle_random_fill.in_set(PlacingSet))
+    .add_systems(Update, handle_click.in_set(EditingSet))
+    .add_systems(
+        Update,
+        (
+            handle_single_step,
+            handle_step_count.after(handle_single_step),
+            handle_extinction.after(paint_board_texture),
+        )
+            .in_set(OngoingSet),
+    )
+    .run();
+}
+
+#[allow(clippy::too_many_arguments)]
+fn setup(
+    mut commands: Commands,
+    mut images: ResMut<Assets<Image>>,
+    mut gizmo_config_store: ResMut<GizmoConfigStore>,
+    mut board: ResMut<DenseBoard>,
+    grid_config: Res<GridConfig>,
+    cell_size: Res<CellSize>,
+    load_source: Res<LoadSource>,
+    seed_pattern: Res<SeedPattern>,
+    (mut ruleset, mut fixed_time, neighborhood, automaton): (
+        ResMut<ActiveRuleset>,
+        ResMut<Time<Fixed>>,
+        Res<ActiveNeighborhood>,
+        Res<ActiveAutomaton>,
+    ),
+    two_state_only: Res<TwoStateOnly>,
+) {
+    let mut cells = HashSet::new();
+
+    if let Some(source) = &load_source.0 {
+        match read_centered_pattern(source, &grid_config) {
+            Ok(pattern) => {
+                apply_pattern_rule(
+                    pattern.rule,
+                    neighborhood.0,
+                    automaton.0,
+                    two_state_only.0,
+                    &mut ruleset.0,
+                );
+                if let Some(tick_seconds) = pattern.tick_seconds {
+                    fixed_time.set_timestep(Duration::from_secs_f32(tick_seconds));
+                }
+                cells = pattern.cells;
+                info!("loaded pattern from {source}");
+            }
+            Err(err) => error!("failed to load pattern from {source}: {err}"),
+        }
+    }
+
+    if let Some((index, (x, y))) = seed_pattern.0 {
+        cells.extend(
+            patterns::PATTERNS[index]
+                .cells
+                .iter()
+                .map(|&(dx, dy)| (x + dx, y + dy)),
+        );
+    }
+
+    board.0.load(grid_config.cols, grid_config.rows, cells);
+
+    let mut image = Image::new_fill(
+        Extent3d {
+            width: grid_config.cols as u32,
+            height: grid_config.rows as u32,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        &[0, 0, 0, 0],
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::default(),
+    );
+    image.sampler = ImageSampler::nearest();
+    let image = images.add(image);
+    let geometry = grid_config.geometry(&cell_size);
+
+    commands.spawn((Camera2d, MainCamera, IsDefaultUiCamera));
+    spawn_population_graph_camera(&mut commands, &mut gizmo_config_store);
+    commands.spawn((
+        smooth::PlainBoard,
+        Sprite {
+            image: image.clone(),
+            custom_size: Some(Vec2::from(geometry.size())),
+            ..default()
+        },
+        Transform::from_translation(Vec2::from(geometry.center()).extend(0.)),
+    ));
+    commands.insert_resource(smooth::SmoothCellsImage(image.clone()));
+    commands.insert_resource(BoardTexture(image));
+    commands.spawn((
+        HudText,
+        Interaction::default(),
+        Text::default(),
+        TextColor(Color::WHITE),
+        BackgroundColor(Color::BLACK.with_alpha(0.6)),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(10.),
+            left: Val::Px(10.),
+            padding: UiRect::all(Val::Px(5.)),
+            ..default()
+        },
+    ));
+}
+
+pub(super) fn state_color(theme: &Theme, state: CellState, stages: u8) -> Color {
+    match state {
+        CellState::Dead => theme.dead,
+        CellState::Alive => theme.alive,
+        CellState::Dying(stage) => dying_color(theme, stage, stages),
+    }
+}
+
+/// Writes one RGBA texel per square into `pixels`, which must hold four bytes for each
+/// entry of `states`. `stages` is how many dying states the rule has.
+pub(super) fn paint(theme: &Theme, states: &[CellState], stages: u8, pixels: &mut [u8]) {
+    // Dead and alive come first, followed by one color for each dying stage.
+    let palette: Vec<[u8; 4]> = [CellState::Dead, CellState::Alive]
+        .into_iter()
+        .chain((1..=stages).map(CellState::Dying))
+        .map(|state| state_color(theme, state, stages).to_srgba().to_u8_array())
+        .collect();
+
+    for (texel, &state) in pixels.chunks_exact_mut(4).zip(states) {
+        let index = match state {
+            CellState::Dead => 0,
+            CellState::Alive => 1,
+            CellState::Dying(stage) => 1 + stage.min(stages) as usize,
+        };
+
+        texel.copy_from_slice(&palette[index]);
+    }
+}
+
+fn paint_board_texture(
+    mut dirty: ResMut<BoardDirty>,
+    board: Res<DenseBoard>,
+    board_texture: Option<Res<BoardTexture>>,
+    mut images: ResMut<Assets<Image>>,
+    theme: Res<Theme>,
+    mut population: ResMut<Population>,
+    (ruleset, automaton): (Res<ActiveRuleset>, Res<ActiveAutomaton>),
+) {
+    let Some(board_texture) = board_texture else {
+        return;
+    };
+
+    if !dirty.0 {
+        return;
+    }
+
+    let Some(image) = images.get_mut(&board_texture.0) else {
+        warn!("board texture is missing");
+        return;
+    };
+
+    let _span = info_span!("paint_board_texture").entered();
+    paint(
+        &theme,
+        board.0.states(),
+        dying_stages(automaton.0, &ruleset.0),
+        &mut image.data,
+    );
+    population.set_if_neq(Population(board.0.live_cells().count()));
+    dirty.0 = false;
+}
+
+fn live_cell_bounds(board: Res<DenseBoard>) -> Option<((i32, i32), (i32, i32))> {
+    cells_bounds(board.0.live_cells())
+}
+
+#[allow(clippy::too_many_arguments)]
+fn step_board(
+    mut board: ResMut<DenseBoard>,
+    mut dirty: ResMut<BoardDirty>,
+    mut generation: ResMut<Generation>,
+    (mut paused, mut pause_at): (ResMut<Paused>, ResMut<PauseAtGeneration>),
+    steps_per_tick: Res<StepsPerTick>,
+    boundary: Res<ActiveBoundary>,
+    (ruleset, neighborhood, automaton): (
+        Res<ActiveRuleset>,
+        Res<ActiveNeighborhood>,
+        Res<ActiveAutomaton>,
+    ),
+    real_time: Res<Time<Real>>,
+    mut tick_rate: ResMut<TickRate>,
+    (rule_chance, mut rng): (Res<RuleChance>, ResMut<SimulationRng>),
+    mut died_out: EventWriter<BoardDiedOut>,
+) {
+    if paused.0 {
+        tick_rate.last_tick = None;
+        return;
+    }
+
+    let mut steps = steps_per_tick.0.clamp(1, MAX_STEPS_PER_TICK);
+    if let Some(target) = pause_at.0 {
+        steps = steps.min(target.saturating_sub(generation.0) as u32);
+    }
+    let _span = info_span!("neighbor_scan", steps).entered();
+
+    let had_live_cells = board.0.live_cells().next().is_some();
+
+    for _ in 0..steps {
+        board.step(
+            &boundary,
+            &neighborhood,
+            &ruleset,
+            &automaton,
+            &rule_chance,
+            &mut rng,
+        );
+    }
+
+    generation.0 += steps as u64;
+    if had_live_cells && board.0.live_cells().next().is_none() {
+        died_out.send(BoardDiedOut);
+    }
+    pause_at_target(generation.0, &mut paused, &mut pause_at);
+    dirty.0 = true;
+    tick_rate.record_tick(real_time.elapsed());
+}
+
+#[allow(clippy::too_many_arguments)]
+fn handle_single_step(
+    keys: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    paused: Res<Paused>,
+    step_count: Res<StepCount>,
+    mut board: ResMut<DenseBoard>,
+    mut dirty: ResMut<BoardDirty>,
+    mut generation: ResMut<Generation>,
+    boundary: Res<ActiveBoundary>,
+    (ruleset, neighborhood, automaton): (
+        Res<ActiveRuleset>,
+        Res<ActiveNeighborhood>,
+        Res<ActiveAutomaton>,
+    ),
+    (rule_chance, mut rng): (Res<RuleChance>, ResMut<SimulationRng>),
+) {
+    if !paused.0 || step_count.0.is_some() || !bindings.just_pressed(&keys, Action::Step) {
+        return;
+    }
+
+    board.step(
+        &boundary,
+        &neighborhood,
+        &ruleset,
+        &automaton,
+        &rule_chance,
+        &mut rng,
+    );
+
+    generation.0 += 1;
+    dirty.0 = true;
+}
+
+#[allow(clippy::too_many_arguments)]
+fn handle_click(
+    mouse_button: Res<ButtonInput<MouseButton>>,
+    keys: Res<ButtonInput<KeyCode>>,
+    q_window: Query<&Window, With<PrimaryWindow>>,
+    q_camera: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+    grid_config: Res<GridConfig>,
+    cell_size: Res<CellSize>,
+    pointer_over_ui: Res<PointerOverUi>,
+    mut last_painted_square: ResMut<LastPaintedSquare>,
+    mut board: ResMut<DenseBoard>,
+    mut dirty: ResMut<BoardDirty>,
+    brush_size: Res<BrushSize>,
+) {
+    if pointer_over_ui.0 {
+        return;
+    }
+
+    let drag_paint = shift_pressed(&keys);
+
+    let dragging = drag_paint && mouse_button.pressed(MouseButton::Left);
+
+    if !mouse_button.just_pressed(MouseButton::Left) && !dragging {
+        return;
+    }
+
+    if mouse_button.just_pressed(MouseButton::Left) {
+        last_painted_square.0 = None;
+    }
+
+    let (Ok((camera, camera_transform)), Ok(window)) =
+        (q_camera.get_single(), q_window.get_single())
+    else {
+        warn!("no camera or primary window to place squares with");
+        return;
+    };
+
+    let Some(current) =
+        cursor_to_square(window, camera, camera_transform, &grid_config, &cell_size)
+    else {
+        last_painted_square.0 = None;
+        return;
+    };
+
+    let side = brush_size.side();
+
+    if drag_paint {
+        let from = last_painted_square.0.unwrap_or(current);
+
+        for (x, y) in brush_stroke(from, current, side, &grid_config) {
+            board.0.set(x, y, CellState::Alive);
+        }
+
+        last_painted_square.0 = Some(current);
+    } else {
+        let state = match board.0.state(current.0, current.1) {
+            CellState::Alive => CellState::Dead,
+            _ => CellState::Alive,
+        };
+
+        for (x, y) in brush_squares(current, side, &grid_config) {
+            board.0.set(x, y, state);
+        }
+    }
+
+    dirty.0 = true;
+}
+
+fn handle_random_fill(
+    keys: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    fill_density: Res<FillDensity>,
+    mut rng: ResMut<SimulationRng>,
+    mut board: ResMut<DenseBoard>,
+    mut dirty: ResMut<BoardDirty>,
+    grid_config: Res<GridConfig>,
+) {
+    if !bindings.just_pressed(&keys, Action::Random) {
+        return;
+    }
+
+    let cells = random_cells(&grid_config, fill_density.0, &mut rng.0);
+    board.0.load(grid_config.cols, grid_config.rows, cells);
+    dirty.0 = true;
+}
+
+fn handle_clear(
+    keys: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    mut board: ResMut<DenseBoard>,
+    mut dirty: ResMut<BoardDirty>,
+    mut generation: ResMut<Generation>,
+    grid_config: Res<GridConfig>,
+) {
+    if !bindings.just_pressed(&keys, Action::Clear) {
+        return;
+    }
+
+    board.0.load(grid_config.cols, grid_config.rows, []);
+    generation.0 = 0;
+    dirty.0 = true;
+}